@@ -0,0 +1,51 @@
+use anchor_lang::{AccountDeserialize, Result};
+
+use crate::{
+    Config, Factory, Farm, LimitOrder, MintRegistry, Oracle, PoolKey, RegistryEntry,
+    StakePosition,
+};
+
+/// Decodes a `Config` account, discriminator included.
+pub fn decode_config(data: &[u8]) -> Result<Config> {
+    Config::try_deserialize(&mut &data[..])
+}
+
+/// Decodes the `Factory` account, discriminator included.
+pub fn decode_factory(data: &[u8]) -> Result<Factory> {
+    Factory::try_deserialize(&mut &data[..])
+}
+
+/// Decodes an `Oracle` account, discriminator included.
+pub fn decode_oracle(data: &[u8]) -> Result<Oracle> {
+    Oracle::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a `PoolKey` account, discriminator included.
+pub fn decode_pool_key(data: &[u8]) -> Result<PoolKey> {
+    PoolKey::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a `MintRegistry` account, discriminator included.
+pub fn decode_mint_registry(data: &[u8]) -> Result<MintRegistry> {
+    MintRegistry::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a `RegistryEntry` account, discriminator included.
+pub fn decode_registry_entry(data: &[u8]) -> Result<RegistryEntry> {
+    RegistryEntry::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a `Farm` account, discriminator included.
+pub fn decode_farm(data: &[u8]) -> Result<Farm> {
+    Farm::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a `StakePosition` account, discriminator included.
+pub fn decode_stake_position(data: &[u8]) -> Result<StakePosition> {
+    StakePosition::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a `LimitOrder` account, discriminator included.
+pub fn decode_limit_order(data: &[u8]) -> Result<LimitOrder> {
+    LimitOrder::try_deserialize(&mut &data[..])
+}
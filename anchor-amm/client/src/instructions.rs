@@ -0,0 +1,183 @@
+use anchor_amm::{accounts, instruction};
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::Instruction, sysvar},
+    system_program, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{associated_token, metadata::mpl_token_metadata};
+
+use crate::{
+    pda::{metadata_address, PoolAddresses},
+    InitArgs, PROGRAM_ID,
+};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `initialize`: creates the pool at `pool`, which must have been derived from `args.seed`.
+pub fn initialize(pool: &PoolAddresses, admin: Pubkey, args: InitArgs) -> Instruction {
+    build(
+        accounts::Initialize {
+            admin,
+            token_x_mint: pool.token_x_mint,
+            token_y_mint: pool.token_y_mint,
+            lp_token_mint: pool.lp_token_mint,
+            config: pool.config,
+            oracle: pool.oracle,
+            pool_token_x_vault: pool.pool_token_x_vault,
+            pool_token_y_vault: pool.pool_token_y_vault,
+            token_x_metadata: metadata_address(&pool.token_x_mint).0,
+            token_y_metadata: metadata_address(&pool.token_y_mint).0,
+            lp_token_metadata: metadata_address(&pool.lp_token_mint).0,
+            token_x_program: pool.token_x_program,
+            token_y_program: pool.token_y_program,
+            token_program: pool.token_program,
+            associated_token_program: associated_token::ID,
+            metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::Initialize { args },
+    )
+}
+
+/// `deposit`: mints `lp_amount` LP tokens to `depositor` for at most `max_x` and `max_y`.
+/// On the first deposit `lp_amount` is the minimum LP amount accepted instead.
+pub fn deposit(
+    pool: &PoolAddresses,
+    depositor: Pubkey,
+    lp_amount: u64,
+    max_x: u64,
+    max_y: u64,
+    expires_at: Option<i64>,
+) -> Instruction {
+    build(
+        accounts::Deposit {
+            depositor,
+            token_x_mint: pool.token_x_mint,
+            token_y_mint: pool.token_y_mint,
+            config: pool.config,
+            oracle: pool.oracle,
+            pool_token_x_vault: pool.pool_token_x_vault,
+            pool_token_y_vault: pool.pool_token_y_vault,
+            lp_token_mint: pool.lp_token_mint,
+            depositor_token_x_account: pool.user_token_account(&depositor, &pool.token_x_mint),
+            depositor_token_y_account: pool.user_token_account(&depositor, &pool.token_y_mint),
+            depositor_lp_token_account: pool
+                .user_token_account(&depositor, &pool.lp_token_mint),
+            locked_lp_token_account: pool.user_token_account(&pool.config, &pool.lp_token_mint),
+            token_x_program: pool.token_x_program,
+            token_y_program: pool.token_y_program,
+            token_program: pool.token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::Deposit {
+            lp_claim_amount: lp_amount,
+            max_x,
+            max_y,
+            expires_at,
+        },
+    )
+}
+
+/// `withdraw`: burns `lp_amount` LP tokens of `withdrawer` for at least `min_x` and `min_y`.
+pub fn withdraw(
+    pool: &PoolAddresses,
+    withdrawer: Pubkey,
+    lp_amount: u64,
+    min_x: u64,
+    min_y: u64,
+    expires_at: Option<i64>,
+) -> Instruction {
+    build(
+        accounts::Withdraw {
+            withdrawer,
+            token_x_mint: pool.token_x_mint,
+            token_y_mint: pool.token_y_mint,
+            config: pool.config,
+            oracle: pool.oracle,
+            lp_token_mint: pool.lp_token_mint,
+            pool_token_x_vault: pool.pool_token_x_vault,
+            pool_token_y_vault: pool.pool_token_y_vault,
+            withdrawer_token_x_account: pool.user_token_account(&withdrawer, &pool.token_x_mint),
+            withdrawer_token_y_account: pool.user_token_account(&withdrawer, &pool.token_y_mint),
+            withdrawer_lp_token_account: pool
+                .user_token_account(&withdrawer, &pool.lp_token_mint),
+            token_x_program: pool.token_x_program,
+            token_y_program: pool.token_y_program,
+            token_program: pool.token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::Withdraw {
+            lp_burn_amount: lp_amount,
+            min_x,
+            min_y,
+            expires_at,
+        },
+    )
+}
+
+/// `swap`: sells `amount_in` of token X (`is_token_x`) or token Y for at least `min_amount_out`.
+pub fn swap(
+    pool: &PoolAddresses,
+    swapper: Pubkey,
+    is_token_x: bool,
+    amount_in: u64,
+    min_amount_out: u64,
+    expires_at: Option<i64>,
+) -> Instruction {
+    build(
+        swap_accounts(pool, swapper),
+        instruction::Swap {
+            is_token_x,
+            amount_in,
+            min_amount_out,
+            expires_at,
+        },
+    )
+}
+
+/// `swap_exact_out`: buys exactly `amount_out` for at most `max_amount_in` of the other token.
+pub fn swap_exact_out(
+    pool: &PoolAddresses,
+    swapper: Pubkey,
+    is_token_x: bool,
+    amount_out: u64,
+    max_amount_in: u64,
+    expires_at: Option<i64>,
+) -> Instruction {
+    build(
+        swap_accounts(pool, swapper),
+        instruction::SwapExactOut {
+            is_token_x,
+            amount_out,
+            max_amount_in,
+            expires_at,
+        },
+    )
+}
+
+fn swap_accounts(pool: &PoolAddresses, swapper: Pubkey) -> accounts::Swap {
+    accounts::Swap {
+        token_pair_swapper: swapper,
+        token_x_mint: pool.token_x_mint,
+        token_y_mint: pool.token_y_mint,
+        config: pool.config,
+        oracle: pool.oracle,
+        lp_token_mint: pool.lp_token_mint,
+        pool_token_x_vault: pool.pool_token_x_vault,
+        pool_token_y_vault: pool.pool_token_y_vault,
+        swapper_token_x_account: pool.user_token_account(&swapper, &pool.token_x_mint),
+        swapper_token_y_account: pool.user_token_account(&swapper, &pool.token_y_mint),
+        token_x_program: pool.token_x_program,
+        token_y_program: pool.token_y_program,
+        associated_token_program: associated_token::ID,
+        system_program: system_program::ID,
+    }
+}
@@ -0,0 +1,16 @@
+//! Off-chain companion of the `anchor-amm` program.
+//!
+//! - [`pda`]: addresses of the pool accounts.
+//! - [`instructions`]: builders for `initialize`, `deposit`, `withdraw`, `swap` and `swap_exact_out`.
+//! - [`accounts`]: decoders for the program accounts.
+//! - [`quote`]: trade quotes computed with the same code the program runs.
+
+pub mod accounts;
+pub mod instructions;
+pub mod pda;
+pub mod quote;
+
+pub use anchor_amm::{
+    helpers::curve::SwapQuote, Config, CurveType, Factory, Farm, InitArgs, LimitOrder, MintRegistry,
+    Oracle, PoolKey, RegistryEntry, StakePosition, ID as PROGRAM_ID,
+};
@@ -0,0 +1,177 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    metadata::mpl_token_metadata::accounts::Metadata,
+};
+
+use crate::{Config, PoolKey, PROGRAM_ID};
+
+/// `["config", seed.to_le_bytes()]`
+pub fn config_address(seed: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config", &seed.to_le_bytes()], &PROGRAM_ID)
+}
+
+/// `["factory"]`
+pub fn factory_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"factory"], &PROGRAM_ID)
+}
+
+/// `["lp", config]`
+pub fn lp_token_mint_address(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"lp", config.as_ref()], &PROGRAM_ID)
+}
+
+/// `["oracle", config]`
+pub fn oracle_address(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"oracle", config.as_ref()], &PROGRAM_ID)
+}
+
+/// `["metadata", metadata_program, mint]`, under the Metaplex token metadata program.
+pub fn metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Metadata::find_pda(mint)
+}
+
+/// `["farm", config, reward_mint]`
+pub fn farm_address(config: &Pubkey, reward_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"farm", config.as_ref(), reward_mint.as_ref()], &PROGRAM_ID)
+}
+
+/// `["stake", farm, owner]`
+pub fn stake_position_address(farm: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"stake", farm.as_ref(), owner.as_ref()], &PROGRAM_ID)
+}
+
+/// `["order", config, owner, seed.to_le_bytes()]`
+pub fn limit_order_address(config: &Pubkey, owner: &Pubkey, seed: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"order", config.as_ref(), owner.as_ref(), &seed.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+/// `["pool", token_x_mint, token_y_mint, fee.to_le_bytes()]`, with the mints sorted first.
+/// Address of the canonical pool marker of a pair and fee tier.
+pub fn pool_key_address(mint_a: &Pubkey, mint_b: &Pubkey, fee: u16) -> (Pubkey, u8) {
+    let (token_x_mint, token_y_mint) = sort_mints(mint_a, mint_b);
+    Pubkey::find_program_address(
+        &[
+            b"pool",
+            token_x_mint.as_ref(),
+            token_y_mint.as_ref(),
+            &fee.to_le_bytes(),
+        ],
+        &PROGRAM_ID,
+    )
+}
+
+/// `["registry", mint]`
+pub fn registry_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"registry", mint.as_ref()], &PROGRAM_ID)
+}
+
+/// `["registry", mint, index.to_le_bytes()]`, the `index`-th canonical pool of `mint`.
+pub fn registry_entry_address(mint: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"registry", mint.as_ref(), &index.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+/// Config seed of the canonical pool of a pair and fee tier, mints in any order.
+pub fn canonical_pool_seed(mint_a: &Pubkey, mint_b: &Pubkey, fee: u16) -> u64 {
+    let (token_x_mint, token_y_mint) = sort_mints(mint_a, mint_b);
+    PoolKey::pool_seed(token_x_mint, token_y_mint, fee)
+}
+
+/// Orders two mints the way canonical pools expect them: token X first.
+pub fn sort_mints<'a>(mint_a: &'a Pubkey, mint_b: &'a Pubkey) -> (&'a Pubkey, &'a Pubkey) {
+    match mint_a < mint_b {
+        true => (mint_a, mint_b),
+        false => (mint_b, mint_a),
+    }
+}
+
+/// Every address an instruction on a pool needs, derived from its config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolAddresses {
+    pub config: Pubkey,
+    pub token_x_mint: Pubkey,
+    pub token_y_mint: Pubkey,
+    /// SPL Token or Token-2022 program owning token X.
+    pub token_x_program: Pubkey,
+    /// SPL Token or Token-2022 program owning token Y.
+    pub token_y_program: Pubkey,
+    /// Token program of the LP mint.
+    pub token_program: Pubkey,
+    pub lp_token_mint: Pubkey,
+    pub oracle: Pubkey,
+    pub pool_token_x_vault: Pubkey,
+    pub pool_token_y_vault: Pubkey,
+}
+
+impl PoolAddresses {
+    /// Addresses of the pool created with `seed`, whether it exists yet or not.
+    pub fn new(
+        seed: u64,
+        token_x_mint: Pubkey,
+        token_y_mint: Pubkey,
+        token_x_program: Pubkey,
+        token_y_program: Pubkey,
+        token_program: Pubkey,
+    ) -> Self {
+        let (config, _) = config_address(seed);
+
+        PoolAddresses {
+            config,
+            token_x_mint,
+            token_y_mint,
+            token_x_program,
+            token_y_program,
+            token_program,
+            lp_token_mint: lp_token_mint_address(&config).0,
+            oracle: oracle_address(&config).0,
+            pool_token_x_vault: get_associated_token_address_with_program_id(
+                &config,
+                &token_x_mint,
+                &token_x_program,
+            ),
+            pool_token_y_vault: get_associated_token_address_with_program_id(
+                &config,
+                &token_y_mint,
+                &token_y_program,
+            ),
+        }
+    }
+
+    /// Addresses of an existing pool, from its decoded config.
+    pub fn from_config(
+        config: &Config,
+        token_x_program: Pubkey,
+        token_y_program: Pubkey,
+        token_program: Pubkey,
+    ) -> Self {
+        PoolAddresses::new(
+            config.seed,
+            config.token_x_mint,
+            config.token_y_mint,
+            token_x_program,
+            token_y_program,
+            token_program,
+        )
+    }
+
+    /// Token program owning `mint`, one of the pool mints. Other mints are assumed to
+    /// be owned by the LP token program.
+    pub fn mint_token_program(&self, mint: &Pubkey) -> Pubkey {
+        match *mint {
+            mint if mint == self.token_x_mint => self.token_x_program,
+            mint if mint == self.token_y_mint => self.token_y_program,
+            _ => self.token_program,
+        }
+    }
+
+    /// Associated token account of `owner` for `mint`, under the token program of `mint`.
+    pub fn user_token_account(&self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, mint, &self.mint_token_program(mint))
+    }
+}
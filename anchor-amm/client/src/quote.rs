@@ -0,0 +1,126 @@
+//! Trade quotes for a pool snapshot.
+//!
+//! Every quote goes through the `Config` methods the program itself calls, so it
+//! matches the on-chain result for the same pool state and timestamp. Amounts are
+//! the ones received or sent by the pool vaults: Token-2022 transfer fees are not
+//! included and have to be added on top for such mints.
+
+use anchor_amm::{
+    helpers::{
+        curve::{initial_liquidity, liquidity_for_amount},
+        stable_swap::U256,
+    },
+    BASIS_POINTS, MINIMUM_LIQUIDITY,
+};
+use anchor_lang::Result;
+
+use crate::{Config, SwapQuote};
+
+/// Snapshot of a pool: its config, vault balances and LP supply.
+#[derive(Clone)]
+pub struct PoolState {
+    pub config: Config,
+    pub pool_token_x_vault_amount: u64,
+    pub pool_token_y_vault_amount: u64,
+    pub lp_supply: u64,
+}
+
+impl PoolState {
+    /// Reserves backing the LP supply, i.e. the vault balances minus unclaimed protocol fees.
+    pub fn reserves(&self) -> Result<(u64, u64)> {
+        self.config
+            .reserves(self.pool_token_x_vault_amount, self.pool_token_y_vault_amount)
+    }
+
+    /// Output of `swap` for `amount_in` of token X (`is_token_x`) or token Y, at unix time `now`.
+    pub fn quote_swap(&self, is_token_x: bool, amount_in: u64, now: i64) -> Result<SwapQuote> {
+        let (reserve_x, reserve_y) = self.reserves()?;
+        self.config.quote_swap(
+            reserve_x,
+            reserve_y,
+            self.lp_supply,
+            is_token_x,
+            amount_in,
+            now,
+        )
+    }
+
+    /// Input `swap_exact_out` takes to pay out exactly `amount_out`, at unix time `now`.
+    pub fn quote_swap_exact_out(
+        &self,
+        is_token_x: bool,
+        amount_out: u64,
+        now: i64,
+    ) -> Result<SwapQuote> {
+        let (reserve_x, reserve_y) = self.reserves()?;
+        self.config
+            .quote_swap_exact_out(reserve_x, reserve_y, is_token_x, amount_out, now)
+    }
+
+    /// Price impact of selling `amount_in`, in basis points of the pre-trade spot price.
+    /// The swap fee is not counted as impact.
+    pub fn price_impact_bps(&self, is_token_x: bool, amount_in: u64, now: i64) -> Result<u16> {
+        let (reserve_x, reserve_y) = self.reserves()?;
+        let (price_x, price_y) = self.config.spot_prices(reserve_x, reserve_y, now)?;
+        let quote = self.quote_swap(is_token_x, amount_in, now)?;
+        let spot_price = match is_token_x {
+            true => price_x,
+            false => price_y,
+        };
+        Ok(price_impact_bps(
+            quote.amount_in - quote.fee,
+            quote.amount_out,
+            spot_price,
+        ))
+    }
+
+    /// Move of the spot price `swap` of `amount_in` would cause, in basis points. The swap
+    /// fails with `PriceImpactExceeded` when it is above `config.max_price_impact_bps`.
+    pub fn price_move_bps(&self, is_token_x: bool, amount_in: u64, now: i64) -> Result<u64> {
+        let (reserve_x, reserve_y) = self.reserves()?;
+        let quote = self.quote_swap(is_token_x, amount_in, now)?;
+        let protocol_fee = self.config.protocol_fee_amount(quote.fee)?;
+        self.config
+            .swap_price_move_bps(reserve_x, reserve_y, is_token_x, &quote, protocol_fee, now)
+    }
+
+    /// LP tokens `deposit` mints for at most `max_x` and `max_y`.
+    ///
+    /// For an empty pool this is `sqrt(max_x * max_y)` minus the locked `MINIMUM_LIQUIDITY`,
+    /// to pass as the minimum LP amount. Otherwise it is the largest `lp_amount` whose
+    /// `deposit_amounts` fit within both maximums.
+    pub fn lp_to_mint(&self, max_x: u64, max_y: u64) -> Result<u64> {
+        if self.lp_supply == 0 {
+            return Ok(initial_liquidity(max_x, max_y).saturating_sub(MINIMUM_LIQUIDITY));
+        }
+        let (reserve_x, reserve_y) = self.reserves()?;
+        Ok(liquidity_for_amount(max_x, reserve_x, self.lp_supply)?
+            .min(liquidity_for_amount(max_y, reserve_y, self.lp_supply)?))
+    }
+
+    /// Token X and Y `deposit` takes to mint `lp_amount` LP tokens in a non-empty pool.
+    pub fn deposit_amounts(&self, lp_amount: u64) -> Result<(u64, u64)> {
+        let (reserve_x, reserve_y) = self.reserves()?;
+        self.config
+            .deposit_amounts(reserve_x, reserve_y, self.lp_supply, lp_amount)
+    }
+
+    /// Token X and Y `withdraw` pays out for burning `lp_amount` LP tokens.
+    pub fn withdraw_amounts(&self, lp_amount: u64) -> Result<(u64, u64)> {
+        let (reserve_x, reserve_y) = self.reserves()?;
+        self.config
+            .withdraw_amounts(reserve_x, reserve_y, self.lp_supply, lp_amount)
+    }
+}
+
+/// Price impact of a swap in basis points: the shortfall of `amount_out` against
+/// `amount_in_after_fee` valued at the pre-trade spot price, a Q64.64 number.
+/// The swap fee is not part of the impact.
+fn price_impact_bps(amount_in_after_fee: u64, amount_out: u64, spot_price_q64: u128) -> u16 {
+    let expected_out = (U256::from(amount_in_after_fee) * U256::from(spot_price_q64)) >> 64;
+    if expected_out.is_zero() || U256::from(amount_out) >= expected_out {
+        return 0;
+    }
+    let impact = (expected_out - U256::from(amount_out)) * U256::from(BASIS_POINTS) / expected_out;
+    impact.as_u64() as u16
+}
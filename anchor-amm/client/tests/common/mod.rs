@@ -0,0 +1,1364 @@
+//! In-process harness for the integration tests.
+//!
+//! Loads the program built by `anchor build` into LiteSVM and writes mints and token
+//! accounts directly into the SVM state, so each test starts from a funded pool in
+//! microseconds instead of a round trip to a validator.
+
+#![allow(dead_code)]
+
+use anchor_amm::{accounts, error::AmmError, instruction, TwapPrice};
+use anchor_amm_client::{
+    accounts::{
+        decode_config, decode_factory, decode_farm, decode_mint_registry, decode_oracle,
+        decode_stake_position,
+    },
+    instructions,
+    pda::{
+        canonical_pool_seed, factory_address, farm_address, limit_order_address, metadata_address,
+        registry_address, registry_entry_address, sort_mints, stake_position_address,
+        PoolAddresses,
+    },
+    quote::PoolState,
+    Config, CurveType, Factory, Farm, InitArgs, Oracle, StakePosition, PROGRAM_ID,
+};
+use anchor_lang::{
+    error::ERROR_CODE_OFFSET,
+    prelude::{Clock, Pubkey},
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_option::COption,
+        sysvar,
+    },
+    system_program, AnchorDeserialize, Event, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address_with_program_id},
+    metadata::mpl_token_metadata::{self, accounts::Metadata},
+    token::spl_token,
+    token_interface::spl_token_2022::{
+        self,
+        extension::{
+            non_transferable::NonTransferable,
+            permanent_delegate::PermanentDelegate,
+            transfer_fee::{TransferFee, TransferFeeConfig},
+            transfer_hook::TransferHook,
+            BaseStateWithExtensions, BaseStateWithExtensionsMut, ExtensionType,
+            StateWithExtensions, StateWithExtensionsMut,
+        },
+        state::{Account as TokenAccount, AccountState, Mint},
+    },
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use litesvm::{types::TransactionMetadata, LiteSVM};
+use solana_sdk::{
+    account::Account,
+    instruction::InstructionError,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+pub const SEED: u64 = 50;
+pub const FEE: u16 = 30;
+pub const DECIMALS: u8 = 6;
+
+/// Path of the program binary produced by `anchor build`.
+const PROGRAM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../target/deploy/anchor_amm.so"
+);
+
+/// Path of the CPI test program, built from `tests/fixtures/cpi-caller` (see the readme).
+const CPI_CALLER_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/cpi_caller.so"
+);
+
+/// Path of the Metaplex token metadata program, dumped from mainnet (see the readme).
+const METADATA_PROGRAM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/mpl_token_metadata.so"
+);
+
+/// A farm of the test pool, see `TestPool::create_farm`.
+#[derive(Clone, Copy)]
+pub struct TestFarm {
+    pub farm: Pubkey,
+    pub reward_mint: Pubkey,
+}
+
+/// A limit order placed on the test pool, see `TestPool::place_limit_order`.
+#[derive(Clone, Copy)]
+pub struct TestOrder {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub is_token_x: bool,
+}
+
+/// Token program and extensions of a pool mint, see `PoolSetup`.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum TestMint {
+    /// Legacy SPL Token mint.
+    #[default]
+    Spl,
+    /// Token-2022 mint withholding `basis_points` of every transfer, up to `maximum_fee`.
+    TransferFee { basis_points: u16, maximum_fee: u64 },
+    /// Token-2022 mint whose tokens cannot be transferred.
+    NonTransferable,
+    /// Token-2022 mint with a delegate over every token account.
+    PermanentDelegate,
+    /// Token-2022 mint calling a program on every transfer.
+    TransferHook,
+}
+
+impl TestMint {
+    pub fn token_program(self) -> Pubkey {
+        match self {
+            TestMint::Spl => spl_token::ID,
+            _ => spl_token_2022::ID,
+        }
+    }
+
+    /// Tokens withheld by the mint when `amount` is sent.
+    pub fn transfer_fee(self, amount: u64) -> u64 {
+        match self {
+            TestMint::TransferFee {
+                basis_points,
+                maximum_fee,
+            } => ((amount as u128 * basis_points as u128).div_ceil(10_000) as u64).min(maximum_fee),
+            _ => 0,
+        }
+    }
+
+    fn extensions(self) -> Vec<ExtensionType> {
+        match self {
+            TestMint::Spl => vec![],
+            TestMint::TransferFee { .. } => vec![ExtensionType::TransferFeeConfig],
+            TestMint::NonTransferable => vec![ExtensionType::NonTransferable],
+            TestMint::PermanentDelegate => vec![ExtensionType::PermanentDelegate],
+            TestMint::TransferHook => vec![ExtensionType::TransferHook],
+        }
+    }
+}
+
+/// Mints, fees and curve of a test pool, see `TestPool::try_with`.
+#[derive(Clone, Copy)]
+pub struct PoolSetup {
+    /// Token X and token Y, in that order.
+    pub mints: [TestMint; 2],
+    /// Decimals of token X and token Y.
+    pub decimals: [u8; 2],
+    pub fee: u16,
+    pub protocol_fee: u16,
+    pub curve_type: CurveType,
+    /// Amplification of a StableSwap pool, ignored by the constant product curve.
+    pub amp: u64,
+}
+
+impl Default for PoolSetup {
+    fn default() -> Self {
+        PoolSetup {
+            mints: [TestMint::Spl; 2],
+            decimals: [DECIMALS; 2],
+            fee: FEE,
+            protocol_fee: 0,
+            curve_type: CurveType::ConstantProduct,
+            amp: 0,
+        }
+    }
+}
+
+pub struct TestPool {
+    pub svm: LiteSVM,
+    pub admin: Keypair,
+    pub pool: PoolAddresses,
+}
+
+impl TestPool {
+    /// Initializes an empty constant product pool with `admin` as its authority.
+    pub fn new(fee: u16, protocol_fee: u16) -> Self {
+        TestPool::try_with(PoolSetup {
+            fee,
+            protocol_fee,
+            ..PoolSetup::default()
+        })
+        .unwrap()
+    }
+
+    /// Initializes an empty pool as described by `setup`, with `admin` as its authority.
+    pub fn try_with(setup: PoolSetup) -> Result<Self, TransactionError> {
+        let mut svm = LiteSVM::new();
+        svm.add_program_from_file(PROGRAM_ID, PROGRAM_PATH)
+            .expect("program binary missing, run `anchor build` first");
+        // `initialize` creates the LP metadata, so no pool can be created without it.
+        svm.add_program_from_file(mpl_token_metadata::ID, METADATA_PROGRAM_PATH)
+            .unwrap_or_else(|_| {
+                panic!(
+                    "metadata program fixture missing, dump it with \
+                     `solana program dump -u m {} {METADATA_PROGRAM_PATH}`",
+                    mpl_token_metadata::ID
+                )
+            });
+
+        let admin = Keypair::new();
+        svm.airdrop(&admin.pubkey(), 100_000_000_000).unwrap();
+
+        let mut mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+        mints.sort();
+        for (i, mint) in mints.into_iter().enumerate() {
+            set_mint(&mut svm, mint, &admin.pubkey(), setup.mints[i], setup.decimals[i]);
+        }
+        let pool = PoolAddresses::new(
+            SEED,
+            mints[0],
+            mints[1],
+            setup.mints[0].token_program(),
+            setup.mints[1].token_program(),
+            spl_token::ID,
+        );
+
+        let mut test_pool = TestPool { svm, admin, pool };
+        let args = InitArgs {
+            seed: SEED,
+            fee: setup.fee,
+            protocol_fee: setup.protocol_fee,
+            curve_type: setup.curve_type,
+            amp: setup.amp,
+            authority: Some(test_pool.admin.pubkey()),
+        };
+        let ix = instructions::initialize(&test_pool.pool, test_pool.admin.pubkey(), args);
+        let admin = test_pool.admin.insecure_clone();
+        test_pool.send(ix, &admin)?;
+        // Canonical pools get the admin as authority too.
+        test_pool.initialize_factory(&admin, admin.pubkey())?;
+        Ok(test_pool)
+    }
+
+    /// A pool already holding `amount_x` and `amount_y` from a first depositor, which is returned.
+    pub fn with_liquidity(amount_x: u64, amount_y: u64) -> (Self, Keypair) {
+        let mut test_pool = TestPool::new(FEE, 0);
+        let provider = test_pool.seed_liquidity(amount_x, amount_y);
+        (test_pool, provider)
+    }
+
+    /// Makes the first deposit of `amount_x` and `amount_y` from a new user, which is returned.
+    pub fn seed_liquidity(&mut self, amount_x: u64, amount_y: u64) -> Keypair {
+        let pool = self.pool;
+        self.seed_pool(&pool, amount_x, amount_y)
+    }
+
+    /// Makes the first deposit of `amount_x` and `amount_y` into `pool` from a new user,
+    /// which is returned.
+    pub fn seed_pool(&mut self, pool: &PoolAddresses, amount_x: u64, amount_y: u64) -> Keypair {
+        let provider = Keypair::new();
+        self.svm.airdrop(&provider.pubkey(), 10_000_000_000).unwrap();
+        self.set_balance(&provider.pubkey(), &pool.token_x_mint, amount_x);
+        self.set_balance(&provider.pubkey(), &pool.token_y_mint, amount_y);
+        let ix = instructions::deposit(pool, provider.pubkey(), 1, amount_x, amount_y, None);
+        self.send(ix, &provider).unwrap();
+        provider
+    }
+
+    /// Initializes another constant product pool of two legacy mints next to the test pool,
+    /// under `seed` and with the test pool authority.
+    pub fn add_pool(&mut self, mint_a: Pubkey, mint_b: Pubkey, seed: u64) -> PoolAddresses {
+        let (token_x_mint, token_y_mint) = sort_mints(&mint_a, &mint_b);
+        let pool = PoolAddresses::new(
+            seed,
+            *token_x_mint,
+            *token_y_mint,
+            spl_token::ID,
+            spl_token::ID,
+            spl_token::ID,
+        );
+        let admin = self.admin.insecure_clone();
+        let args = InitArgs {
+            seed,
+            fee: FEE,
+            protocol_fee: 0,
+            curve_type: CurveType::ConstantProduct,
+            amp: 0,
+            authority: Some(admin.pubkey()),
+        };
+        self.send(instructions::initialize(&pool, admin.pubkey(), args), &admin)
+            .unwrap();
+        pool
+    }
+
+    /// A new funded user holding `amount_x` of token X and `amount_y` of token Y.
+    pub fn user(&mut self, amount_x: u64, amount_y: u64) -> Keypair {
+        let user = Keypair::new();
+        self.svm.airdrop(&user.pubkey(), 10_000_000_000).unwrap();
+        self.fund(&user.pubkey(), amount_x, amount_y);
+        user
+    }
+
+    /// Sets the token X and token Y balances of `owner` to `amount_x` and `amount_y`.
+    pub fn fund(&mut self, owner: &Pubkey, amount_x: u64, amount_y: u64) {
+        let pool = self.pool;
+        self.set_balance(owner, &pool.token_x_mint, amount_x);
+        self.set_balance(owner, &pool.token_y_mint, amount_y);
+    }
+
+    /// Sets the balance of the `mint` associated token account of `owner` to `amount`.
+    pub fn set_balance(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) {
+        let address = self.token_account(owner, mint);
+        set_token_account(&mut self.svm, address, *mint, *owner, amount);
+    }
+
+    /// Associated token account of `owner` for `mint`, under the token program of `mint`.
+    pub fn token_account(&self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, mint, &self.token_program(mint))
+    }
+
+    /// Token program owning `mint`.
+    pub fn token_program(&self, mint: &Pubkey) -> Pubkey {
+        self.svm.get_account(mint).unwrap().owner
+    }
+
+    /// Loads the CPI test program, which forwards an instruction to another program.
+    pub fn add_cpi_caller(&mut self) {
+        self.svm
+            .add_program_from_file(cpi_caller::ID, CPI_CALLER_PATH)
+            .unwrap_or_else(|_| {
+                panic!(
+                    "cpi-caller binary missing, build it with `cargo build-sbf --manifest-path \
+                     client/tests/fixtures/cpi-caller/Cargo.toml --sbf-out-dir \
+                     client/tests/fixtures`"
+                )
+            });
+    }
+
+    /// A new mint, outside of the pool, with the pool authority as mint authority.
+    pub fn new_mint(&mut self) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        set_mint(&mut self.svm, mint, &self.admin.pubkey(), TestMint::Spl, DECIMALS);
+        mint
+    }
+
+    /// Signs `ix` with `signer` as fee payer and executes it.
+    pub fn send(&mut self, ix: Instruction, signer: &Keypair) -> Result<(), TransactionError> {
+        self.send_metered(ix, signer).map(|_| ())
+    }
+
+    /// Like `send`, returning the compute units used by the transaction.
+    pub fn send_metered(
+        &mut self,
+        ix: Instruction,
+        signer: &Keypair,
+    ) -> Result<u64, TransactionError> {
+        self.send_transaction(&[ix], signer)
+            .map(|metadata| metadata.compute_units_consumed)
+    }
+
+    /// Signs `ixs` with `signer` as fee payer and executes them in one transaction.
+    pub fn send_transaction(
+        &mut self,
+        ixs: &[Instruction],
+        signer: &Keypair,
+    ) -> Result<TransactionMetadata, TransactionError> {
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&signer.pubkey()),
+            &[signer],
+            self.svm.latest_blockhash(),
+        );
+        let result = self.svm.send_transaction(tx).map_err(|e| e.err);
+        // Lets the same instruction be sent twice in a row.
+        self.svm.expire_blockhash();
+        result
+    }
+
+    /// Sends `initialize_factory` signed by `signer`.
+    pub fn initialize_factory(
+        &mut self,
+        signer: &Keypair,
+        authority: Pubkey,
+    ) -> Result<(), TransactionError> {
+        let accounts = accounts::InitializeFactory {
+            admin: signer.pubkey(),
+            factory: factory_address().0,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::InitializeFactory { authority }.data(),
+        };
+        self.send(ix, signer)
+    }
+
+    /// Sends `transfer_factory_authority` signed by `signer`.
+    pub fn transfer_factory_authority(
+        &mut self,
+        signer: &Keypair,
+        new_authority: Pubkey,
+    ) -> Result<(), TransactionError> {
+        let accounts = accounts::UpdateFactory {
+            authority: signer.pubkey(),
+            factory: factory_address().0,
+        }
+        .to_account_metas(None);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::TransferFactoryAuthority { new_authority }.data(),
+        };
+        self.send(ix, signer)
+    }
+
+    /// Sends `create_pool` for two new mints at the canonical seed of `fee`, signed by the
+    /// admin, and returns the compute units it used.
+    pub fn create_pool(&mut self, fee: u16) -> Result<u64, TransactionError> {
+        let mut mints = [self.new_mint(), self.new_mint()];
+        mints.sort();
+        let seed = canonical_pool_seed(&mints[0], &mints[1], fee);
+        self.send_create_pool(mints[0], mints[1], fee, seed)
+    }
+
+    /// Sends `create_pool` with `token_x_mint` and `token_y_mint` in that order, signed by
+    /// the admin, and returns the compute units it used. The registry entries are the next
+    /// free ones of each mint.
+    pub fn send_create_pool(
+        &mut self,
+        token_x_mint: Pubkey,
+        token_y_mint: Pubkey,
+        fee: u16,
+        seed: u64,
+    ) -> Result<u64, TransactionError> {
+        let args = InitArgs {
+            seed,
+            fee,
+            protocol_fee: 0,
+            curve_type: CurveType::ConstantProduct,
+            amp: 0,
+            authority: None,
+        };
+        let ix = self.create_pool_ix(token_x_mint, token_y_mint, args);
+        let admin = self.admin.insecure_clone();
+        self.send_metered(ix, &admin)
+    }
+
+    /// A `create_pool` instruction paid by the admin, with `token_x_mint` and `token_y_mint`
+    /// in that order.
+    pub fn create_pool_ix(
+        &self,
+        token_x_mint: Pubkey,
+        token_y_mint: Pubkey,
+        args: InitArgs,
+    ) -> Instruction {
+        let pool = PoolAddresses::new(
+            args.seed,
+            token_x_mint,
+            token_y_mint,
+            spl_token::ID,
+            spl_token::ID,
+            spl_token::ID,
+        );
+        // Derived from the mints as given, `pool_key_address` would sort them.
+        let (pool_key, _) = Pubkey::find_program_address(
+            &[
+                b"pool",
+                token_x_mint.as_ref(),
+                token_y_mint.as_ref(),
+                &args.fee.to_le_bytes(),
+            ],
+            &PROGRAM_ID,
+        );
+
+        // `CreatePool` starts with the `Initialize` accounts. `InitArgs` is not `Clone`,
+        // but all its fields are `Copy`.
+        let mut ix = instructions::initialize(&pool, self.admin.pubkey(), InitArgs { ..args });
+        ix.accounts.extend([
+            AccountMeta::new_readonly(factory_address().0, false),
+            AccountMeta::new(pool_key, false),
+            AccountMeta::new(registry_address(&token_x_mint).0, false),
+            AccountMeta::new(self.next_registry_entry(&token_x_mint), false),
+            AccountMeta::new(registry_address(&token_y_mint).0, false),
+            AccountMeta::new(self.next_registry_entry(&token_y_mint), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]);
+        ix.data = instruction::CreatePool { args }.data();
+        ix
+    }
+
+    /// Number of canonical pools registered for `mint`.
+    pub fn registered_pools(&self, mint: &Pubkey) -> u64 {
+        self.svm
+            .get_account(&registry_address(mint).0)
+            .map_or(0, |account| {
+                decode_mint_registry(&account.data).unwrap().pool_count
+            })
+    }
+
+    /// Address of the registry entry the next canonical pool of `mint` gets.
+    fn next_registry_entry(&self, mint: &Pubkey) -> Pubkey {
+        registry_entry_address(mint, self.registered_pools(mint)).0
+    }
+
+    pub fn deposit(
+        &mut self,
+        user: &Keypair,
+        lp_amount: u64,
+        max_x: u64,
+        max_y: u64,
+    ) -> Result<(), TransactionError> {
+        let ix = instructions::deposit(&self.pool, user.pubkey(), lp_amount, max_x, max_y, None);
+        self.send(ix, user)
+    }
+
+    pub fn withdraw(
+        &mut self,
+        user: &Keypair,
+        lp_amount: u64,
+        min_x: u64,
+        min_y: u64,
+    ) -> Result<(), TransactionError> {
+        let ix = instructions::withdraw(&self.pool, user.pubkey(), lp_amount, min_x, min_y, None);
+        self.send(ix, user)
+    }
+
+    pub fn swap(
+        &mut self,
+        user: &Keypair,
+        is_token_x: bool,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<(), TransactionError> {
+        let ix = instructions::swap(
+            &self.pool,
+            user.pubkey(),
+            is_token_x,
+            amount_in,
+            min_amount_out,
+            None,
+        );
+        self.send(ix, user)
+    }
+
+    pub fn swap_exact_out(
+        &mut self,
+        user: &Keypair,
+        is_token_x: bool,
+        amount_out: u64,
+        max_amount_in: u64,
+    ) -> Result<(), TransactionError> {
+        let ix = instructions::swap_exact_out(
+            &self.pool,
+            user.pubkey(),
+            is_token_x,
+            amount_out,
+            max_amount_in,
+            None,
+        );
+        self.send(ix, user)
+    }
+
+    /// Sends `zap_deposit` of `amount_in` token X (`is_token_x`) or token Y.
+    pub fn zap_deposit(
+        &mut self,
+        user: &Keypair,
+        is_token_x: bool,
+        amount_in: u64,
+        min_lp_amount: u64,
+    ) -> Result<(), TransactionError> {
+        let data = instruction::ZapDeposit {
+            is_token_x,
+            amount_in,
+            min_lp_amount,
+            expires_at: None,
+        };
+        let ix = self.zap_ix(&user.pubkey(), data);
+        self.send(ix, user)
+    }
+
+    /// Sends `zap_withdraw` of `lp_amount` LP tokens, paid out in token X (`is_token_x`)
+    /// or token Y.
+    pub fn zap_withdraw(
+        &mut self,
+        user: &Keypair,
+        is_token_x: bool,
+        lp_amount: u64,
+        min_amount_out: u64,
+    ) -> Result<(), TransactionError> {
+        let data = instruction::ZapWithdraw {
+            is_token_x,
+            lp_amount,
+            min_amount_out,
+            expires_at: None,
+        };
+        let ix = self.zap_ix(&user.pubkey(), data);
+        self.send(ix, user)
+    }
+
+    /// A zap instruction (`Zap` accounts) for `user`.
+    fn zap_ix(&self, user: &Pubkey, data: impl InstructionData) -> Instruction {
+        let pool = &self.pool;
+        let accounts = accounts::Zap {
+            user: *user,
+            token_x_mint: pool.token_x_mint,
+            token_y_mint: pool.token_y_mint,
+            config: pool.config,
+            oracle: pool.oracle,
+            lp_token_mint: pool.lp_token_mint,
+            pool_token_x_vault: pool.pool_token_x_vault,
+            pool_token_y_vault: pool.pool_token_y_vault,
+            user_token_x_account: pool.user_token_account(user, &pool.token_x_mint),
+            user_token_y_account: pool.user_token_account(user, &pool.token_y_mint),
+            user_lp_token_account: pool.user_token_account(user, &pool.lp_token_mint),
+            token_x_program: pool.token_x_program,
+            token_y_program: pool.token_y_program,
+            token_program: pool.token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: data.data(),
+        }
+    }
+
+    /// Sends `lock` (`lock = true`) or `unlock`, signed by the pool authority.
+    pub fn set_locked(&mut self, lock: bool) -> Result<(), TransactionError> {
+        match lock {
+            true => self.update(instruction::Lock {}),
+            false => self.update(instruction::Unlock {}),
+        }
+    }
+
+    /// Sends a pool administration instruction (`Update` accounts), signed by the pool
+    /// authority.
+    pub fn update(&mut self, data: impl InstructionData) -> Result<(), TransactionError> {
+        let ix = self.update_ix(data);
+        let admin = self.admin.insecure_clone();
+        self.send(ix, &admin)
+    }
+
+    /// A pool administration instruction (`Update` accounts) for the pool authority.
+    pub fn update_ix(&self, data: impl InstructionData) -> Instruction {
+        let accounts = accounts::Update {
+            authority: self.admin.pubkey(),
+            config: self.pool.config,
+        }
+        .to_account_metas(None);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: data.data(),
+        }
+    }
+
+    /// Sends `claim_protocol_fees` signed by `signer`, paying the token accounts of
+    /// `treasury`.
+    pub fn claim_protocol_fees(
+        &mut self,
+        signer: &Keypair,
+        treasury: &Pubkey,
+    ) -> Result<(), TransactionError> {
+        let ix = self.claim_protocol_fees_ix(&signer.pubkey(), treasury);
+        self.send(ix, signer)
+    }
+
+    /// `claim_protocol_fees` by `authority`, paying the token accounts of `treasury`.
+    pub fn claim_protocol_fees_ix(&self, authority: &Pubkey, treasury: &Pubkey) -> Instruction {
+        let pool = &self.pool;
+        let accounts = accounts::ClaimProtocolFees {
+            authority: *authority,
+            token_x_mint: pool.token_x_mint,
+            token_y_mint: pool.token_y_mint,
+            config: pool.config,
+            pool_token_x_vault: pool.pool_token_x_vault,
+            pool_token_y_vault: pool.pool_token_y_vault,
+            treasury_token_x_account: pool.user_token_account(treasury, &pool.token_x_mint),
+            treasury_token_y_account: pool.user_token_account(treasury, &pool.token_y_mint),
+            token_x_program: pool.token_x_program,
+            token_y_program: pool.token_y_program,
+        }
+        .to_account_metas(None);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::ClaimProtocolFees {}.data(),
+        }
+    }
+
+    /// `route_swap` of `amount_in` of `source_mint` through the pools of `route`, in order.
+    pub fn route_swap_ix(
+        &self,
+        swapper: &Pubkey,
+        source_mint: Pubkey,
+        route: &[PoolAddresses],
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Instruction {
+        let mut hop_accounts = Vec::new();
+        let mut token_in_mint = source_mint;
+        for pool in route {
+            let (pool_token_in_vault, pool_token_out_vault, token_out_mint) =
+                match token_in_mint == pool.token_x_mint {
+                    true => (pool.pool_token_x_vault, pool.pool_token_y_vault, pool.token_y_mint),
+                    false => (pool.pool_token_y_vault, pool.pool_token_x_vault, pool.token_x_mint),
+                };
+            hop_accounts.extend([
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new(pool.oracle, false),
+                AccountMeta::new_readonly(pool.lp_token_mint, false),
+                AccountMeta::new(pool_token_in_vault, false),
+                AccountMeta::new(pool_token_out_vault, false),
+                AccountMeta::new_readonly(token_out_mint, false),
+                AccountMeta::new_readonly(self.token_program(&token_out_mint), false),
+            ]);
+            token_in_mint = token_out_mint;
+        }
+
+        let destination_mint = token_in_mint;
+        let mut accounts = accounts::RouteSwap {
+            token_pair_swapper: *swapper,
+            source_mint,
+            destination_mint,
+            swapper_source_account: self.token_account(swapper, &source_mint),
+            swapper_destination_account: self.token_account(swapper, &destination_mint),
+            source_token_program: self.token_program(&source_mint),
+            destination_token_program: self.token_program(&destination_mint),
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(hop_accounts);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::RouteSwap {
+                amount_in,
+                min_amount_out,
+                expires_at: None,
+            }
+            .data(),
+        }
+    }
+
+    /// Sends `flash_borrow`, then `between`, then `flash_repay` in one transaction
+    /// signed by `borrower`.
+    pub fn flash_loan(
+        &mut self,
+        borrower: &Keypair,
+        is_token_x: bool,
+        amount: u64,
+        between: &[Instruction],
+    ) -> Result<(), TransactionError> {
+        let mut ixs = vec![self.flash_borrow_ix(&borrower.pubkey(), is_token_x, amount)];
+        ixs.extend_from_slice(between);
+        ixs.push(self.flash_repay_ix(&borrower.pubkey()));
+        self.send_transaction(&ixs, borrower).map(|_| ())
+    }
+
+    /// `flash_borrow` of `amount` of token X (`is_token_x`) or token Y by `borrower`.
+    pub fn flash_borrow_ix(&self, borrower: &Pubkey, is_token_x: bool, amount: u64) -> Instruction {
+        self.flash_loan_ix(borrower, instruction::FlashBorrow { is_token_x, amount })
+    }
+
+    /// `flash_repay` of the loan outstanding on the pool by `borrower`.
+    pub fn flash_repay_ix(&self, borrower: &Pubkey) -> Instruction {
+        self.flash_loan_ix(borrower, instruction::FlashRepay {})
+    }
+
+    /// A flash loan instruction (`FlashLoan` accounts) for `borrower`.
+    fn flash_loan_ix(&self, borrower: &Pubkey, data: impl InstructionData) -> Instruction {
+        let pool = &self.pool;
+        let accounts = accounts::FlashLoan {
+            borrower: *borrower,
+            token_x_mint: pool.token_x_mint,
+            token_y_mint: pool.token_y_mint,
+            config: pool.config,
+            oracle: pool.oracle,
+            pool_token_x_vault: pool.pool_token_x_vault,
+            pool_token_y_vault: pool.pool_token_y_vault,
+            borrower_token_x_account: pool.user_token_account(borrower, &pool.token_x_mint),
+            borrower_token_y_account: pool.user_token_account(borrower, &pool.token_y_mint),
+            instruction_sysvar: sysvar::instructions::ID,
+            token_x_program: pool.token_x_program,
+            token_y_program: pool.token_y_program,
+        }
+        .to_account_metas(None);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: data.data(),
+        }
+    }
+
+    /// Sends `read_twap` over `window` seconds and decodes the returned prices.
+    pub fn read_twap(&mut self, window: u32) -> Result<TwapPrice, TransactionError> {
+        let ix = self.read_twap_ix(window);
+        let admin = self.admin.insecure_clone();
+        let metadata = self.send_transaction(&[ix], &admin)?;
+        Ok(TwapPrice::deserialize(&mut &metadata.return_data.data[..]).unwrap())
+    }
+
+    /// `read_twap` over `window` seconds.
+    pub fn read_twap_ix(&self, window: u32) -> Instruction {
+        let pool = &self.pool;
+        let accounts = accounts::ReadTwap {
+            token_x_mint: pool.token_x_mint,
+            token_y_mint: pool.token_y_mint,
+            config: pool.config,
+            oracle: pool.oracle,
+            pool_token_x_vault: pool.pool_token_x_vault,
+            pool_token_y_vault: pool.pool_token_y_vault,
+            token_x_program: pool.token_x_program,
+            token_y_program: pool.token_y_program,
+        }
+        .to_account_metas(None);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::ReadTwap { window }.data(),
+        }
+    }
+
+    /// Sends `update_lp_metadata_uri` signed by `signer`.
+    pub fn update_lp_metadata_uri(
+        &mut self,
+        signer: &Keypair,
+        uri: &str,
+    ) -> Result<(), TransactionError> {
+        let accounts = accounts::UpdateLpMetadata {
+            authority: signer.pubkey(),
+            config: self.pool.config,
+            lp_token_mint: self.pool.lp_token_mint,
+            lp_token_metadata: metadata_address(&self.pool.lp_token_mint).0,
+            metadata_program: mpl_token_metadata::ID,
+        }
+        .to_account_metas(None);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::UpdateLpMetadataUri {
+                uri: uri.to_string(),
+            }
+            .data(),
+        };
+        self.send(ix, signer)
+    }
+
+    /// Creates a farm paying a new reward mint at `reward_rate` per second for `duration`
+    /// seconds, funded by the pool authority with `funding` reward tokens.
+    pub fn create_farm(&mut self, reward_rate: u64, duration: i64, funding: u64) -> TestFarm {
+        let reward_mint = self.new_mint();
+        let farm = farm_address(&self.pool.config, &reward_mint).0;
+        let test_farm = TestFarm { farm, reward_mint };
+
+        let end_timestamp = self.now() + duration;
+        self.initialize_farm(&test_farm, reward_rate, end_timestamp)
+            .unwrap();
+        if funding > 0 {
+            self.fund_farm(&test_farm, funding).unwrap();
+        }
+        test_farm
+    }
+
+    /// Sends `initialize_farm` for `test_farm`, signed by the pool authority.
+    pub fn initialize_farm(
+        &mut self,
+        test_farm: &TestFarm,
+        reward_rate: u64,
+        end_timestamp: i64,
+    ) -> Result<(), TransactionError> {
+        let accounts = accounts::InitializeFarm {
+            authority: self.admin.pubkey(),
+            config: self.pool.config,
+            lp_token_mint: self.pool.lp_token_mint,
+            reward_mint: test_farm.reward_mint,
+            farm: test_farm.farm,
+            farm_lp_vault: self.farm_vault(test_farm, &self.pool.lp_token_mint),
+            farm_reward_vault: self.farm_vault(test_farm, &test_farm.reward_mint),
+            token_program: spl_token::ID,
+            reward_token_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::InitializeFarm {
+                reward_rate,
+                end_timestamp,
+            }
+            .data(),
+        };
+        let admin = self.admin.insecure_clone();
+        self.send(ix, &admin)
+    }
+
+    /// Sends `update_farm` for `test_farm`, signed by the pool authority.
+    pub fn update_farm(
+        &mut self,
+        test_farm: &TestFarm,
+        reward_rate: u64,
+        end_timestamp: i64,
+    ) -> Result<(), TransactionError> {
+        let accounts = accounts::UpdateFarm {
+            authority: self.admin.pubkey(),
+            config: self.pool.config,
+            farm: test_farm.farm,
+        }
+        .to_account_metas(None);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::UpdateFarm {
+                reward_rate,
+                end_timestamp,
+            }
+            .data(),
+        };
+        let admin = self.admin.insecure_clone();
+        self.send(ix, &admin)
+    }
+
+    /// Mints `amount` reward tokens to the pool authority and sends them to the farm.
+    pub fn fund_farm(&mut self, test_farm: &TestFarm, amount: u64) -> Result<(), TransactionError> {
+        let funder = self.admin.pubkey();
+        let funder_reward_account = self.reward_account(test_farm, &funder);
+        set_token_account(
+            &mut self.svm,
+            funder_reward_account,
+            test_farm.reward_mint,
+            funder,
+            amount,
+        );
+        let accounts = accounts::FundFarm {
+            funder,
+            farm: test_farm.farm,
+            reward_mint: test_farm.reward_mint,
+            farm_reward_vault: self.farm_vault(test_farm, &test_farm.reward_mint),
+            funder_reward_account,
+            reward_token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::FundFarm { amount }.data(),
+        };
+        let admin = self.admin.insecure_clone();
+        self.send(ix, &admin)
+    }
+
+    pub fn stake(
+        &mut self,
+        user: &Keypair,
+        test_farm: &TestFarm,
+        amount: u64,
+    ) -> Result<(), TransactionError> {
+        self.send_stake(user, test_farm, instruction::Stake { amount })
+    }
+
+    pub fn unstake(
+        &mut self,
+        user: &Keypair,
+        test_farm: &TestFarm,
+        amount: u64,
+    ) -> Result<(), TransactionError> {
+        self.send_stake(user, test_farm, instruction::Unstake { amount })
+    }
+
+    pub fn harvest(
+        &mut self,
+        user: &Keypair,
+        test_farm: &TestFarm,
+    ) -> Result<(), TransactionError> {
+        self.send_stake(user, test_farm, instruction::Harvest {})
+    }
+
+    /// Sends `stake`, `unstake` or `harvest` (`Stake` accounts) signed by `user`.
+    fn send_stake(
+        &mut self,
+        user: &Keypair,
+        test_farm: &TestFarm,
+        data: impl InstructionData,
+    ) -> Result<(), TransactionError> {
+        let owner = user.pubkey();
+        let accounts = accounts::Stake {
+            owner,
+            farm: test_farm.farm,
+            position: stake_position_address(&test_farm.farm, &owner).0,
+            lp_token_mint: self.pool.lp_token_mint,
+            reward_mint: test_farm.reward_mint,
+            farm_lp_vault: self.farm_vault(test_farm, &self.pool.lp_token_mint),
+            farm_reward_vault: self.farm_vault(test_farm, &test_farm.reward_mint),
+            owner_lp_token_account: self
+                .pool
+                .user_token_account(&owner, &self.pool.lp_token_mint),
+            owner_reward_account: self.reward_account(test_farm, &owner),
+            token_program: spl_token::ID,
+            reward_token_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: data.data(),
+        };
+        self.send(ix, user)
+    }
+
+    /// Sends `place_limit_order` signed by `user`, selling `amount_in` of token X
+    /// (`is_token_x`) or token Y.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_limit_order(
+        &mut self,
+        user: &Keypair,
+        seed: u64,
+        is_token_x: bool,
+        amount_in: u64,
+        min_amount_out: u64,
+        expires_at: i64,
+        keeper_tip: u64,
+    ) -> Result<TestOrder, TransactionError> {
+        let owner = user.pubkey();
+        let (token_in_mint, token_out_mint) = self.order_mints(is_token_x);
+        let order = limit_order_address(&self.pool.config, &owner, seed).0;
+        let test_order = TestOrder {
+            order,
+            owner,
+            is_token_x,
+        };
+        let accounts = accounts::PlaceLimitOrder {
+            owner,
+            token_in_mint,
+            token_out_mint,
+            config: self.pool.config,
+            order,
+            order_escrow: self.order_escrow(&test_order),
+            owner_token_in_account: self.pool.user_token_account(&owner, &token_in_mint),
+            owner_token_out_account: self.pool.user_token_account(&owner, &token_out_mint),
+            token_in_program: spl_token::ID,
+            token_out_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::PlaceLimitOrder {
+                seed,
+                amount_in,
+                min_amount_out,
+                expires_at,
+                keeper_tip,
+            }
+            .data(),
+        };
+        self.send(ix, user).map(|_| test_order)
+    }
+
+    /// Sends `execute_limit_order` for `test_order`, signed by `keeper`.
+    pub fn execute_limit_order(
+        &mut self,
+        keeper: &Keypair,
+        test_order: &TestOrder,
+    ) -> Result<(), TransactionError> {
+        let (_, token_out_mint) = self.order_mints(test_order.is_token_x);
+        let accounts = accounts::ExecuteLimitOrder {
+            keeper: keeper.pubkey(),
+            token_x_mint: self.pool.token_x_mint,
+            token_y_mint: self.pool.token_y_mint,
+            config: self.pool.config,
+            oracle: self.pool.oracle,
+            lp_token_mint: self.pool.lp_token_mint,
+            pool_token_x_vault: self.pool.pool_token_x_vault,
+            pool_token_y_vault: self.pool.pool_token_y_vault,
+            order: test_order.order,
+            owner: test_order.owner,
+            order_escrow: self.order_escrow(test_order),
+            owner_token_out_account: self
+                .pool
+                .user_token_account(&test_order.owner, &token_out_mint),
+            token_x_program: spl_token::ID,
+            token_y_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::ExecuteLimitOrder {}.data(),
+        };
+        self.send(ix, keeper)
+    }
+
+    /// Sends `cancel_limit_order` for `test_order`, signed by `user`.
+    pub fn cancel_limit_order(
+        &mut self,
+        user: &Keypair,
+        test_order: &TestOrder,
+    ) -> Result<(), TransactionError> {
+        let (token_in_mint, _) = self.order_mints(test_order.is_token_x);
+        let accounts = accounts::CancelLimitOrder {
+            owner: user.pubkey(),
+            token_in_mint,
+            order: test_order.order,
+            order_escrow: self.order_escrow(test_order),
+            owner_token_in_account: self
+                .pool
+                .user_token_account(&test_order.owner, &token_in_mint),
+            token_in_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: instruction::CancelLimitOrder {}.data(),
+        };
+        self.send(ix, user)
+    }
+
+    /// Input and output mints of an order selling token X (`is_token_x`) or token Y.
+    pub fn order_mints(&self, is_token_x: bool) -> (Pubkey, Pubkey) {
+        match is_token_x {
+            true => (self.pool.token_x_mint, self.pool.token_y_mint),
+            false => (self.pool.token_y_mint, self.pool.token_x_mint),
+        }
+    }
+
+    /// Token account of the order holding its escrowed input.
+    pub fn order_escrow(&self, test_order: &TestOrder) -> Pubkey {
+        let (token_in_mint, _) = self.order_mints(test_order.is_token_x);
+        get_associated_token_address_with_program_id(
+            &test_order.order,
+            &token_in_mint,
+            &spl_token::ID,
+        )
+    }
+
+    /// Lamports held by `address`, zero if it does not exist.
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.svm.get_account(address).map_or(0, |account| account.lamports)
+    }
+
+    pub fn farm(&self, test_farm: &TestFarm) -> Farm {
+        decode_farm(&self.svm.get_account(&test_farm.farm).unwrap().data).unwrap()
+    }
+
+    pub fn stake_position(&self, test_farm: &TestFarm, owner: &Pubkey) -> StakePosition {
+        let address = stake_position_address(&test_farm.farm, owner).0;
+        decode_stake_position(&self.svm.get_account(&address).unwrap().data).unwrap()
+    }
+
+    /// Reward token account of `owner` for the farm reward mint.
+    pub fn reward_account(&self, test_farm: &TestFarm, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &test_farm.reward_mint, &spl_token::ID)
+    }
+
+    /// Vault of the farm for `mint`, the LP or the reward mint.
+    pub fn farm_vault(&self, test_farm: &TestFarm, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(&test_farm.farm, mint, &spl_token::ID)
+    }
+
+    pub fn config(&self) -> Config {
+        decode_config(&self.svm.get_account(&self.pool.config).unwrap().data).unwrap()
+    }
+
+    pub fn factory(&self) -> Factory {
+        let account = self.svm.get_account(&factory_address().0).unwrap();
+        decode_factory(&account.data).unwrap()
+    }
+
+    pub fn oracle(&self) -> Oracle {
+        decode_oracle(&self.svm.get_account(&self.pool.oracle).unwrap().data).unwrap()
+    }
+
+    /// Current pool snapshot, as an off-chain client would fetch it.
+    pub fn state(&self) -> PoolState {
+        self.pool_state(&self.pool)
+    }
+
+    /// Current snapshot of `pool`, the test pool or one added next to it.
+    pub fn pool_state(&self, pool: &PoolAddresses) -> PoolState {
+        let lp_mint = self.svm.get_account(&pool.lp_token_mint).unwrap();
+        PoolState {
+            config: decode_config(&self.svm.get_account(&pool.config).unwrap().data).unwrap(),
+            pool_token_x_vault_amount: self.token_balance(&pool.pool_token_x_vault),
+            pool_token_y_vault_amount: self.token_balance(&pool.pool_token_y_vault),
+            lp_supply: StateWithExtensions::<Mint>::unpack(&lp_mint.data)
+                .unwrap()
+                .base
+                .supply,
+        }
+    }
+
+    /// Metaplex metadata of the LP mint.
+    pub fn lp_metadata(&self) -> Metadata {
+        let address = metadata_address(&self.pool.lp_token_mint).0;
+        Metadata::safe_deserialize(&self.svm.get_account(&address).unwrap().data).unwrap()
+    }
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    /// Moves the clock `seconds` forward.
+    pub fn warp(&mut self, seconds: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += seconds;
+        self.svm.set_sysvar(&clock);
+    }
+
+    pub fn lp_supply(&self) -> u64 {
+        let account = self.svm.get_account(&self.pool.lp_token_mint).unwrap();
+        StateWithExtensions::<Mint>::unpack(&account.data)
+            .unwrap()
+            .base
+            .supply
+    }
+
+    /// Balance of a SPL Token or Token-2022 account, zero if it does not exist yet.
+    pub fn token_balance(&self, address: &Pubkey) -> u64 {
+        self.svm
+            .get_account(address)
+            .filter(|account| !account.data.is_empty())
+            .map_or(0, |account| {
+                StateWithExtensions::<TokenAccount>::unpack(&account.data)
+                    .unwrap()
+                    .base
+                    .amount
+            })
+    }
+
+    /// Token X, token Y and LP balances of `owner`.
+    pub fn balances(&self, owner: &Pubkey) -> (u64, u64, u64) {
+        (
+            self.token_balance(&self.pool.user_token_account(owner, &self.pool.token_x_mint)),
+            self.token_balance(&self.pool.user_token_account(owner, &self.pool.token_y_mint)),
+            self.token_balance(&self.pool.user_token_account(owner, &self.pool.lp_token_mint)),
+        )
+    }
+}
+
+/// Asserts that a transaction failed with `error`.
+pub fn assert_amm_error(result: Result<(), TransactionError>, error: AmmError) {
+    let expected = ERROR_CODE_OFFSET + error as u32;
+    match result {
+        Err(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            assert_eq!(code, expected, "expected error {expected}, got {code}")
+        }
+        other => panic!("expected error {expected}, got {other:?}"),
+    }
+}
+
+/// Events of type `E` logged by a transaction, in order.
+pub fn events<E: Event>(logs: &[String]) -> Vec<E> {
+    logs.iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .map(|data| STANDARD.decode(data).unwrap())
+        .filter_map(|data| {
+            data.strip_prefix(E::DISCRIMINATOR)
+                .map(|mut event| E::deserialize(&mut event).unwrap())
+        })
+        .collect()
+}
+
+fn set_mint(
+    svm: &mut LiteSVM,
+    address: Pubkey,
+    authority: &Pubkey,
+    test_mint: TestMint,
+    decimals: u8,
+) {
+    let len = ExtensionType::try_calculate_account_len::<Mint>(&test_mint.extensions()).unwrap();
+    let mut data = vec![0; len];
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    match test_mint {
+        TestMint::Spl => {}
+        TestMint::TransferFee {
+            basis_points,
+            maximum_fee,
+        } => {
+            let transfer_fee = TransferFee {
+                epoch: 0.into(),
+                maximum_fee: maximum_fee.into(),
+                transfer_fee_basis_points: basis_points.into(),
+            };
+            let config = mint.init_extension::<TransferFeeConfig>(true).unwrap();
+            config.older_transfer_fee = transfer_fee;
+            config.newer_transfer_fee = transfer_fee;
+        }
+        TestMint::NonTransferable => {
+            mint.init_extension::<NonTransferable>(true).unwrap();
+        }
+        TestMint::PermanentDelegate => {
+            mint.init_extension::<PermanentDelegate>(true).unwrap().delegate =
+                Some(*authority).try_into().unwrap();
+        }
+        TestMint::TransferHook => {
+            mint.init_extension::<TransferHook>(true).unwrap().program_id =
+                Some(Pubkey::new_unique()).try_into().unwrap();
+        }
+    }
+    mint.base = Mint {
+        mint_authority: COption::Some(*authority),
+        supply: 0,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    mint.pack_base();
+    mint.init_account_type().unwrap();
+    set_token_program_account(svm, address, data, test_mint.token_program());
+}
+
+/// Writes a token account holding `amount`, under the token program of `mint` and with
+/// the account extensions its mint requires.
+fn set_token_account(svm: &mut LiteSVM, address: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) {
+    let mint_account = svm.get_account(&mint).unwrap();
+    let mint_extensions = StateWithExtensions::<Mint>::unpack(&mint_account.data)
+        .unwrap()
+        .get_extension_types()
+        .unwrap();
+    let extensions = ExtensionType::get_required_init_account_extensions(&mint_extensions);
+
+    let len = ExtensionType::try_calculate_account_len::<TokenAccount>(&extensions).unwrap();
+    let mut data = vec![0; len];
+    let mut account =
+        StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+    for extension in extensions {
+        account.init_account_extension_from_type(extension).unwrap();
+    }
+    account.base = TokenAccount {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    account.pack_base();
+    account.init_account_type().unwrap();
+    set_token_program_account(svm, address, data, mint_account.owner);
+}
+
+fn set_token_program_account(svm: &mut LiteSVM, address: Pubkey, data: Vec<u8>, owner: Pubkey) {
+    let lamports = svm.minimum_balance_for_rent_exemption(data.len());
+    svm.set_account(
+        address,
+        Account {
+            lamports,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .unwrap();
+}
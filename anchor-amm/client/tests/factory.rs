@@ -0,0 +1,285 @@
+//! Canonical pools and the mint registry against the compiled program:
+//! `anchor build && cargo test -p anchor-amm-client`.
+
+mod common;
+
+use anchor_amm::{accounts, error::AmmError, instruction};
+use anchor_amm_client::{
+    accounts::{decode_config, decode_pool_key, decode_registry_entry},
+    instructions,
+    pda::{
+        canonical_pool_seed, config_address, pool_key_address, registry_entry_address,
+        PoolAddresses,
+    },
+    CurveType, InitArgs, PROGRAM_ID,
+};
+use anchor_lang::{prelude::Pubkey, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use common::{assert_amm_error, TestPool, FEE};
+use solana_sdk::{instruction::Instruction, signer::Signer};
+
+/// Two new mints, sorted.
+fn new_pair(test_pool: &mut TestPool) -> (Pubkey, Pubkey) {
+    let mut mints = [test_pool.new_mint(), test_pool.new_mint()];
+    mints.sort();
+    (mints[0], mints[1])
+}
+
+/// Creates the canonical pool of a sorted pair and tier, and returns its config.
+fn create(
+    test_pool: &mut TestPool,
+    (token_x_mint, token_y_mint): (Pubkey, Pubkey),
+    fee: u16,
+) -> Pubkey {
+    let seed = canonical_pool_seed(&token_x_mint, &token_y_mint, fee);
+    test_pool
+        .send_create_pool(token_x_mint, token_y_mint, fee, seed)
+        .unwrap();
+    config_address(seed).0
+}
+
+/// Configs of the pools registered for `mint`, by registry index.
+fn registered_configs(test_pool: &TestPool, mint: &Pubkey) -> Vec<Pubkey> {
+    (0..test_pool.registered_pools(mint))
+        .map(|index| {
+            let address = registry_entry_address(mint, index).0;
+            let entry = decode_registry_entry(&test_pool.svm.get_account(&address).unwrap().data)
+                .unwrap();
+            assert_eq!((entry.mint, entry.index), (*mint, index));
+            entry.config
+        })
+        .collect()
+}
+
+/// `InitArgs` of the canonical pool of a sorted pair and tier.
+fn canonical_args((token_x_mint, token_y_mint): (Pubkey, Pubkey), fee: u16) -> InitArgs {
+    InitArgs {
+        seed: canonical_pool_seed(&token_x_mint, &token_y_mint, fee),
+        fee,
+        protocol_fee: 0,
+        curve_type: CurveType::ConstantProduct,
+        amp: 0,
+        authority: None,
+    }
+}
+
+#[test]
+fn create_pool_registers_a_pool_run_by_the_factory_authority() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let pair = new_pair(&mut test_pool);
+
+    let config = create(&mut test_pool, pair, FEE);
+
+    let state = decode_config(&test_pool.svm.get_account(&config).unwrap().data).unwrap();
+    assert_eq!((state.token_x_mint, state.token_y_mint), pair);
+    assert_eq!(state.fee, FEE);
+    assert_eq!(state.authority, Some(test_pool.factory().authority));
+    assert_eq!(state.authority, Some(test_pool.admin.pubkey()));
+    let pool_key = pool_key_address(&pair.1, &pair.0, FEE).0;
+    let pool_key = decode_pool_key(&test_pool.svm.get_account(&pool_key).unwrap().data).unwrap();
+    assert_eq!(pool_key.config, config);
+    assert_eq!(registered_configs(&test_pool, &pair.0), [config]);
+    assert_eq!(registered_configs(&test_pool, &pair.1), [config]);
+}
+
+#[test]
+fn factory_authority_administers_canonical_pools() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let pair = new_pair(&mut test_pool);
+    let config = create(&mut test_pool, pair, FEE);
+    let lock_ix = |authority: Pubkey| Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::Update { authority, config }.to_account_metas(None),
+        data: instruction::Lock {}.data(),
+    };
+
+    let stranger = test_pool.user(0, 0);
+    assert_amm_error(
+        test_pool.send(lock_ix(stranger.pubkey()), &stranger),
+        AmmError::InvalidAuthority,
+    );
+
+    let admin = test_pool.admin.insecure_clone();
+    test_pool.send(lock_ix(admin.pubkey()), &admin).unwrap();
+    let state = decode_config(&test_pool.svm.get_account(&config).unwrap().data).unwrap();
+    assert!(state.locked);
+}
+
+#[test]
+fn create_pool_accepts_a_protocol_fee() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let pair = new_pair(&mut test_pool);
+    let args = InitArgs {
+        protocol_fee: 2_000,
+        ..canonical_args(pair, FEE)
+    };
+    let config = config_address(args.seed).0;
+
+    let admin = test_pool.admin.insecure_clone();
+    let ix = test_pool.create_pool_ix(pair.0, pair.1, args);
+    test_pool.send(ix, &admin).unwrap();
+
+    let state = decode_config(&test_pool.svm.get_account(&config).unwrap().data).unwrap();
+    assert_eq!(state.protocol_fee, 2_000);
+}
+
+#[test]
+fn create_pool_rejects_a_creator_chosen_authority() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let pair = new_pair(&mut test_pool);
+    let admin = test_pool.admin.insecure_clone();
+    let args = InitArgs {
+        authority: Some(admin.pubkey()),
+        ..canonical_args(pair, FEE)
+    };
+
+    let ix = test_pool.create_pool_ix(pair.0, pair.1, args);
+    assert_amm_error(test_pool.send(ix, &admin), AmmError::InvalidAuthority);
+}
+
+#[test]
+fn transfer_factory_authority_applies_to_later_pools() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let (mint_a, mint_b) = new_pair(&mut test_pool);
+    let admin = test_pool.admin.insecure_clone();
+    let new_authority = test_pool.user(0, 0);
+    let config_30 = create(&mut test_pool, (mint_a, mint_b), FEE);
+
+    // Only the factory authority can move it.
+    assert_amm_error(
+        test_pool.transfer_factory_authority(&new_authority, new_authority.pubkey()),
+        AmmError::InvalidAuthority,
+    );
+    test_pool
+        .transfer_factory_authority(&admin, new_authority.pubkey())
+        .unwrap();
+    let config_5 = create(&mut test_pool, (mint_a, mint_b), 5);
+
+    assert_eq!(test_pool.factory().authority, new_authority.pubkey());
+    let authority = |config: &Pubkey| {
+        decode_config(&test_pool.svm.get_account(config).unwrap().data)
+            .unwrap()
+            .authority
+    };
+    assert_eq!(authority(&config_30), Some(admin.pubkey()));
+    assert_eq!(authority(&config_5), Some(new_authority.pubkey()));
+}
+
+#[test]
+fn factory_cannot_be_initialized_twice() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let other = test_pool.user(0, 0);
+
+    assert!(test_pool.initialize_factory(&other, other.pubkey()).is_err());
+    assert_eq!(test_pool.factory().authority, test_pool.admin.pubkey());
+}
+
+#[test]
+fn registry_enumerates_the_pools_of_a_mint() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let (mint_a, mint_b) = new_pair(&mut test_pool);
+    let mint_c = test_pool.new_mint();
+    let sorted = |mint_a: Pubkey, mint_b: Pubkey| (mint_a.min(mint_b), mint_a.max(mint_b));
+
+    let config_ab = create(&mut test_pool, (mint_a, mint_b), FEE);
+    let config_ac = create(&mut test_pool, sorted(mint_a, mint_c), FEE);
+    let config_ab_5 = create(&mut test_pool, (mint_a, mint_b), 5);
+
+    assert_eq!(
+        registered_configs(&test_pool, &mint_a),
+        [config_ab, config_ac, config_ab_5]
+    );
+    assert_eq!(registered_configs(&test_pool, &mint_b), [config_ab, config_ab_5]);
+    assert_eq!(registered_configs(&test_pool, &mint_c), [config_ac]);
+}
+
+#[test]
+fn second_pool_for_the_same_pair_and_tier_fails() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let (token_x_mint, token_y_mint) = new_pair(&mut test_pool);
+    create(&mut test_pool, (token_x_mint, token_y_mint), FEE);
+
+    let seed = canonical_pool_seed(&token_x_mint, &token_y_mint, FEE);
+    assert!(test_pool
+        .send_create_pool(token_x_mint, token_y_mint, FEE, seed)
+        .is_err());
+    assert_eq!(test_pool.registered_pools(&token_x_mint), 1);
+    assert_eq!(test_pool.registered_pools(&token_y_mint), 1);
+}
+
+#[test]
+fn create_pool_rejects_reverse_sorted_mints() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let (token_x_mint, token_y_mint) = new_pair(&mut test_pool);
+    let seed = canonical_pool_seed(&token_x_mint, &token_y_mint, FEE);
+
+    assert_amm_error(
+        test_pool
+            .send_create_pool(token_y_mint, token_x_mint, FEE, seed)
+            .map(|_| ()),
+        AmmError::InvalidMintOrder,
+    );
+}
+
+#[test]
+fn create_pool_rejects_fees_outside_the_tiers() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let (token_x_mint, token_y_mint) = new_pair(&mut test_pool);
+    let seed = canonical_pool_seed(&token_x_mint, &token_y_mint, 25);
+
+    assert_amm_error(
+        test_pool
+            .send_create_pool(token_x_mint, token_y_mint, 25, seed)
+            .map(|_| ()),
+        AmmError::InvalidFeeTier,
+    );
+}
+
+#[test]
+fn create_pool_rejects_non_canonical_seeds() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let (token_x_mint, token_y_mint) = new_pair(&mut test_pool);
+
+    for seed in [
+        canonical_pool_seed(&token_x_mint, &token_y_mint, FEE) ^ 1,
+        // The seed of another tier of the same pair.
+        canonical_pool_seed(&token_x_mint, &token_y_mint, 5),
+    ] {
+        assert_amm_error(
+            test_pool
+                .send_create_pool(token_x_mint, token_y_mint, FEE, seed)
+                .map(|_| ()),
+            AmmError::InvalidPoolSeed,
+        );
+    }
+}
+
+#[test]
+fn initialize_rejects_canonical_seeds() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let (token_x_mint, token_y_mint) = new_pair(&mut test_pool);
+    let seed = canonical_pool_seed(&token_x_mint, &token_y_mint, FEE);
+    let pool = PoolAddresses::new(
+        seed,
+        token_x_mint,
+        token_y_mint,
+        spl_token::ID,
+        spl_token::ID,
+        spl_token::ID,
+    );
+    let admin = test_pool.admin.insecure_clone();
+    let args = InitArgs {
+        seed,
+        fee: FEE,
+        protocol_fee: 0,
+        curve_type: CurveType::ConstantProduct,
+        amp: 0,
+        authority: None,
+    };
+
+    // Canonical seeds are only handed out by `create_pool`.
+    assert_amm_error(
+        test_pool.send(instructions::initialize(&pool, admin.pubkey(), args), &admin),
+        AmmError::InvalidPoolSeed,
+    );
+}
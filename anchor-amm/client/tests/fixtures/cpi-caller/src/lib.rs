@@ -0,0 +1,46 @@
+//! Test program invoking another program through CPI, used by the LiteSVM tests to
+//! check that `flash_borrow` only runs as a top-level instruction.
+
+// `#[program]` generates the IDL instructions with the deprecated `AccountInfo::realloc`
+// in Anchor 0.31, and an `allow` on the module does not reach them.
+#![allow(deprecated)]
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke},
+};
+
+declare_id!("C28kT1jNPn18ujYuetGdEqyN4Pf7XZmRTUtqQhkaU7ue");
+
+#[program]
+pub mod cpi_caller {
+    use super::*;
+
+    /// Invokes `program` with `data` and the remaining accounts, signer and writable
+    /// flags unchanged.
+    pub fn forward(ctx: Context<Forward>, data: Vec<u8>) -> Result<()> {
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect();
+        let ix = Instruction {
+            program_id: ctx.accounts.program.key(),
+            accounts,
+            data,
+        };
+        invoke(&ix, ctx.remaining_accounts)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Forward<'info> {
+    /// CHECK: any executable program, invoked with the remaining accounts.
+    #[account(executable)]
+    pub program: UncheckedAccount<'info>,
+}
@@ -0,0 +1,149 @@
+//! Flash loans against the compiled program: `anchor build && cargo test -p anchor-amm-client`.
+
+mod common;
+
+use anchor_amm::{error::AmmError, instruction};
+use anchor_amm_client::{instructions, PROGRAM_ID};
+use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token::error::TokenError;
+use common::{assert_amm_error, TestPool, FEE};
+use solana_sdk::{
+    instruction::InstructionError, signer::Signer, transaction::TransactionError,
+};
+
+const LIQUIDITY: u64 = 1_000_000_000;
+const AMOUNT: u64 = 100_000_000;
+/// Pool swap fee on `AMOUNT`, rounded up.
+const LOAN_FEE: u64 = (AMOUNT * FEE as u64).div_ceil(10_000);
+
+#[test]
+fn repaid_flash_loan_leaves_the_fee_in_the_pool() {
+    let (mut test_pool, _) = TestPool::with_liquidity(LIQUIDITY, LIQUIDITY);
+    let borrower = test_pool.user(LOAN_FEE, 0);
+    let (reserve_x, reserve_y) = test_pool.state().reserves().unwrap();
+
+    test_pool.flash_loan(&borrower, true, AMOUNT, &[]).unwrap();
+
+    assert_eq!(test_pool.balances(&borrower.pubkey()), (0, 0, 0));
+    assert_eq!(
+        test_pool.state().reserves().unwrap(),
+        (reserve_x + LOAN_FEE, reserve_y)
+    );
+    let config = test_pool.config();
+    assert!(config.flash_loan.is_none());
+    assert!(!config.locked);
+}
+
+#[test]
+fn flash_borrow_without_repay_fails() {
+    let (mut test_pool, _) = TestPool::with_liquidity(LIQUIDITY, LIQUIDITY);
+    let borrower = test_pool.user(LOAN_FEE, 0);
+
+    let borrow = test_pool.flash_borrow_ix(&borrower.pubkey(), true, AMOUNT);
+    assert_amm_error(
+        test_pool.send(borrow, &borrower),
+        AmmError::FlashLoanNotRepaid,
+    );
+}
+
+#[test]
+fn flash_borrow_through_cpi_fails() {
+    let (mut test_pool, _) = TestPool::with_liquidity(LIQUIDITY, LIQUIDITY);
+    test_pool.add_cpi_caller();
+    let borrower = test_pool.user(LOAN_FEE, 0);
+
+    // The repay follows, only the borrow is wrapped in a CPI.
+    let borrow = test_pool.flash_borrow_ix(&borrower.pubkey(), true, AMOUNT);
+    let mut accounts = cpi_caller::accounts::Forward {
+        program: PROGRAM_ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(borrow.accounts);
+    let forward = Instruction {
+        program_id: cpi_caller::ID,
+        accounts,
+        data: cpi_caller::instruction::Forward { data: borrow.data }.data(),
+    };
+    let repay = test_pool.flash_repay_ix(&borrower.pubkey());
+
+    assert_amm_error(
+        test_pool
+            .send_transaction(&[forward, repay], &borrower)
+            .map(|_| ()),
+        AmmError::InvalidFlashLoan,
+    );
+}
+
+#[test]
+fn flash_loan_repaid_without_the_fee_fails() {
+    let (mut test_pool, _) = TestPool::with_liquidity(LIQUIDITY, LIQUIDITY);
+    let borrower = test_pool.user(LOAN_FEE - 1, 0);
+    let reserves = test_pool.state().reserves().unwrap();
+
+    // `flash_repay` pulls the amount and the fee, the borrower is one token short.
+    assert_eq!(
+        test_pool.flash_loan(&borrower, true, AMOUNT, &[]),
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(TokenError::InsufficientFunds as u32)
+        ))
+    );
+    assert_eq!(test_pool.state().reserves().unwrap(), reserves);
+    assert!(test_pool.config().flash_loan.is_none());
+}
+
+#[test]
+fn pool_is_locked_while_a_flash_loan_is_open() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(LIQUIDITY, LIQUIDITY);
+    test_pool.fund(&provider.pubkey(), LOAN_FEE + 1_000_000, 1_000_000);
+    let pool = test_pool.pool;
+    let owner = provider.pubkey();
+
+    for ix in [
+        instructions::swap(&pool, owner, true, 1_000, 1, None),
+        instructions::swap_exact_out(&pool, owner, true, 1_000, u64::MAX, None),
+        instructions::deposit(&pool, owner, 1_000, u64::MAX, u64::MAX, None),
+        instructions::withdraw(&pool, owner, 1_000, 0, 0, None),
+        test_pool.read_twap_ix(1),
+    ] {
+        assert_amm_error(
+            test_pool.flash_loan(&provider, true, AMOUNT, &[ix]),
+            AmmError::PoolLocked,
+        );
+    }
+}
+
+#[test]
+fn pool_cannot_be_locked_while_a_flash_loan_is_open() {
+    let (mut test_pool, _) = TestPool::with_liquidity(LIQUIDITY, LIQUIDITY);
+    let admin = test_pool.admin.insecure_clone();
+    test_pool.fund(&admin.pubkey(), LOAN_FEE, 0);
+
+    let lock = test_pool.update_ix(instruction::Lock {});
+    assert_amm_error(
+        test_pool.flash_loan(&admin, true, AMOUNT, &[lock]),
+        AmmError::InvalidFlashLoan,
+    );
+    let unlock = test_pool.update_ix(instruction::Unlock {});
+    assert_amm_error(
+        test_pool.flash_loan(&admin, true, AMOUNT, &[unlock]),
+        AmmError::InvalidFlashLoan,
+    );
+}
+
+#[test]
+fn protocol_fees_cannot_be_claimed_while_a_flash_loan_is_open() {
+    let mut test_pool = TestPool::new(FEE, 2_000);
+    test_pool.seed_liquidity(LIQUIDITY, LIQUIDITY);
+    let swapper = test_pool.user(AMOUNT, 0);
+    test_pool.swap(&swapper, true, AMOUNT, 1).unwrap();
+    let admin = test_pool.admin.insecure_clone();
+    test_pool.fund(&admin.pubkey(), LOAN_FEE, 0);
+
+    let claim = test_pool.claim_protocol_fees_ix(&admin.pubkey(), &admin.pubkey());
+    assert_amm_error(
+        test_pool.flash_loan(&admin, true, AMOUNT, &[claim]),
+        AmmError::PoolLocked,
+    );
+    assert_ne!(test_pool.config().protocol_fees_x, 0);
+}
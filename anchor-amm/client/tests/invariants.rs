@@ -0,0 +1,106 @@
+//! Pool invariants checked over random operation sequences against the compiled program.
+
+mod common;
+
+use anchor_amm::helpers::stable_swap::U256;
+use common::TestPool;
+use proptest::prelude::*;
+use solana_sdk::signer::Signer;
+
+const RESERVE: u64 = 1_000_000_000;
+const TRADER_BALANCE: u64 = 1_000_000_000_000;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Swap { is_token_x: bool, amount_in: u64 },
+    /// Deposit of a fraction of the LP supply, in basis points.
+    Deposit { supply_bps: u64 },
+    /// Withdrawal of a fraction of the trader's LP balance, in basis points.
+    Withdraw { balance_bps: u64 },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (any::<bool>(), 1..RESERVE).prop_map(|(is_token_x, amount_in)| Op::Swap {
+            is_token_x,
+            amount_in
+        }),
+        (1..20_000u64).prop_map(|supply_bps| Op::Deposit { supply_bps }),
+        (1..=10_000u64).prop_map(|balance_bps| Op::Withdraw { balance_bps }),
+    ]
+}
+
+/// Reserves excluding unclaimed protocol fees, and LP supply.
+fn pool_snapshot(test_pool: &TestPool) -> (U256, U256, U256) {
+    let state = test_pool.state();
+    let (reserve_x, reserve_y) = state.reserves().unwrap();
+    (
+        U256::from(reserve_x),
+        U256::from(reserve_y),
+        U256::from(state.lp_supply),
+    )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    /// Fees stay in the pool, so `reserve_x * reserve_y` never goes down across swaps.
+    #[test]
+    fn swaps_never_decrease_k(
+        swaps in prop::collection::vec((any::<bool>(), 1..RESERVE), 1..16)
+    ) {
+        let (mut test_pool, _) = TestPool::with_liquidity(RESERVE, RESERVE);
+        let trader = test_pool.user(TRADER_BALANCE, TRADER_BALANCE);
+
+        for (is_token_x, amount_in) in swaps {
+            let (x_before, y_before, _) = pool_snapshot(&test_pool);
+            // Swaps whose output rounds to zero are rejected and leave the pool untouched.
+            let _ = test_pool.swap(&trader, is_token_x, amount_in, 1);
+            let (x_after, y_after, _) = pool_snapshot(&test_pool);
+
+            prop_assert!(x_after * y_after >= x_before * y_before);
+        }
+    }
+
+    /// Swaps, deposits and withdrawals by others never lower the value of an LP token,
+    /// measured as `sqrt(reserve_x * reserve_y) / lp_supply`.
+    #[test]
+    fn lp_value_never_drops(ops in prop::collection::vec(op(), 1..16)) {
+        let (mut test_pool, _) = TestPool::with_liquidity(RESERVE, RESERVE);
+        let trader = test_pool.user(TRADER_BALANCE, TRADER_BALANCE);
+
+        for op in ops {
+            let (x_before, y_before, supply_before) = pool_snapshot(&test_pool);
+            // Rejected operations leave the pool untouched, so their result is not checked.
+            let _ = match op {
+                Op::Swap { is_token_x, amount_in } => {
+                    test_pool.swap(&trader, is_token_x, amount_in, 1)
+                }
+                Op::Deposit { supply_bps } => {
+                    let state = test_pool.state();
+                    let lp_amount =
+                        ((state.lp_supply as u128 * supply_bps as u128 / 10_000) as u64).max(1);
+                    match state.deposit_amounts(lp_amount) {
+                        Ok((max_x, max_y)) => test_pool.deposit(&trader, lp_amount, max_x, max_y),
+                        Err(_) => continue,
+                    }
+                }
+                Op::Withdraw { balance_bps } => {
+                    let (_, _, lp_balance) = test_pool.balances(&trader.pubkey());
+                    let lp_amount = (lp_balance as u128 * balance_bps as u128 / 10_000) as u64;
+                    if lp_amount == 0 {
+                        continue;
+                    }
+                    test_pool.withdraw(&trader, lp_amount, 0, 0)
+                }
+            };
+            let (x_after, y_after, supply_after) = pool_snapshot(&test_pool);
+
+            // k_after / supply_after^2 >= k_before / supply_before^2, cross-multiplied.
+            prop_assert!(
+                x_after * y_after * supply_before * supply_before
+                    >= x_before * y_before * supply_after * supply_after
+            );
+        }
+    }
+}
@@ -0,0 +1,752 @@
+//! Pool lifecycle against the compiled program: `anchor build && cargo test -p anchor-amm-client`.
+
+mod common;
+
+use anchor_amm::{
+    error::AmmError, instruction, DepositEvent, SwapEvent, WithdrawEvent, MAX_REWARD_RATE,
+    MINIMUM_LIQUIDITY, MIN_AMP,
+};
+use anchor_amm_client::{instructions, pda::farm_address, CurveType};
+use common::{assert_amm_error, events, PoolSetup, TestFarm, TestPool, FEE};
+use solana_sdk::signer::Signer;
+
+#[test]
+fn initialize_creates_empty_pool() {
+    let test_pool = TestPool::new(FEE, 0);
+
+    let config = test_pool.config();
+    assert_eq!(config.token_x_mint, test_pool.pool.token_x_mint);
+    assert_eq!(config.token_y_mint, test_pool.pool.token_y_mint);
+    assert_eq!(config.fee, FEE);
+    assert_eq!(config.authority, Some(test_pool.admin.pubkey()));
+    assert!(!config.locked);
+    assert_eq!(test_pool.lp_supply(), 0);
+    assert_eq!(test_pool.token_balance(&test_pool.pool.pool_token_x_vault), 0);
+    assert_eq!(test_pool.token_balance(&test_pool.pool.pool_token_y_vault), 0);
+}
+
+#[test]
+fn initialize_creates_lp_metadata() {
+    let test_pool = TestPool::new(FEE, 0);
+
+    // The test mints have no metadata, so their symbols fall back to their address.
+    let symbol_x = &test_pool.pool.token_x_mint.to_string()[..4];
+    let symbol_y = &test_pool.pool.token_y_mint.to_string()[..4];
+    let metadata = test_pool.lp_metadata();
+    assert_eq!(metadata.mint, test_pool.pool.lp_token_mint);
+    assert_eq!(metadata.update_authority, test_pool.pool.config);
+    assert_eq!(
+        metadata.name.trim_end_matches('\0'),
+        format!("{symbol_x}-{symbol_y} LP")
+    );
+    assert_eq!(
+        metadata.symbol.trim_end_matches('\0'),
+        format!("{symbol_x}-{symbol_y}")
+    );
+}
+
+#[test]
+fn initialize_rejects_stable_swap_mints_with_different_decimals() {
+    let stable_swap = |decimals| PoolSetup {
+        decimals,
+        curve_type: CurveType::StableSwap,
+        amp: MIN_AMP,
+        ..PoolSetup::default()
+    };
+
+    assert_amm_error(
+        TestPool::try_with(stable_swap([6, 9])).map(|_| ()),
+        AmmError::MismatchedDecimals,
+    );
+    assert!(TestPool::try_with(stable_swap([9, 9])).is_ok());
+    // The constant product price follows the reserves whatever their decimals.
+    let constant_product = PoolSetup {
+        decimals: [6, 9],
+        ..PoolSetup::default()
+    };
+    assert!(TestPool::try_with(constant_product).is_ok());
+}
+
+#[test]
+fn authority_updates_lp_metadata_uri() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let name = test_pool.lp_metadata().name;
+    let admin = test_pool.admin.insecure_clone();
+
+    test_pool
+        .update_lp_metadata_uri(&admin, "https://example.com/lp.json")
+        .unwrap();
+
+    let metadata = test_pool.lp_metadata();
+    assert_eq!(metadata.uri.trim_end_matches('\0'), "https://example.com/lp.json");
+    assert_eq!(metadata.name, name);
+}
+
+#[test]
+fn update_lp_metadata_uri_requires_authority() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let user = test_pool.user(0, 0);
+
+    assert_amm_error(
+        test_pool.update_lp_metadata_uri(&user, "https://example.com/lp.json"),
+        AmmError::InvalidAuthority,
+    );
+}
+
+#[test]
+fn first_deposit_locks_minimum_liquidity() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let user = test_pool.user(4_000_000, 1_000_000);
+
+    test_pool.deposit(&user, 1, 4_000_000, 1_000_000).unwrap();
+
+    // sqrt(4_000_000 * 1_000_000)
+    assert_eq!(test_pool.lp_supply(), 2_000_000);
+    assert_eq!(
+        test_pool.balances(&user.pubkey()),
+        (0, 0, 2_000_000 - MINIMUM_LIQUIDITY)
+    );
+    let pool = test_pool.pool;
+    let locked_lp_token_account = pool.user_token_account(&pool.config, &pool.lp_token_mint);
+    assert_eq!(
+        test_pool.token_balance(&locked_lp_token_account),
+        MINIMUM_LIQUIDITY
+    );
+}
+
+#[test]
+fn first_deposit_below_minimum_liquidity_fails() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let user = test_pool.user(1_000, 1_000);
+
+    let result = test_pool.deposit(&user, 1, 1_000, 1_000);
+    assert_amm_error(result, AmmError::LiquidityLessThanMinimum);
+}
+
+#[test]
+fn later_deposit_is_proportional() {
+    let (mut test_pool, _) = TestPool::with_liquidity(4_000_000, 1_000_000);
+    let user = test_pool.user(10_000_000, 10_000_000);
+
+    let (x, y) = test_pool.state().deposit_amounts(500_000).unwrap();
+    test_pool.deposit(&user, 500_000, x, y).unwrap();
+
+    assert_eq!((x, y), (1_000_000, 250_000));
+    assert_eq!(
+        test_pool.balances(&user.pubkey()),
+        (10_000_000 - x, 10_000_000 - y, 500_000)
+    );
+    assert_eq!(test_pool.lp_supply(), 2_500_000);
+}
+
+#[test]
+fn swap_x_for_y_matches_quote() {
+    let (mut test_pool, _) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(1_000_000, 0);
+
+    let quote = test_pool
+        .state()
+        .quote_swap(true, 1_000_000, test_pool.now())
+        .unwrap();
+    test_pool
+        .swap(&user, true, 1_000_000, quote.amount_out)
+        .unwrap();
+
+    assert!(quote.amount_out > 0);
+    assert_eq!(test_pool.balances(&user.pubkey()), (0, quote.amount_out, 0));
+    let (reserve_x, reserve_y) = test_pool.state().reserves().unwrap();
+    assert_eq!(reserve_x, 11_000_000);
+    assert_eq!(reserve_y, 10_000_000 - quote.amount_out);
+}
+
+#[test]
+fn swap_y_for_x_matches_quote() {
+    let (mut test_pool, _) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(0, 1_000_000);
+
+    let quote = test_pool
+        .state()
+        .quote_swap(false, 1_000_000, test_pool.now())
+        .unwrap();
+    test_pool
+        .swap(&user, false, 1_000_000, quote.amount_out)
+        .unwrap();
+
+    assert!(quote.amount_out > 0);
+    assert_eq!(test_pool.balances(&user.pubkey()), (quote.amount_out, 0, 0));
+}
+
+#[test]
+fn swap_exact_out_pays_exact_amount() {
+    let (mut test_pool, _) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(2_000_000, 0);
+
+    let quote = test_pool
+        .state()
+        .quote_swap_exact_out(true, 500_000, test_pool.now())
+        .unwrap();
+    test_pool
+        .swap_exact_out(&user, true, 500_000, quote.amount_in)
+        .unwrap();
+
+    assert_eq!(
+        test_pool.balances(&user.pubkey()),
+        (2_000_000 - quote.amount_in, 500_000, 0)
+    );
+}
+
+#[test]
+fn withdraw_returns_proportional_share() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(4_000_000, 1_000_000);
+    let (_, _, lp_balance) = test_pool.balances(&provider.pubkey());
+
+    let (x, y) = test_pool.state().withdraw_amounts(lp_balance).unwrap();
+    test_pool.withdraw(&provider, lp_balance, x, y).unwrap();
+
+    assert_eq!(test_pool.balances(&provider.pubkey()), (x, y, 0));
+    // Only the locked minimum liquidity is left.
+    assert_eq!(test_pool.lp_supply(), MINIMUM_LIQUIDITY);
+    assert_eq!(
+        test_pool.state().reserves().unwrap(),
+        (4_000_000 - x, 1_000_000 - y)
+    );
+}
+
+#[test]
+fn swap_emits_the_settled_trade() {
+    let (mut test_pool, _) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(1_000_000, 0);
+    let quote = test_pool
+        .state()
+        .quote_swap(true, 1_000_000, test_pool.now())
+        .unwrap();
+
+    let ix = instructions::swap(&test_pool.pool, user.pubkey(), true, 1_000_000, 1, None);
+    let logs = test_pool.send_transaction(&[ix], &user).unwrap().logs;
+
+    let [event] = &events::<SwapEvent>(&logs)[..] else {
+        panic!("expected one swap event");
+    };
+    assert_eq!((event.config, event.swapper), (test_pool.pool.config, user.pubkey()));
+    assert!(event.is_token_x);
+    assert_eq!(
+        (event.amount_in, event.amount_out, event.fee, event.protocol_fee),
+        (1_000_000, quote.amount_out, quote.fee, 0)
+    );
+    assert_eq!(
+        (event.reserve_x, event.reserve_y),
+        test_pool.state().reserves().unwrap()
+    );
+    assert_eq!(event.lp_supply, test_pool.lp_supply());
+    assert_eq!(event.timestamp, test_pool.now());
+}
+
+#[test]
+fn deposit_and_withdraw_emit_the_amounts_moved() {
+    let (mut test_pool, _) = TestPool::with_liquidity(4_000_000, 1_000_000);
+    let user = test_pool.user(10_000_000, 10_000_000);
+    let pool = test_pool.pool;
+
+    let (x, y) = test_pool.state().deposit_amounts(500_000).unwrap();
+    let ix = instructions::deposit(&pool, user.pubkey(), 500_000, x, y, None);
+    let logs = test_pool.send_transaction(&[ix], &user).unwrap().logs;
+    let [event] = &events::<DepositEvent>(&logs)[..] else {
+        panic!("expected one deposit event");
+    };
+    assert_eq!((event.config, event.depositor), (pool.config, user.pubkey()));
+    assert_eq!((event.amount_x, event.amount_y, event.lp_amount), (x, y, 500_000));
+    assert_eq!(
+        (event.reserve_x, event.reserve_y, event.lp_supply),
+        (4_000_000 + x, 1_000_000 + y, 2_500_000)
+    );
+
+    let (x, y) = test_pool.state().withdraw_amounts(500_000).unwrap();
+    let ix = instructions::withdraw(&pool, user.pubkey(), 500_000, x, y, None);
+    let logs = test_pool.send_transaction(&[ix], &user).unwrap().logs;
+    let [event] = &events::<WithdrawEvent>(&logs)[..] else {
+        panic!("expected one withdraw event");
+    };
+    assert_eq!((event.config, event.withdrawer), (pool.config, user.pubkey()));
+    assert_eq!((event.amount_x, event.amount_y, event.lp_amount), (x, y, 500_000));
+    assert_eq!(
+        (event.reserve_x, event.reserve_y),
+        test_pool.state().reserves().unwrap()
+    );
+    assert_eq!(event.lp_supply, 2_000_000);
+    assert_eq!(event.timestamp, test_pool.now());
+}
+
+#[test]
+fn locked_pool_rejects_trading() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(1_000_000, 1_000_000);
+    test_pool.set_locked(true).unwrap();
+
+    assert_amm_error(test_pool.swap(&user, true, 1_000, 1), AmmError::PoolLocked);
+    assert_amm_error(
+        test_pool.swap_exact_out(&user, true, 1_000, u64::MAX),
+        AmmError::PoolLocked,
+    );
+    assert_amm_error(
+        test_pool.deposit(&user, 1_000, 1_000_000, 1_000_000),
+        AmmError::PoolLocked,
+    );
+    assert_amm_error(test_pool.withdraw(&provider, 1_000, 0, 0), AmmError::PoolLocked);
+
+    test_pool.set_locked(false).unwrap();
+    test_pool.swap(&user, true, 1_000, 1).unwrap();
+}
+
+#[test]
+fn expired_trades_are_rejected() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(1_000_000, 1_000_000);
+    let (pool, owner) = (test_pool.pool, user.pubkey());
+    let expired = Some(test_pool.now() - 1);
+
+    for ix in [
+        instructions::swap(&pool, owner, true, 1_000, 1, expired),
+        instructions::swap_exact_out(&pool, owner, true, 1_000, u64::MAX, expired),
+        instructions::deposit(&pool, owner, 1_000, 1_000_000, 1_000_000, expired),
+    ] {
+        assert_amm_error(test_pool.send(ix, &user), AmmError::OfferExpired);
+    }
+    let ix = instructions::withdraw(&pool, provider.pubkey(), 1_000, 0, 0, expired);
+    assert_amm_error(test_pool.send(ix, &provider), AmmError::OfferExpired);
+}
+
+#[test]
+fn trades_go_through_until_their_deadline() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(1_000_000, 1_000_000);
+    let (pool, owner) = (test_pool.pool, user.pubkey());
+    let deadline = Some(test_pool.now());
+
+    for ix in [
+        instructions::swap(&pool, owner, true, 1_000, 1, deadline),
+        instructions::swap_exact_out(&pool, owner, true, 1_000, u64::MAX, deadline),
+        instructions::deposit(&pool, owner, 1_000, 1_000_000, 1_000_000, deadline),
+    ] {
+        test_pool.send(ix, &user).unwrap();
+    }
+    let ix = instructions::withdraw(&pool, provider.pubkey(), 1_000, 0, 0, deadline);
+    test_pool.send(ix, &provider).unwrap();
+}
+
+#[test]
+fn swap_below_min_amount_out_fails() {
+    let (mut test_pool, _) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(1_000_000, 0);
+
+    let quote = test_pool
+        .state()
+        .quote_swap(true, 1_000_000, test_pool.now())
+        .unwrap();
+    let result = test_pool.swap(&user, true, 1_000_000, quote.amount_out + 1);
+
+    assert_amm_error(result, AmmError::SlippageExceeded);
+    assert_eq!(test_pool.balances(&user.pubkey()), (1_000_000, 0, 0));
+}
+
+#[test]
+fn swap_exact_out_above_max_amount_in_fails() {
+    let (mut test_pool, _) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(2_000_000, 0);
+
+    let quote = test_pool
+        .state()
+        .quote_swap_exact_out(true, 500_000, test_pool.now())
+        .unwrap();
+    let result = test_pool.swap_exact_out(&user, true, 500_000, quote.amount_in - 1);
+
+    assert_amm_error(result, AmmError::SlippageExceeded);
+}
+
+#[test]
+fn deposit_above_max_amounts_fails() {
+    let (mut test_pool, _) = TestPool::with_liquidity(4_000_000, 1_000_000);
+    let user = test_pool.user(10_000_000, 10_000_000);
+
+    let (x, y) = test_pool.state().deposit_amounts(500_000).unwrap();
+
+    assert_amm_error(
+        test_pool.deposit(&user, 500_000, x - 1, y),
+        AmmError::SlippageExceeded,
+    );
+    assert_amm_error(
+        test_pool.deposit(&user, 500_000, x, y - 1),
+        AmmError::SlippageExceeded,
+    );
+}
+
+#[test]
+fn first_deposit_below_min_lp_amount_fails() {
+    let mut test_pool = TestPool::new(FEE, 0);
+    let user = test_pool.user(4_000_000, 1_000_000);
+
+    let min_lp_amount = test_pool.state().lp_to_mint(4_000_000, 1_000_000).unwrap();
+    let result = test_pool.deposit(&user, min_lp_amount + 1, 4_000_000, 1_000_000);
+
+    assert_amm_error(result, AmmError::SlippageExceeded);
+}
+
+#[test]
+fn withdraw_below_min_amounts_fails() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(4_000_000, 1_000_000);
+
+    let (x, y) = test_pool.state().withdraw_amounts(100_000).unwrap();
+
+    assert_amm_error(
+        test_pool.withdraw(&provider, 100_000, x + 1, y),
+        AmmError::SlippageExceeded,
+    );
+    assert_amm_error(
+        test_pool.withdraw(&provider, 100_000, x, y + 1),
+        AmmError::SlippageExceeded,
+    );
+}
+
+#[test]
+fn dynamic_fee_grows_with_volatility_and_decays() {
+    let (mut test_pool, _) = TestPool::with_liquidity(1_000_000_000, 1_000_000_000);
+    let user = test_pool.user(200_000_000, 0);
+    let (min_fee, max_fee, decay_period) = (10, 200, 600);
+    test_pool
+        .update(instruction::SetDynamicFee {
+            min_fee,
+            max_fee,
+            volatility_factor: 1_000,
+            decay_period,
+        })
+        .unwrap();
+    assert_eq!(test_pool.config().swap_fee(test_pool.now()), min_fee);
+
+    // A 5% trade moves the price by about 10%, a tenth of which is added to the fee.
+    let quote = test_pool
+        .state()
+        .quote_swap(true, 50_000_000, test_pool.now())
+        .unwrap();
+    test_pool.swap(&user, true, 50_000_000, quote.amount_out).unwrap();
+    let fee_after_one = test_pool.config().swap_fee(test_pool.now());
+    assert!(fee_after_one > min_fee && fee_after_one < max_fee, "{fee_after_one}");
+
+    // The next swap is charged the higher fee, as quoted.
+    let quote = test_pool
+        .state()
+        .quote_swap(true, 50_000_000, test_pool.now())
+        .unwrap();
+    assert!(quote.fee > 50_000_000 * min_fee as u64 / 10_000);
+    test_pool.swap(&user, true, 50_000_000, quote.amount_out).unwrap();
+    let fee_after_two = test_pool.config().swap_fee(test_pool.now());
+    assert!(fee_after_two > fee_after_one && fee_after_two <= max_fee, "{fee_after_two}");
+
+    // Volatility decays linearly, back to the calm fee after `decay_period`.
+    test_pool.warp(decay_period as i64 / 2);
+    let fee_half_decayed = test_pool.config().swap_fee(test_pool.now());
+    assert!(fee_half_decayed > min_fee && fee_half_decayed < fee_after_two);
+    test_pool.warp(decay_period as i64 / 2);
+    assert_eq!(test_pool.config().swap_fee(test_pool.now()), min_fee);
+}
+
+#[test]
+fn dynamic_fee_is_capped_at_max_fee() {
+    let (mut test_pool, _) = TestPool::with_liquidity(1_000_000_000, 1_000_000_000);
+    let user = test_pool.user(500_000_000, 0);
+    test_pool
+        .update(instruction::SetDynamicFee {
+            min_fee: 10,
+            max_fee: 200,
+            volatility_factor: 10_000,
+            decay_period: 600,
+        })
+        .unwrap();
+
+    test_pool.swap(&user, true, 500_000_000, 1).unwrap();
+
+    assert_eq!(test_pool.config().swap_fee(test_pool.now()), 200);
+}
+
+#[test]
+fn dynamic_fee_rejects_full_max_fee() {
+    let mut test_pool = TestPool::new(FEE, 0);
+
+    let result = test_pool.update(instruction::SetDynamicFee {
+        min_fee: 10,
+        max_fee: 10_000,
+        volatility_factor: 1_000,
+        decay_period: 600,
+    });
+
+    assert_amm_error(result, AmmError::InvalidFee);
+}
+
+#[test]
+fn farm_rewards_accrue_pro_rata() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let depositor = test_pool.user(1_000_000, 1_000_000);
+    test_pool.deposit(&depositor, 1_000_000, 1_000_000, 1_000_000).unwrap();
+    let farm = test_pool.create_farm(100, 1_000, 100_000);
+
+    test_pool.stake(&provider, &farm, 2_000_000).unwrap();
+    test_pool.stake(&depositor, &farm, 1_000_000).unwrap();
+    assert_eq!(test_pool.farm(&farm).total_staked, 3_000_000);
+
+    test_pool.warp(300);
+    test_pool.harvest(&provider, &farm).unwrap();
+    test_pool.harvest(&depositor, &farm).unwrap();
+
+    // 300 seconds at 100 per second, split 2:1. The accumulator rounds down.
+    let provider_rewards =
+        test_pool.token_balance(&test_pool.reward_account(&farm, &provider.pubkey()));
+    let depositor_rewards =
+        test_pool.token_balance(&test_pool.reward_account(&farm, &depositor.pubkey()));
+    assert!(provider_rewards.abs_diff(20_000) <= 1, "{provider_rewards}");
+    assert!(depositor_rewards.abs_diff(10_000) <= 1, "{depositor_rewards}");
+}
+
+#[test]
+fn farm_rewards_stop_at_end_timestamp() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let farm = test_pool.create_farm(100, 1_000, 1_000_000);
+    // Nothing accrues while the farm is empty.
+    test_pool.warp(200);
+    test_pool.stake(&provider, &farm, 1_000_000).unwrap();
+
+    test_pool.warp(5_000);
+    test_pool.harvest(&provider, &farm).unwrap();
+
+    let rewards = test_pool.token_balance(&test_pool.reward_account(&farm, &provider.pubkey()));
+    assert!(rewards.abs_diff(80_000) <= 1, "{rewards}");
+    assert_eq!(test_pool.stake_position(&farm, &provider.pubkey()).rewards_owed, 0);
+}
+
+#[test]
+fn harvest_pays_at_most_the_reward_vault() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let farm = test_pool.create_farm(100, 1_000, 30_000);
+    test_pool.stake(&provider, &farm, 1_000_000).unwrap();
+
+    test_pool.warp(1_000);
+    test_pool.harvest(&provider, &farm).unwrap();
+
+    let reward_account = test_pool.reward_account(&farm, &provider.pubkey());
+    assert_eq!(test_pool.token_balance(&reward_account), 30_000);
+    let owed = test_pool.stake_position(&farm, &provider.pubkey()).rewards_owed;
+    assert!(owed.abs_diff(70_000) <= 1, "{owed}");
+    assert_amm_error(test_pool.harvest(&provider, &farm), AmmError::ZeroBalance);
+
+    // The rest is paid once the farm is funded again.
+    test_pool.fund_farm(&farm, 100_000).unwrap();
+    test_pool.harvest(&provider, &farm).unwrap();
+    assert_eq!(test_pool.token_balance(&reward_account), 30_000 + owed);
+    assert_eq!(test_pool.stake_position(&farm, &provider.pubkey()).rewards_owed, 0);
+}
+
+#[test]
+fn unstake_above_stake_fails() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let farm = test_pool.create_farm(100, 1_000, 0);
+    let (_, _, lp_balance) = test_pool.balances(&provider.pubkey());
+    test_pool.stake(&provider, &farm, 1_000_000).unwrap();
+
+    assert_amm_error(
+        test_pool.unstake(&provider, &farm, 1_000_001),
+        AmmError::InsufficientBalance,
+    );
+
+    test_pool.unstake(&provider, &farm, 1_000_000).unwrap();
+    assert_eq!(test_pool.balances(&provider.pubkey()).2, lp_balance);
+    assert_eq!(test_pool.farm(&farm).total_staked, 0);
+}
+
+#[test]
+fn farm_reward_rate_is_capped() {
+    let (mut test_pool, _) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let farm = test_pool.create_farm(MAX_REWARD_RATE, 1_000, 0);
+    let end_timestamp = test_pool.now() + 1_000;
+
+    assert_amm_error(
+        test_pool.update_farm(&farm, MAX_REWARD_RATE + 1, end_timestamp),
+        AmmError::InvalidRewardSchedule,
+    );
+    test_pool.update_farm(&farm, 1, end_timestamp).unwrap();
+    assert_eq!(test_pool.farm(&farm).reward_rate, 1);
+
+    let reward_mint = test_pool.new_mint();
+    let too_fast = TestFarm {
+        farm: farm_address(&test_pool.pool.config, &reward_mint).0,
+        reward_mint,
+    };
+    assert_amm_error(
+        test_pool.initialize_farm(&too_fast, MAX_REWARD_RATE + 1, end_timestamp),
+        AmmError::InvalidRewardSchedule,
+    );
+}
+
+#[test]
+fn swap_above_max_price_impact_fails() {
+    let (mut test_pool, _) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(1_000_000, 1_000_000);
+    test_pool
+        .update(instruction::UpdateMaxPriceImpact {
+            max_price_impact_bps: 100,
+        })
+        .unwrap();
+
+    // About 2% of the reserves moves the price by about 4%.
+    let state = test_pool.state();
+    assert!(state.price_move_bps(true, 200_000, test_pool.now()).unwrap() > 100);
+    assert_amm_error(
+        test_pool.swap(&user, true, 200_000, 1),
+        AmmError::PriceImpactExceeded,
+    );
+    assert_amm_error(
+        test_pool.swap_exact_out(&user, false, 200_000, u64::MAX),
+        AmmError::PriceImpactExceeded,
+    );
+    assert_eq!(test_pool.balances(&user.pubkey()), (1_000_000, 1_000_000, 0));
+
+    // Removing the cap lets the same swap through.
+    test_pool
+        .update(instruction::UpdateMaxPriceImpact {
+            max_price_impact_bps: 0,
+        })
+        .unwrap();
+    test_pool.swap(&user, true, 200_000, 1).unwrap();
+}
+
+#[test]
+fn swap_within_max_price_impact_succeeds() {
+    let (mut test_pool, _) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(1_000_000, 0);
+    test_pool
+        .update(instruction::UpdateMaxPriceImpact {
+            max_price_impact_bps: 100,
+        })
+        .unwrap();
+
+    let state = test_pool.state();
+    assert!(state.price_move_bps(true, 20_000, test_pool.now()).unwrap() <= 100);
+    let quote = state.quote_swap(true, 20_000, test_pool.now()).unwrap();
+    test_pool.swap(&user, true, 20_000, quote.amount_out).unwrap();
+
+    assert_eq!(
+        test_pool.balances(&user.pubkey()),
+        (980_000, quote.amount_out, 0)
+    );
+}
+
+/// Fee LiteSVM charges per transaction signature.
+const SIGNATURE_FEE: u64 = 5_000;
+
+#[test]
+fn limit_order_executes_once_the_price_is_reached() {
+    let (mut test_pool, _) = TestPool::with_liquidity(1_000_000, 1_000_000);
+    let owner = test_pool.user(10_000, 0);
+    let keeper = test_pool.user(0, 0);
+    let expires_at = test_pool.now() + 3_600;
+    let keeper_tip = 1_000_000;
+
+    // Asks about 5% above the pool price.
+    let order = test_pool
+        .place_limit_order(&owner, 1, true, 10_000, 10_500, expires_at, keeper_tip)
+        .unwrap();
+    assert_eq!(test_pool.token_balance(&test_pool.order_escrow(&order)), 10_000);
+    assert_amm_error(
+        test_pool.execute_limit_order(&keeper, &order),
+        AmmError::SlippageExceeded,
+    );
+
+    // Buying token X moves the price past the limit.
+    let trader = test_pool.user(0, 200_000);
+    test_pool.swap(&trader, false, 200_000, 1).unwrap();
+    let quote = test_pool
+        .state()
+        .quote_swap(true, 10_000, test_pool.now())
+        .unwrap();
+    assert!(quote.amount_out >= 10_500);
+
+    let owner_lamports = test_pool.lamports(&owner.pubkey());
+    let order_lamports = test_pool.lamports(&order.order);
+    let escrow_lamports = test_pool.lamports(&test_pool.order_escrow(&order));
+    let keeper_lamports = test_pool.lamports(&keeper.pubkey());
+    test_pool.execute_limit_order(&keeper, &order).unwrap();
+
+    assert_eq!(
+        test_pool.balances(&owner.pubkey()),
+        (0, quote.amount_out, 0)
+    );
+    // The keeper only gets the tip, the tokens went straight from the escrow to the owner.
+    assert_eq!(test_pool.balances(&keeper.pubkey()), (0, 0, 0));
+    assert_eq!(
+        test_pool.lamports(&keeper.pubkey()),
+        keeper_lamports + keeper_tip - SIGNATURE_FEE
+    );
+    // The order and its escrow are closed, their rent going back to the owner.
+    assert_eq!(test_pool.lamports(&order.order), 0);
+    assert_eq!(test_pool.lamports(&test_pool.order_escrow(&order)), 0);
+    assert_eq!(
+        test_pool.lamports(&owner.pubkey()),
+        owner_lamports + order_lamports + escrow_lamports - keeper_tip
+    );
+}
+
+#[test]
+fn expired_limit_order_cannot_be_executed() {
+    let (mut test_pool, _) = TestPool::with_liquidity(1_000_000, 1_000_000);
+    let owner = test_pool.user(10_000, 0);
+    let keeper = test_pool.user(0, 0);
+    let expires_at = test_pool.now() + 60;
+
+    // Any price is good enough, only the expiry stops the order.
+    let order = test_pool
+        .place_limit_order(&owner, 1, true, 10_000, 1, expires_at, 0)
+        .unwrap();
+    test_pool.warp(61);
+
+    assert_amm_error(
+        test_pool.execute_limit_order(&keeper, &order),
+        AmmError::OfferExpired,
+    );
+    assert_eq!(test_pool.token_balance(&test_pool.order_escrow(&order)), 10_000);
+
+    // The owner can still get the escrow back.
+    test_pool.cancel_limit_order(&owner, &order).unwrap();
+    assert_eq!(test_pool.balances(&owner.pubkey()), (10_000, 0, 0));
+}
+
+#[test]
+fn cancel_limit_order_refunds_the_escrow_and_the_tip() {
+    let (mut test_pool, _) = TestPool::with_liquidity(1_000_000, 1_000_000);
+    let owner = test_pool.user(0, 10_000);
+    let expires_at = test_pool.now() + 3_600;
+    let owner_lamports = test_pool.lamports(&owner.pubkey());
+
+    let order = test_pool
+        .place_limit_order(&owner, 7, false, 10_000, 20_000, expires_at, 1_000_000)
+        .unwrap();
+    assert_eq!(test_pool.balances(&owner.pubkey()), (0, 0, 0));
+
+    // Nobody else can cancel it.
+    let other = test_pool.user(0, 0);
+    assert!(test_pool.cancel_limit_order(&other, &order).is_err());
+
+    test_pool.cancel_limit_order(&owner, &order).unwrap();
+    assert_eq!(test_pool.balances(&owner.pubkey()), (0, 10_000, 0));
+    assert_eq!(test_pool.lamports(&order.order), 0);
+    assert_eq!(test_pool.lamports(&test_pool.order_escrow(&order)), 0);
+    // Only the fees of the two transactions are gone.
+    assert_eq!(
+        test_pool.lamports(&owner.pubkey()),
+        owner_lamports - 2 * SIGNATURE_FEE
+    );
+}
+
+#[test]
+fn create_pool_fits_the_default_compute_budget() {
+    let mut test_pool = TestPool::new(FEE, 0);
+
+    let compute_units = test_pool.create_pool(FEE).unwrap();
+    // Pools can be created without a compute budget instruction.
+    assert!(compute_units <= 200_000, "create_pool used {compute_units} compute units");
+}
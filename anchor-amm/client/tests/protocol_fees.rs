@@ -0,0 +1,161 @@
+//! Protocol fees against the compiled program: `anchor build && cargo test -p anchor-amm-client`.
+
+mod common;
+
+use anchor_amm::{error::AmmError, instruction, BASIS_POINTS};
+use anchor_lang::prelude::Pubkey;
+use common::{assert_amm_error, TestPool, FEE};
+use solana_sdk::signer::Signer;
+
+const LIQUIDITY: u64 = 10_000_000;
+/// Share of the swap fee kept for the protocol, 20%.
+const PROTOCOL_FEE: u16 = 2_000;
+
+/// A pool charging `PROTOCOL_FEE` after one swap in each direction.
+fn pool_with_accrued_fees() -> TestPool {
+    let mut test_pool = TestPool::new(FEE, PROTOCOL_FEE);
+    test_pool.seed_liquidity(LIQUIDITY, LIQUIDITY);
+    let user = test_pool.user(1_000_000, 1_000_000);
+    test_pool.swap(&user, true, 1_000_000, 1).unwrap();
+    test_pool.swap(&user, false, 1_000_000, 1).unwrap();
+    test_pool
+}
+
+fn protocol_share(fee: u64) -> u64 {
+    fee * PROTOCOL_FEE as u64 / BASIS_POINTS as u64
+}
+
+#[test]
+fn swaps_accrue_the_protocol_share_of_the_fee() {
+    let mut test_pool = TestPool::new(FEE, PROTOCOL_FEE);
+    test_pool.seed_liquidity(LIQUIDITY, LIQUIDITY);
+    let user = test_pool.user(1_000_000, 1_000_000);
+
+    let quote_x = test_pool.state().quote_swap(true, 1_000_000, test_pool.now()).unwrap();
+    test_pool.swap(&user, true, 1_000_000, 1).unwrap();
+    let quote_y = test_pool.state().quote_swap(false, 1_000_000, test_pool.now()).unwrap();
+    test_pool.swap(&user, false, 1_000_000, 1).unwrap();
+
+    let state = test_pool.state();
+    let (fees_x, fees_y) = (protocol_share(quote_x.fee), protocol_share(quote_y.fee));
+    assert!(fees_x > 0 && fees_y > 0);
+    assert_eq!(
+        (state.config.protocol_fees_x, state.config.protocol_fees_y),
+        (fees_x, fees_y)
+    );
+    // The fees sit in the vaults on top of the reserves.
+    let (reserve_x, reserve_y) = state.reserves().unwrap();
+    assert_eq!(state.pool_token_x_vault_amount, reserve_x + fees_x);
+    assert_eq!(state.pool_token_y_vault_amount, reserve_y + fees_y);
+}
+
+#[test]
+fn deposits_and_withdrawals_exclude_unclaimed_protocol_fees() {
+    let mut test_pool = pool_with_accrued_fees();
+    let user = test_pool.user(LIQUIDITY, LIQUIDITY);
+    let lp_amount = 500_000;
+
+    let state = test_pool.state();
+    let (x, y) = state.deposit_amounts(lp_amount).unwrap();
+    // Priced on the vault balances, the deposit would cost more.
+    let (vault_x, vault_y) = state
+        .config
+        .deposit_amounts(
+            state.pool_token_x_vault_amount,
+            state.pool_token_y_vault_amount,
+            state.lp_supply,
+            lp_amount,
+        )
+        .unwrap();
+    assert!(vault_x > x && vault_y > y);
+
+    test_pool.deposit(&user, lp_amount, x, y).unwrap();
+    assert_eq!(
+        test_pool.balances(&user.pubkey()),
+        (LIQUIDITY - x, LIQUIDITY - y, lp_amount)
+    );
+
+    let state = test_pool.state();
+    let (out_x, out_y) = state.withdraw_amounts(lp_amount).unwrap();
+    // Priced on the vault balances, the withdrawal would take part of the fees.
+    let (vault_x, vault_y) = state
+        .config
+        .withdraw_amounts(
+            state.pool_token_x_vault_amount,
+            state.pool_token_y_vault_amount,
+            state.lp_supply,
+            lp_amount,
+        )
+        .unwrap();
+    assert!(vault_x > out_x && vault_y > out_y);
+
+    test_pool.withdraw(&user, lp_amount, out_x, out_y).unwrap();
+    assert_eq!(
+        test_pool.balances(&user.pubkey()),
+        (LIQUIDITY - x + out_x, LIQUIDITY - y + out_y, 0)
+    );
+    // The accrued fees are untouched.
+    let config = test_pool.config();
+    assert_eq!(
+        (config.protocol_fees_x, config.protocol_fees_y),
+        (state.config.protocol_fees_x, state.config.protocol_fees_y)
+    );
+}
+
+#[test]
+fn claim_protocol_fees_pays_the_treasury() {
+    let mut test_pool = pool_with_accrued_fees();
+    let treasury = Pubkey::new_unique();
+    test_pool.fund(&treasury, 0, 0);
+    let state = test_pool.state();
+    let admin = test_pool.admin.insecure_clone();
+
+    test_pool.claim_protocol_fees(&admin, &treasury).unwrap();
+
+    assert_eq!(
+        test_pool.balances(&treasury),
+        (state.config.protocol_fees_x, state.config.protocol_fees_y, 0)
+    );
+    let config = test_pool.config();
+    assert_eq!((config.protocol_fees_x, config.protocol_fees_y), (0, 0));
+    assert_eq!(
+        test_pool.state().reserves().unwrap(),
+        state.reserves().unwrap()
+    );
+    // Nothing is left to claim.
+    assert_amm_error(
+        test_pool.claim_protocol_fees(&admin, &treasury),
+        AmmError::ZeroBalance,
+    );
+}
+
+#[test]
+fn claim_protocol_fees_requires_authority() {
+    let mut test_pool = pool_with_accrued_fees();
+    let user = test_pool.user(0, 0);
+
+    assert_amm_error(
+        test_pool.claim_protocol_fees(&user, &user.pubkey()),
+        AmmError::InvalidAuthority,
+    );
+}
+
+#[test]
+fn renounce_authority_requires_claimed_protocol_fees() {
+    let mut test_pool = pool_with_accrued_fees();
+    let treasury = Pubkey::new_unique();
+    test_pool.fund(&treasury, 0, 0);
+
+    assert_amm_error(
+        test_pool.update(instruction::RenounceAuthority {}),
+        AmmError::UnclaimedProtocolFees,
+    );
+
+    let admin = test_pool.admin.insecure_clone();
+    test_pool.claim_protocol_fees(&admin, &treasury).unwrap();
+    test_pool.update(instruction::RenounceAuthority {}).unwrap();
+
+    let config = test_pool.config();
+    assert_eq!(config.authority, None);
+    assert_eq!(config.protocol_fee, 0);
+}
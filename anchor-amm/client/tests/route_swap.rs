@@ -0,0 +1,138 @@
+//! Routes against the compiled program: `anchor build && cargo test -p anchor-amm-client`.
+
+mod common;
+
+use anchor_amm::error::AmmError;
+use anchor_amm_client::pda::PoolAddresses;
+use anchor_lang::prelude::Pubkey;
+use common::{assert_amm_error, TestPool};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+const LIQUIDITY: u64 = 100_000_000;
+const AMOUNT_IN: u64 = 1_000_000;
+
+/// The test pool (X, Y) followed by pools (Y, Z) and (Z, W), all funded.
+/// Returns the pools and the mints X, Y, Z and W.
+fn chain_of_pools() -> (TestPool, Vec<PoolAddresses>, Vec<Pubkey>) {
+    let (mut test_pool, _) = TestPool::with_liquidity(LIQUIDITY, LIQUIDITY);
+    let mut mints = vec![test_pool.pool.token_x_mint, test_pool.pool.token_y_mint];
+    let mut pools = vec![test_pool.pool];
+    for seed in [1, 2] {
+        let mint = test_pool.new_mint();
+        let pool = test_pool.add_pool(mints[mints.len() - 1], mint, seed);
+        // Prices differ from pool to pool, so a wrong route gives a different output.
+        test_pool.seed_pool(&pool, LIQUIDITY, LIQUIDITY * (seed + 1));
+        mints.push(mint);
+        pools.push(pool);
+    }
+    (test_pool, pools, mints)
+}
+
+/// Output of `route` for `amount_in` of `source_mint`, chaining the single-pool quotes.
+fn quote_route(
+    test_pool: &TestPool,
+    source_mint: Pubkey,
+    route: &[PoolAddresses],
+    amount_in: u64,
+) -> u64 {
+    let mut token_in_mint = source_mint;
+    let mut amount = amount_in;
+    for pool in route {
+        let is_token_x = token_in_mint == pool.token_x_mint;
+        amount = test_pool
+            .pool_state(pool)
+            .quote_swap(is_token_x, amount, test_pool.now())
+            .unwrap()
+            .amount_out;
+        token_in_mint = match is_token_x {
+            true => pool.token_y_mint,
+            false => pool.token_x_mint,
+        };
+    }
+    amount
+}
+
+/// A swapper holding `AMOUNT_IN` of `source_mint`.
+fn swapper(test_pool: &mut TestPool, source_mint: &Pubkey) -> Keypair {
+    let swapper = test_pool.user(0, 0);
+    test_pool.set_balance(&swapper.pubkey(), source_mint, AMOUNT_IN);
+    swapper
+}
+
+fn balance(test_pool: &TestPool, owner: &Keypair, mint: &Pubkey) -> u64 {
+    test_pool.token_balance(&test_pool.token_account(&owner.pubkey(), mint))
+}
+
+#[test]
+fn two_hop_route_matches_chained_quotes() {
+    let (mut test_pool, pools, mints) = chain_of_pools();
+    let swapper = swapper(&mut test_pool, &mints[0]);
+    let route = &pools[..2];
+
+    let expected = quote_route(&test_pool, mints[0], route, AMOUNT_IN);
+    let ix = test_pool.route_swap_ix(&swapper.pubkey(), mints[0], route, AMOUNT_IN, expected);
+    test_pool.send(ix, &swapper).unwrap();
+
+    assert_eq!(balance(&test_pool, &swapper, &mints[0]), 0);
+    // The intermediate token goes from vault to vault.
+    assert_eq!(balance(&test_pool, &swapper, &mints[1]), 0);
+    assert_eq!(balance(&test_pool, &swapper, &mints[2]), expected);
+}
+
+#[test]
+fn three_hop_route_matches_chained_quotes() {
+    let (mut test_pool, pools, mints) = chain_of_pools();
+    let swapper = swapper(&mut test_pool, &mints[3]);
+    // W → Z → Y → X, against the direction the pools were chained in.
+    let route: Vec<_> = pools.iter().rev().copied().collect();
+
+    let expected = quote_route(&test_pool, mints[3], &route, AMOUNT_IN);
+    let ix = test_pool.route_swap_ix(&swapper.pubkey(), mints[3], &route, AMOUNT_IN, expected);
+    test_pool.send(ix, &swapper).unwrap();
+
+    assert_eq!(balance(&test_pool, &swapper, &mints[3]), 0);
+    assert_eq!(balance(&test_pool, &swapper, &mints[0]), expected);
+    let (reserve_x, _) = test_pool.state().reserves().unwrap();
+    assert_eq!(reserve_x, LIQUIDITY - expected);
+}
+
+#[test]
+fn route_below_min_amount_out_fails() {
+    let (mut test_pool, pools, mints) = chain_of_pools();
+    let swapper = swapper(&mut test_pool, &mints[0]);
+
+    let expected = quote_route(&test_pool, mints[0], &pools, AMOUNT_IN);
+    let ix = test_pool.route_swap_ix(&swapper.pubkey(), mints[0], &pools, AMOUNT_IN, expected + 1);
+    assert_amm_error(test_pool.send(ix, &swapper), AmmError::SlippageExceeded);
+
+    assert_eq!(balance(&test_pool, &swapper, &mints[0]), AMOUNT_IN);
+    assert_eq!(test_pool.state().reserves().unwrap(), (LIQUIDITY, LIQUIDITY));
+}
+
+#[test]
+fn route_through_the_same_pool_twice_fails() {
+    let (mut test_pool, pools, mints) = chain_of_pools();
+    let swapper = swapper(&mut test_pool, &mints[0]);
+    // X → Y → Z → Y
+    let route = [pools[0], pools[1], pools[1]];
+
+    let ix = test_pool.route_swap_ix(&swapper.pubkey(), mints[0], &route, AMOUNT_IN, 1);
+    assert_amm_error(test_pool.send(ix, &swapper), AmmError::InvalidRoute);
+}
+
+#[test]
+fn route_with_a_foreign_vault_fails() {
+    let (mut test_pool, pools, mints) = chain_of_pools();
+    let swapper = swapper(&mut test_pool, &mints[0]);
+    let route = &pools[..2];
+
+    // The first hop pays into the swapper's own token X account instead of the pool vault.
+    let mut ix = test_pool.route_swap_ix(&swapper.pubkey(), mints[0], route, AMOUNT_IN, 1);
+    let swapper_source_account = test_pool.token_account(&swapper.pubkey(), &mints[0]);
+    for account in ix.accounts.iter_mut() {
+        if account.pubkey == pools[0].pool_token_x_vault {
+            account.pubkey = swapper_source_account;
+        }
+    }
+    assert_amm_error(test_pool.send(ix, &swapper), AmmError::InvalidRoute);
+}
@@ -0,0 +1,123 @@
+//! Token-2022 against the compiled program: `anchor build && cargo test -p anchor-amm-client`.
+
+mod common;
+
+use anchor_amm::{error::AmmError, helpers::curve::initial_liquidity};
+use common::{assert_amm_error, PoolSetup, TestMint, TestPool};
+use solana_sdk::signer::Signer;
+
+const LIQUIDITY: u64 = 10_000_000;
+/// Token X withholds 1% of every transfer.
+const FEE_MINT: TestMint = TestMint::TransferFee {
+    basis_points: 100,
+    maximum_fee: u64::MAX,
+};
+
+/// A pool of `FEE_MINT` and a legacy mint, without liquidity.
+fn transfer_fee_pool() -> TestPool {
+    TestPool::try_with(PoolSetup {
+        mints: [FEE_MINT, TestMint::Spl],
+        ..PoolSetup::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn first_deposit_mints_lp_for_the_amounts_received() {
+    let mut test_pool = transfer_fee_pool();
+    test_pool.seed_liquidity(LIQUIDITY, LIQUIDITY);
+
+    let received_x = LIQUIDITY - FEE_MINT.transfer_fee(LIQUIDITY);
+    assert_eq!(
+        test_pool.state().reserves().unwrap(),
+        (received_x, LIQUIDITY)
+    );
+    assert_eq!(test_pool.lp_supply(), initial_liquidity(received_x, LIQUIDITY));
+}
+
+#[test]
+fn swap_is_priced_on_the_amount_received_by_the_vault() {
+    let mut test_pool = transfer_fee_pool();
+    test_pool.seed_liquidity(LIQUIDITY, LIQUIDITY);
+    let user = test_pool.user(1_000_000, 0);
+    let (reserve_x, _) = test_pool.state().reserves().unwrap();
+
+    let received = 1_000_000 - FEE_MINT.transfer_fee(1_000_000);
+    let quote = test_pool
+        .state()
+        .quote_swap(true, received, test_pool.now())
+        .unwrap();
+    test_pool
+        .swap(&user, true, 1_000_000, quote.amount_out)
+        .unwrap();
+
+    assert_eq!(test_pool.balances(&user.pubkey()), (0, quote.amount_out, 0));
+    assert_eq!(test_pool.state().reserves().unwrap().0, reserve_x + received);
+}
+
+#[test]
+fn swap_slippage_is_checked_on_the_amount_received_by_the_swapper() {
+    let mut test_pool = transfer_fee_pool();
+    test_pool.seed_liquidity(LIQUIDITY, LIQUIDITY);
+    let user = test_pool.user(0, 1_000_000);
+
+    let quote = test_pool
+        .state()
+        .quote_swap(false, 1_000_000, test_pool.now())
+        .unwrap();
+    let received = quote.amount_out - FEE_MINT.transfer_fee(quote.amount_out);
+
+    // The vault sends `amount_out`, the swapper only gets what is left after the fee.
+    assert_amm_error(
+        test_pool.swap(&user, false, 1_000_000, received + 1),
+        AmmError::SlippageExceeded,
+    );
+    test_pool.swap(&user, false, 1_000_000, received).unwrap();
+    assert_eq!(test_pool.balances(&user.pubkey()), (received, 0, 0));
+}
+
+#[test]
+fn deposit_charges_the_transfer_fee_to_the_depositor() {
+    let mut test_pool = transfer_fee_pool();
+    test_pool.seed_liquidity(LIQUIDITY, LIQUIDITY);
+    let user = test_pool.user(LIQUIDITY, LIQUIDITY);
+    let lp_amount = 1_000_000;
+
+    let state = test_pool.state();
+    let (reserve_x, reserve_y) = state.reserves().unwrap();
+    let (x, y) = state.deposit_amounts(lp_amount).unwrap();
+
+    // `max_x` has to cover the amount sent, fee included.
+    assert_amm_error(
+        test_pool.deposit(&user, lp_amount, x, y),
+        AmmError::SlippageExceeded,
+    );
+    test_pool.deposit(&user, lp_amount, 2 * x, y).unwrap();
+
+    let (balance_x, balance_y, lp_balance) = test_pool.balances(&user.pubkey());
+    let (sent_x, received_x) = (
+        LIQUIDITY - balance_x,
+        test_pool.state().reserves().unwrap().0 - reserve_x,
+    );
+    assert!(received_x >= x);
+    assert_eq!(sent_x - received_x, FEE_MINT.transfer_fee(sent_x));
+    assert_eq!((LIQUIDITY - balance_y, lp_balance), (y, lp_amount));
+    assert_eq!(test_pool.state().reserves().unwrap().1, reserve_y + y);
+}
+
+#[test]
+fn initialize_rejects_unsupported_mint_extensions() {
+    for test_mint in [
+        TestMint::NonTransferable,
+        TestMint::PermanentDelegate,
+        TestMint::TransferHook,
+    ] {
+        for mints in [[test_mint, TestMint::Spl], [TestMint::Spl, test_mint]] {
+            let result = TestPool::try_with(PoolSetup {
+                mints,
+                ..PoolSetup::default()
+            });
+            assert_amm_error(result.map(|_| ()), AmmError::UnsupportedMint);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Price oracle against the compiled program: `anchor build && cargo test -p anchor-amm-client`.
+
+mod common;
+
+use anchor_amm::{error::AmmError, OBSERVATION_COUNT, OBSERVATION_INTERVAL};
+use common::{assert_amm_error, TestPool};
+use solana_sdk::signature::Keypair;
+
+const LIQUIDITY: u64 = 1_000_000_000;
+/// Shorthand for the spacing of the oracle observations.
+const INTERVAL: i64 = OBSERVATION_INTERVAL;
+
+/// Spot prices of a pool over time: when each one became live, and its X and Y prices.
+struct PriceHistory(Vec<(i64, u128, u128)>);
+
+impl PriceHistory {
+    fn new(test_pool: &TestPool) -> Self {
+        let mut history = PriceHistory(Vec::new());
+        history.record(test_pool);
+        history
+    }
+
+    /// Records the current spot prices, live from now on.
+    fn record(&mut self, test_pool: &TestPool) {
+        let state = test_pool.state();
+        let (reserve_x, reserve_y) = state.reserves().unwrap();
+        let (price_x, price_y) = state
+            .config
+            .spot_prices(reserve_x, reserve_y, test_pool.now())
+            .unwrap();
+        self.0.push((test_pool.now(), price_x, price_y));
+    }
+
+    /// Time-weighted average prices between `from` and `to`.
+    fn twap(&self, from: i64, to: i64) -> (u128, u128) {
+        let (mut sum_x, mut sum_y) = (0, 0);
+        for (i, &(start, price_x, price_y)) in self.0.iter().enumerate() {
+            let end = self.0.get(i + 1).map_or(to, |next| next.0).min(to);
+            let seconds = (end - start.max(from)).max(0) as u128;
+            sum_x += price_x * seconds;
+            sum_y += price_y * seconds;
+        }
+        let elapsed = (to - from) as u128;
+        (sum_x / elapsed, sum_y / elapsed)
+    }
+}
+
+/// Waits `seconds`, then swaps `amount_in`, alternating directions so the price moves
+/// back and forth.
+fn swap_after(
+    test_pool: &mut TestPool,
+    history: &mut PriceHistory,
+    user: &Keypair,
+    seconds: i64,
+    is_token_x: bool,
+    amount_in: u64,
+) {
+    test_pool.warp(seconds);
+    test_pool.swap(user, is_token_x, amount_in, 1).unwrap();
+    history.record(test_pool);
+}
+
+#[test]
+fn read_twap_averages_from_the_latest_observation_before_the_window() {
+    let (mut test_pool, _) = TestPool::with_liquidity(LIQUIDITY, LIQUIDITY);
+    let user = test_pool.user(LIQUIDITY, LIQUIDITY);
+    let start = test_pool.now();
+    let mut history = PriceHistory::new(&test_pool);
+
+    // Observations at `start`, `start + INTERVAL` and `start + 2 * INTERVAL`.
+    swap_after(&mut test_pool, &mut history, &user, INTERVAL, true, 10_000_000);
+    swap_after(&mut test_pool, &mut history, &user, INTERVAL, false, 30_000_000);
+    test_pool.warp(INTERVAL / 2);
+    let now = test_pool.now();
+
+    // `now - window` falls between the last two observations, the older one is used.
+    let twap = test_pool.read_twap(INTERVAL as u32).unwrap();
+    assert_eq!(twap.elapsed, now - start - INTERVAL);
+    assert_eq!((twap.price_x, twap.price_y), history.twap(start + INTERVAL, now));
+
+    let window = (now - start) as u32;
+    let twap = test_pool.read_twap(window).unwrap();
+    assert_eq!(twap.elapsed, now - start);
+    assert_eq!((twap.price_x, twap.price_y), history.twap(start, now));
+
+    // Nothing was observed before the pool was created.
+    assert_amm_error(
+        test_pool.read_twap(window + 1).map(|_| ()),
+        AmmError::InvalidObservationWindow,
+    );
+}
+
+#[test]
+fn read_twap_only_reaches_back_as_far_as_the_ring_buffer() {
+    let (mut test_pool, _) = TestPool::with_liquidity(LIQUIDITY, LIQUIDITY);
+    let user = test_pool.user(LIQUIDITY, LIQUIDITY);
+    let start = test_pool.now();
+    let mut history = PriceHistory::new(&test_pool);
+
+    // 30 observations after the initial one, one per interval.
+    let swaps = OBSERVATION_COUNT as i64 + 6;
+    for i in 0..swaps {
+        swap_after(&mut test_pool, &mut history, &user, INTERVAL, i % 2 == 0, 1_000_000);
+    }
+    test_pool.warp(INTERVAL / 2);
+    let now = test_pool.now();
+
+    // The buffer wrapped around and now holds the observations of swaps 7 to 30.
+    assert_eq!(test_pool.oracle().index as i64, swaps % OBSERVATION_COUNT as i64);
+    let oldest = start + INTERVAL * (swaps - OBSERVATION_COUNT as i64 + 1);
+    let window = (now - oldest) as u32;
+
+    let twap = test_pool.read_twap(window).unwrap();
+    assert_eq!(twap.elapsed, now - oldest);
+    assert_eq!((twap.price_x, twap.price_y), history.twap(oldest, now));
+
+    assert_amm_error(
+        test_pool.read_twap(window + 1).map(|_| ()),
+        AmmError::InvalidObservationWindow,
+    );
+}
+
+#[test]
+fn trades_within_the_interval_do_not_evict_observations() {
+    let (mut test_pool, _) = TestPool::with_liquidity(LIQUIDITY, LIQUIDITY);
+    let user = test_pool.user(LIQUIDITY, LIQUIDITY);
+    let start = test_pool.now();
+    let mut history = PriceHistory::new(&test_pool);
+
+    // Enough swaps to wrap the buffer twice, all within one interval.
+    for i in 0..2 * OBSERVATION_COUNT {
+        swap_after(&mut test_pool, &mut history, &user, 1, i % 2 == 0, 1_000_000);
+    }
+    let now = test_pool.now();
+    assert!(now - start < INTERVAL);
+
+    // Only the initial observation is stored, but the accumulators followed every swap.
+    assert_eq!(test_pool.oracle().index, 0);
+    let twap = test_pool.read_twap((now - start) as u32).unwrap();
+    assert_eq!(twap.elapsed, now - start);
+    assert_eq!((twap.price_x, twap.price_y), history.twap(start, now));
+
+    // The next trade after a full interval records an observation again.
+    swap_after(&mut test_pool, &mut history, &user, INTERVAL, true, 1_000_000);
+    assert_eq!(test_pool.oracle().index, 1);
+    assert_eq!(test_pool.oracle().latest().timestamp, test_pool.now());
+}
+
+#[test]
+fn read_twap_goes_on_while_the_pool_is_locked() {
+    let (mut test_pool, _) = TestPool::with_liquidity(LIQUIDITY, LIQUIDITY);
+    let start = test_pool.now();
+    let history = PriceHistory::new(&test_pool);
+    test_pool.set_locked(true).unwrap();
+    test_pool.warp(INTERVAL);
+
+    let twap = test_pool.read_twap(INTERVAL as u32).unwrap();
+    assert_eq!(twap.elapsed, INTERVAL);
+    assert_eq!(
+        (twap.price_x, twap.price_y),
+        history.twap(start, test_pool.now())
+    );
+}
@@ -0,0 +1,129 @@
+//! Zaps against the compiled program: `anchor build && cargo test -p anchor-amm-client`.
+
+mod common;
+
+use anchor_amm::{
+    error::AmmError,
+    helpers::curve::{liquidity_for_amount, zap_swap_amount},
+    MIN_AMP,
+};
+use anchor_amm_client::CurveType;
+use common::{assert_amm_error, PoolSetup, TestPool};
+use solana_sdk::signer::Signer;
+
+/// LP tokens `zap_deposit` mints for `amount_in` in a pool without protocol fee.
+fn zap_deposit_lp(test_pool: &TestPool, is_token_x: bool, amount_in: u64) -> u64 {
+    let state = test_pool.state();
+    let now = test_pool.now();
+    let (reserve_x, reserve_y) = state.reserves().unwrap();
+    let (reserve_in, reserve_out) = match is_token_x {
+        true => (reserve_x, reserve_y),
+        false => (reserve_y, reserve_x),
+    };
+    let swap_amount = zap_swap_amount(reserve_in, amount_in, state.config.swap_fee(now)).unwrap();
+    let quote = state.quote_swap(is_token_x, swap_amount, now).unwrap();
+    let lp_in =
+        liquidity_for_amount(amount_in - swap_amount, reserve_in + swap_amount, state.lp_supply);
+    let lp_out =
+        liquidity_for_amount(quote.amount_out, reserve_out - quote.amount_out, state.lp_supply);
+    lp_in.unwrap().min(lp_out.unwrap())
+}
+
+/// Tokens `zap_withdraw` pays out for burning `lp_amount`.
+fn zap_withdraw_amount(test_pool: &TestPool, is_token_x: bool, lp_amount: u64) -> u64 {
+    let state = test_pool.state();
+    let (reserve_x, reserve_y) = state.reserves().unwrap();
+    let (x, y) = state.withdraw_amounts(lp_amount).unwrap();
+    let (amount_kept, amount_swapped) = match is_token_x {
+        true => (x, y),
+        false => (y, x),
+    };
+    let quote = state
+        .config
+        .quote_swap(
+            reserve_x - x,
+            reserve_y - y,
+            state.lp_supply - lp_amount,
+            !is_token_x,
+            amount_swapped,
+            test_pool.now(),
+        )
+        .unwrap();
+    amount_kept + quote.amount_out
+}
+
+#[test]
+fn zap_deposit_mints_the_quoted_lp_amount() {
+    let (mut test_pool, _) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(1_000_000, 0);
+
+    let lp_amount = zap_deposit_lp(&test_pool, true, 1_000_000);
+    test_pool.zap_deposit(&user, true, 1_000_000, lp_amount).unwrap();
+
+    assert!(lp_amount > 0);
+    assert_eq!(test_pool.balances(&user.pubkey()), (0, 0, lp_amount));
+    // Only token X moved: the swapped share stays in the pool.
+    let (reserve_x, reserve_y) = test_pool.state().reserves().unwrap();
+    assert_eq!((reserve_x, reserve_y), (11_000_000, 10_000_000));
+}
+
+#[test]
+fn zap_deposit_below_min_lp_amount_fails() {
+    let (mut test_pool, _) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(0, 1_000_000);
+
+    let lp_amount = zap_deposit_lp(&test_pool, false, 1_000_000);
+    assert_amm_error(
+        test_pool.zap_deposit(&user, false, 1_000_000, lp_amount + 1),
+        AmmError::SlippageExceeded,
+    );
+    assert_eq!(test_pool.balances(&user.pubkey()), (0, 1_000_000, 0));
+}
+
+#[test]
+fn zap_withdraw_pays_the_quoted_amount() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let lp_amount = test_pool.balances(&provider.pubkey()).2 / 10;
+
+    let amount_out = zap_withdraw_amount(&test_pool, false, lp_amount);
+    test_pool
+        .zap_withdraw(&provider, false, lp_amount, amount_out)
+        .unwrap();
+
+    let (x, y) = test_pool.state().withdraw_amounts(lp_amount).unwrap();
+    assert!(amount_out > y);
+    assert!(amount_out < x + y);
+    let (balance_x, balance_y, _) = test_pool.balances(&provider.pubkey());
+    assert_eq!((balance_x, balance_y), (0, amount_out));
+}
+
+#[test]
+fn zap_withdraw_below_min_amount_out_fails() {
+    let (mut test_pool, provider) = TestPool::with_liquidity(10_000_000, 10_000_000);
+    let lp_balance = test_pool.balances(&provider.pubkey()).2;
+    let lp_amount = lp_balance / 10;
+
+    let amount_out = zap_withdraw_amount(&test_pool, true, lp_amount);
+    assert_amm_error(
+        test_pool.zap_withdraw(&provider, true, lp_amount, amount_out + 1),
+        AmmError::SlippageExceeded,
+    );
+    assert_eq!(test_pool.balances(&provider.pubkey()), (0, 0, lp_balance));
+}
+
+#[test]
+fn zap_deposit_rejects_stable_swap_pools() {
+    let mut test_pool = TestPool::try_with(PoolSetup {
+        curve_type: CurveType::StableSwap,
+        amp: MIN_AMP,
+        ..PoolSetup::default()
+    })
+    .unwrap();
+    test_pool.seed_liquidity(10_000_000, 10_000_000);
+    let user = test_pool.user(1_000_000, 0);
+
+    assert_amm_error(
+        test_pool.zap_deposit(&user, true, 1_000_000, 0),
+        AmmError::InvalidCurve,
+    );
+}
@@ -2,3 +2,56 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+/// Denominator for every fee expressed in basis points (10_000 = 100%).
+#[constant]
+pub const BASIS_POINTS: u16 = 10_000;
+
+/// Maximum number of pools a single `route_swap` can go through.
+pub const MAX_ROUTE_HOPS: usize = 4;
+
+/// Number of remaining accounts describing one hop of a `route_swap`.
+pub const ROUTE_HOP_ACCOUNTS: usize = 7;
+
+/// Number of price observations kept in each pool's oracle ring buffer.
+pub const OBSERVATION_COUNT: usize = 24;
+
+/// Minimum number of seconds between two oracle observations. A burst of trades cannot
+/// flush the ring buffer, which always spans at least `OBSERVATION_COUNT` intervals.
+pub const OBSERVATION_INTERVAL: i64 = 300;
+
+/// Bounds of the StableSwap amplification coefficient.
+#[constant]
+pub const MIN_AMP: u64 = 1;
+#[constant]
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Largest factor the amplification coefficient can move by in a single ramp.
+#[constant]
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+/// Shortest allowed amplification ramp, in seconds.
+#[constant]
+pub const MIN_RAMP_DURATION: i64 = 86_400;
+
+/// LP tokens minted to the pool itself on the first deposit and locked forever, so the
+/// LP supply can never go back to zero and the share price cannot be inflated.
+#[constant]
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+/// Swap fees, in basis points, a canonical pool can be created with.
+/// A mint pair can have one canonical pool per tier.
+#[constant]
+pub const FEE_TIERS: [u16; 4] = [1, 5, 30, 100];
+
+/// Bit set in the seed of every canonical pool config and in no other.
+/// `initialize` rejects seeds with it, so no pool can take a canonical config address
+/// before `create_pool` gets to it.
+#[constant]
+pub const CANONICAL_SEED_FLAG: u64 = 1 << 63;
+
+/// Highest farm `reward_rate`, in reward tokens per second. Keeps the Q64.64
+/// `reward_per_share` accumulator from overflowing for over a century even with a
+/// single LP token unit staked, so unstaking can never fail on it.
+#[constant]
+pub const MAX_REWARD_RATE: u64 = 1 << 32;
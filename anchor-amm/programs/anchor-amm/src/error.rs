@@ -1,4 +1,4 @@
-use anchor_lang::{error::Error, error_code};
+use anchor_lang::error_code;
 use constant_product_curve::CurveError;
 
 #[error_code]
@@ -39,6 +39,40 @@ pub enum AmmError {
     InsufficientBalance,
     #[msg("Zero balance.")]
     ZeroBalance,
+    #[msg("Mint has an extension the pool does not support.")]
+    UnsupportedMint,
+    #[msg("Invalid swap route.")]
+    InvalidRoute,
+    #[msg("Not enough price history for the requested window.")]
+    InvalidObservationWindow,
+    #[msg("Invalid amplification coefficient.")]
+    InvalidAmp,
+    #[msg("Invalid amplification ramp.")]
+    InvalidRamp,
+    #[msg("Operation not supported by this pool curve.")]
+    InvalidCurve,
+    #[msg("Invalid flash loan.")]
+    InvalidFlashLoan,
+    #[msg("Flash loan not repaid.")]
+    FlashLoanNotRepaid,
+    #[msg("Mints must be sorted, token X first.")]
+    InvalidMintOrder,
+    #[msg("Fee is not one of the canonical fee tiers.")]
+    InvalidFeeTier,
+    #[msg("Invalid dynamic fee parameters.")]
+    InvalidDynamicFee,
+    #[msg("Farm end time must not be in the past.")]
+    InvalidRewardSchedule,
+    #[msg("Swap moves the price more than the pool allows.")]
+    PriceImpactExceeded,
+    #[msg("Metadata URI is too long.")]
+    InvalidMetadataUri,
+    #[msg("Protocol fees must be claimed first.")]
+    UnclaimedProtocolFees,
+    #[msg("Invalid pool seed.")]
+    InvalidPoolSeed,
+    #[msg("StableSwap mints must have the same decimals.")]
+    MismatchedDecimals,
 }
 
 impl From<CurveError> for AmmError {
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+/// Emitted for every swap, including each hop of a `route_swap`.
+///
+/// Reserves exclude unclaimed protocol fees and are read after the swap settled.
+#[event]
+pub struct SwapEvent {
+    pub config: Pubkey,
+    pub swapper: Pubkey,
+    /// `true` when token X was sold for token Y.
+    pub is_token_x: bool,
+    /// Input sent into the pool, transfer fee included.
+    pub amount_in: u64,
+    /// Output sent by the pool, before any transfer fee.
+    pub amount_out: u64,
+    /// Swap fee charged, in the input token.
+    pub fee: u64,
+    /// Part of `fee` set aside for the protocol.
+    pub protocol_fee: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when liquidity is added to a pool.
+#[event]
+pub struct DepositEvent {
+    pub config: Pubkey,
+    pub depositor: Pubkey,
+    /// Token X sent by the depositor, transfer fee included.
+    pub amount_x: u64,
+    /// Token Y sent by the depositor, transfer fee included.
+    pub amount_y: u64,
+    /// LP tokens minted to the depositor.
+    pub lp_amount: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when liquidity is removed from a pool.
+#[event]
+pub struct WithdrawEvent {
+    pub config: Pubkey,
+    pub withdrawer: Pubkey,
+    /// Token X sent by the pool, before any transfer fee.
+    pub amount_x: u64,
+    /// Token Y sent by the pool, before any transfer fee.
+    pub amount_y: u64,
+    /// LP tokens burned.
+    pub lp_amount: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,287 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, helpers::stable_swap::U256, BASIS_POINTS};
+
+/// Outcome of pricing a swap against a pool curve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Input received by the pool, fee included.
+    pub amount_in: u64,
+    /// Part of `amount_in` charged as the swap fee.
+    pub fee: u64,
+    /// Output paid by the pool.
+    pub amount_out: u64,
+}
+
+/// Swap fee charged on `amount_in`, rounded down.
+pub fn fee_amount(amount_in: u64, fee: u16) -> Result<u64> {
+    Ok(((amount_in as u128)
+        .checked_mul(fee as u128)
+        .ok_or(AmmError::Overflow)?
+        / BASIS_POINTS as u128) as u64)
+}
+
+/// Fee charged on `amount`, rounded up so the pool is never undercharged.
+pub fn fee_amount_rounded_up(amount: u64, fee: u16) -> Result<u64> {
+    let fee = div_ceil(
+        (amount as u128)
+            .checked_mul(fee as u128)
+            .ok_or(AmmError::Overflow)?,
+        BASIS_POINTS as u128,
+    )?;
+    Ok(u64::try_from(fee).map_err(|_| AmmError::Overflow)?)
+}
+
+/// Share of `reserve` matching `lp_amount` out of `lp_supply`.
+/// Rounds up when `round_up` is set, so deposits never underpay.
+pub fn proportional_amount(
+    reserve: u64,
+    lp_amount: u64,
+    lp_supply: u64,
+    round_up: bool,
+) -> Result<u64> {
+    let numerator = (reserve as u128)
+        .checked_mul(lp_amount as u128)
+        .ok_or(AmmError::Overflow)?;
+    let amount = match round_up {
+        true => div_ceil(numerator, lp_supply as u128)?,
+        false => {
+            require!(lp_supply != 0, AmmError::ZeroBalance);
+            numerator / lp_supply as u128
+        }
+    };
+    Ok(u64::try_from(amount).map_err(|_| AmmError::Overflow)?)
+}
+
+/// LP supply created by the first deposit: the geometric mean `sqrt(x * y)` of the
+/// amounts deposited, which does not depend on the ratio they are deposited at.
+pub fn initial_liquidity(amount_x: u64, amount_y: u64) -> u64 {
+    sqrt(amount_x as u128 * amount_y as u128) as u64
+}
+
+/// LP tokens matching `amount` of a token out of `reserve`, rounded down.
+pub fn liquidity_for_amount(amount: u64, reserve: u64, lp_supply: u64) -> Result<u64> {
+    require!(reserve != 0, AmmError::ZeroBalance);
+    let liquidity = (amount as u128)
+        .checked_mul(lp_supply as u128)
+        .ok_or(AmmError::Overflow)?
+        / reserve as u128;
+    Ok(u64::try_from(liquidity).map_err(|_| AmmError::Overflow)?)
+}
+
+/// Part of a single-sided deposit of `amount_in` that has to be swapped through a
+/// constant product pool so the rest and the swap output match the pool ratio.
+///
+/// With `B = BASIS_POINTS` and `F = fee`, solving for the swapped amount `s` gives
+/// `s = (sqrt(((2B - F)·r)^2 + 4B(B - F)·a·r) - (2B - F)·r) / (2(B - F))`.
+pub fn zap_swap_amount(reserve_in: u64, amount_in: u64, fee: u16) -> Result<u64> {
+    require!(fee < BASIS_POINTS, AmmError::InvalidFee);
+    let (b, f) = (U256::from(BASIS_POINTS), U256::from(fee));
+    let (r, a) = (U256::from(reserve_in), U256::from(amount_in));
+
+    let k = (b * 2u64 - f) * r;
+    let root = (k * k + b * (b - f) * a * r * 4u64).integer_sqrt();
+    let swap_amount = (root - k) / ((b - f) * 2u64);
+
+    Ok(swap_amount.min(a).as_u64())
+}
+
+/// Computes the input a constant product pool needs, fee included, to pay out
+/// exactly `amount_out` from `reserve_out`.
+///
+/// Both divisions round up, so swapping the returned amount through
+/// `ConstantProduct::swap` always yields at least `amount_out`.
+pub fn amount_in_for_exact_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+) -> Result<u64> {
+    require!(amount_out < reserve_out, AmmError::InsufficientBalance);
+
+    // x * dy / (y - dy): input needed after the fee has been taken.
+    let numerator = (reserve_in as u128)
+        .checked_mul(amount_out as u128)
+        .ok_or(AmmError::Overflow)?;
+    let denominator = (reserve_out - amount_out) as u128;
+    let amount_in_after_fee = div_ceil(numerator, denominator)?;
+
+    gross_up_for_fee(
+        u64::try_from(amount_in_after_fee).map_err(|_| AmmError::Overflow)?,
+        fee,
+    )
+}
+
+/// Smallest input that still leaves `amount_in_after_fee` once the swap fee is taken.
+pub fn gross_up_for_fee(amount_in_after_fee: u64, fee: u16) -> Result<u64> {
+    require!(fee < BASIS_POINTS, AmmError::InvalidFee);
+    let amount_in = div_ceil(
+        (amount_in_after_fee as u128)
+            .checked_mul(BASIS_POINTS as u128)
+            .ok_or(AmmError::Overflow)?,
+        (BASIS_POINTS - fee) as u128,
+    )?;
+
+    Ok(u64::try_from(amount_in).map_err(|_| AmmError::Overflow)?)
+}
+
+fn div_ceil(numerator: u128, denominator: u128) -> Result<u128> {
+    require!(denominator != 0, AmmError::ZeroBalance);
+    Ok(numerator
+        .checked_add(denominator - 1)
+        .ok_or(AmmError::Overflow)?
+        / denominator)
+}
+
+/// Relative move from `price_before` to `price_after` in basis points, either direction.
+pub fn price_change_bps(price_before: u128, price_after: u128) -> u64 {
+    if price_before == 0 {
+        return 0;
+    }
+    let change = U256::from(price_before.abs_diff(price_after)) * U256::from(BASIS_POINTS)
+        / U256::from(price_before);
+    change.min(U256::from(u64::MAX)).as_u64()
+}
+
+/// Integer square root, rounded down.
+fn sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    // Newton's method from an initial guess above the root.
+    let mut x = 1u128 << ((128 - value.leading_zeros()).div_ceil(2));
+    loop {
+        let y = (x + value / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Spot price of the base token in units of the quote token, as a Q64.64 fixed
+/// point number: `(reserve_quote << 64) / reserve_base`.
+pub fn spot_price_q64(reserve_base: u64, reserve_quote: u64) -> Result<u128> {
+    require!(reserve_base != 0, AmmError::ZeroBalance);
+    Ok(((reserve_quote as u128) << 64) / reserve_base as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEES: [u16; 4] = [0, 30, 100, BASIS_POINTS - 1];
+    const RESERVES: [(u64, u64); 4] = [
+        (1_000_000, 1_000_000),
+        (1_000, 1_000_000_000),
+        (1_000_000_000, 1_000),
+        (u64::MAX / 2, u64::MAX / 3),
+    ];
+
+    #[test]
+    fn exact_out_input_keeps_k() {
+        for fee in FEES {
+            for (reserve_in, reserve_out) in RESERVES {
+                for amount_out in [1, reserve_out / 1_000, reserve_out / 2, reserve_out - 1] {
+                    let Ok(amount_in) =
+                        amount_in_for_exact_out(reserve_in, reserve_out, amount_out, fee)
+                    else {
+                        // Only inputs that no longer fit in a u64 may fail.
+                        continue;
+                    };
+                    let amount_in_after_fee = amount_in - fee_amount(amount_in, fee).unwrap();
+
+                    let k_before = reserve_in as u128 * reserve_out as u128;
+                    let k_after = (reserve_in as u128 + amount_in_after_fee as u128)
+                        * (reserve_out - amount_out) as u128;
+                    assert!(
+                        k_after >= k_before,
+                        "fee {fee}, ({reserve_in}, {reserve_out}), out {amount_out}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exact_out_rejects_draining_the_pool() {
+        assert!(amount_in_for_exact_out(1_000, 1_000, 1_000, 30).is_err());
+        assert!(amount_in_for_exact_out(1_000, 1_000, 2_000, 30).is_err());
+    }
+
+    #[test]
+    fn gross_up_covers_the_fee() {
+        for fee in FEES {
+            for amount in [0, 1, 999, 1_000_000, u64::MAX / BASIS_POINTS as u64] {
+                let gross = gross_up_for_fee(amount, fee).unwrap();
+                assert!(gross - fee_amount(gross, fee).unwrap() >= amount, "fee {fee}, {amount}");
+            }
+        }
+        assert!(gross_up_for_fee(1_000, BASIS_POINTS).is_err());
+    }
+
+    #[test]
+    fn sqrt_rounds_down() {
+        let values = [0, 1, 2, 3, 4, 15, 16, 17, 1 << 64, (1 << 64) - 1, u128::MAX];
+        for value in values {
+            let root = sqrt(value);
+            assert!(root * root <= value, "{value}");
+            assert!(
+                (root + 1).checked_mul(root + 1).map_or(true, |square| square > value),
+                "{value}"
+            );
+        }
+        assert_eq!(sqrt(u128::MAX), u64::MAX as u128);
+    }
+
+    #[test]
+    fn initial_liquidity_is_geometric_mean() {
+        assert_eq!(initial_liquidity(500_000, 500_000), 500_000);
+        assert_eq!(initial_liquidity(10, 1_000), 100);
+        assert_eq!(initial_liquidity(0, 1_000), 0);
+        assert_eq!(initial_liquidity(u64::MAX, u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn zap_swap_leaves_amounts_at_pool_ratio() {
+        let cases = [
+            (1_000_000, 2_000_000, 100_000),
+            (1_000_000, 1_000_000, 5_000_000),
+            (10u64.pow(18), 10u64.pow(15), 10u64.pow(17)),
+            (u64::MAX / 4, u64::MAX / 4, u64::MAX / 4),
+        ];
+        // Near 100% fees the output rounds to a handful of units, too coarse for a ratio.
+        for fee in [0, 30, 100, 1_000] {
+            for (reserve_in, reserve_out, amount_in) in cases {
+                let swapped = zap_swap_amount(reserve_in, amount_in, fee).unwrap();
+                assert!(swapped <= amount_in);
+
+                let swapped_after_fee = (swapped - fee_amount(swapped, fee).unwrap()) as u128;
+                let amount_out = reserve_out as u128 * swapped_after_fee
+                    / (reserve_in as u128 + swapped_after_fee);
+
+                // kept / amount_out == (reserve_in + swapped) / (reserve_out - amount_out)
+                let kept = (amount_in - swapped) as u128;
+                let lhs = U256::from(kept) * U256::from(reserve_out as u128 - amount_out);
+                let rhs = U256::from(reserve_in as u128 + swapped as u128) * U256::from(amount_out);
+                let diff = if lhs > rhs { lhs - rhs } else { rhs - lhs };
+                assert!(
+                    diff <= rhs / 10_000,
+                    "fee {fee}, ({reserve_in}, {reserve_out}), in {amount_in}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn zap_swap_amount_handles_edges() {
+        assert_eq!(zap_swap_amount(1_000_000, 0, 30).unwrap(), 0);
+        assert!(zap_swap_amount(1_000_000, 1_000, BASIS_POINTS).is_err());
+        // Tiny deposits into a deep pool swap about half.
+        let swapped = zap_swap_amount(u64::MAX, 1_000_000, 0).unwrap();
+        assert!(swapped.abs_diff(500_000) <= 1, "{swapped}");
+        // Deposits dwarfing the pool do not overflow.
+        assert!(zap_swap_amount(1, u64::MAX, 30).is_ok());
+        assert!(zap_swap_amount(u64::MAX, u64::MAX, BASIS_POINTS - 1).is_ok());
+    }
+}
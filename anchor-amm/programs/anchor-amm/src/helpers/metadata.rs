@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::mpl_token_metadata::{
+        accounts::Metadata, ID as METADATA_PROGRAM_ID, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH,
+    },
+    token_interface::{
+        spl_token_2022::{
+            extension::{BaseStateWithExtensions, StateWithExtensions},
+            state::Mint as MintState,
+        },
+        spl_token_metadata_interface::state::TokenMetadata,
+        Mint,
+    },
+};
+
+/// Characters of the mint address used as symbol when a mint has no metadata.
+const FALLBACK_SYMBOL_LENGTH: usize = 4;
+
+/// Symbol of `mint`, read from its Metaplex metadata account or, for Token-2022 mints,
+/// from its `TokenMetadata` extension. Mints with neither fall back to the first
+/// characters of their address.
+pub fn mint_symbol(mint: &InterfaceAccount<Mint>, metadata: &AccountInfo) -> String {
+    let symbol = metaplex_symbol(metadata)
+        .or_else(|| token_2022_symbol(mint))
+        .unwrap_or_default();
+    if symbol.is_empty() {
+        mint.key().to_string()[..FALLBACK_SYMBOL_LENGTH].to_string()
+    } else {
+        symbol
+    }
+}
+
+fn metaplex_symbol(metadata: &AccountInfo) -> Option<String> {
+    if metadata.owner != &METADATA_PROGRAM_ID {
+        return None;
+    }
+    let data = metadata.try_borrow_data().ok()?;
+    let metadata = Metadata::safe_deserialize(&data).ok()?;
+    Some(metadata.symbol.trim_matches(char::from(0)).trim().to_string())
+}
+
+fn token_2022_symbol(mint: &InterfaceAccount<Mint>) -> Option<String> {
+    let mint_info = mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data().ok()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data).ok()?;
+    let metadata = mint_state
+        .get_variable_len_extension::<TokenMetadata>()
+        .ok()?;
+    Some(metadata.symbol.trim().to_string())
+}
+
+/// Name and symbol of the LP token of an X/Y pool, `"X-Y LP"` and `"X-Y"`, cut to the
+/// Metaplex length limits.
+pub fn lp_name_and_symbol(symbol_x: &str, symbol_y: &str) -> (String, String) {
+    let pair = format!("{symbol_x}-{symbol_y}");
+    (
+        truncate(&format!("{pair} LP"), MAX_NAME_LENGTH),
+        truncate(&pair, MAX_SYMBOL_LENGTH),
+    )
+}
+
+/// Cuts `value` to at most `max_bytes` bytes without splitting a character.
+fn truncate(value: &str, max_bytes: usize) -> String {
+    let mut end = value.len().min(max_bytes);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    value[..end].to_string()
+}
@@ -1,3 +1,9 @@
+pub mod curve;
+pub mod metadata;
+pub mod settlement;
+pub mod stable_swap;
+pub mod token_extensions;
+
 #[macro_export]
 macro_rules! require_non_zero {
     ($array: expr) => {
@@ -12,13 +18,36 @@ macro_rules! require_not_locked {
     };
 }
 
-// #[macro_export]
-// macro_rules! require_has_update_authority {
-//     ($x: expr) => {
-//         match $x.config.authority{
-//             Some(authority)=>{
-//                 require_keys_eq!()
-//             }
-//         }
-//     };
-// }
+/// Rejects the instruction once the optional `expires_at` unix timestamp has passed.
+#[macro_export]
+macro_rules! require_not_expired {
+    ($expires_at: expr) => {
+        if let Some(expires_at) = $expires_at {
+            require!(
+                Clock::get()?.unix_timestamp <= expires_at,
+                AmmError::OfferExpired
+            );
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! require_has_update_authority {
+    ($x: expr) => {
+        match $x.config.authority {
+            Some(authority) => {
+                require_keys_eq!(authority, $x.authority.key(), AmmError::InvalidAuthority)
+            }
+            None => return err!(AmmError::NoAuthoritySet),
+        }
+    };
+}
+
+/// Rejects fees of 100% or more. A full fee leaves nothing to swap and breaks the
+/// exact-out and zap math, which divide by `BASIS_POINTS - fee`.
+#[macro_export]
+macro_rules! require_valid_fee {
+    ($fee: expr) => {
+        require!($fee < $crate::BASIS_POINTS, AmmError::InvalidFee)
+    };
+}
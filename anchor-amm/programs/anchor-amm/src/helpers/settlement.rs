@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, Mint, TransferChecked};
+
+use crate::{
+    error::AmmError,
+    helpers::{
+        curve::SwapQuote,
+        token_extensions::{amount_received, amount_to_send},
+    },
+    Config, Oracle,
+};
+
+/// Amount a swap is priced on.
+#[derive(Clone, Copy, Debug)]
+pub enum SwapAmount {
+    /// Exactly this much of the input token is sent into the pool, which prices what it
+    /// receives after any transfer fee.
+    ExactIn(u64),
+    /// Exactly this much of the output token is received, after any transfer fee.
+    ExactOut(u64),
+}
+
+/// Transfer of the input token into the pool vault.
+pub struct SwapInput<'a, 'info> {
+    pub from: AccountInfo<'info>,
+    pub to: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    /// Seeds of `authority` when it is a PDA, empty otherwise.
+    pub signer_seeds: &'a [&'a [&'a [u8]]],
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: AccountInfo<'info>,
+}
+
+/// Transfer of the output token out of the pool vault, signed by the pool config.
+pub struct SwapOutput<'a, 'info> {
+    pub from: AccountInfo<'info>,
+    pub to: AccountInfo<'info>,
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: AccountInfo<'info>,
+}
+
+/// A swap against one pool, ready to be settled by [`settle_swap`].
+pub struct SwapLeg<'a, 'info> {
+    pub config: &'a mut Account<'info, Config>,
+    pub oracle: &'a mut Oracle,
+    /// Reserves the swap is priced against.
+    pub reserves: (u64, u64),
+    /// LP supply the swap is priced against.
+    pub lp_supply: u64,
+    /// True when token X goes into the pool.
+    pub is_token_x: bool,
+    pub amount: SwapAmount,
+    /// `None` when the input is already in the vault: it is then priced as is.
+    pub input: Option<SwapInput<'a, 'info>>,
+    /// `None` when the output stays in the vault: it is then priced as is.
+    pub output: Option<SwapOutput<'a, 'info>>,
+}
+
+/// Outcome of [`settle_swap`].
+#[derive(Clone, Copy, Debug)]
+pub struct SettledSwap {
+    /// Input sent into the pool, transfer fee included.
+    pub amount_in: u64,
+    pub quote: SwapQuote,
+    /// Part of `quote.fee` set aside for the protocol.
+    pub protocol_fee: u64,
+}
+
+/// Settles a swap against one pool: feeds the pre-trade reserves into the price
+/// accumulators and the oracle, prices the swap on the pool curve, books the protocol
+/// fee, enforces the price impact limit, updates the dynamic fee volatility, then moves
+/// the input and the output tokens.
+///
+/// Slippage limits are left to the caller, which checks them on the returned amounts.
+pub fn settle_swap(leg: SwapLeg) -> Result<SettledSwap> {
+    let SwapLeg {
+        config,
+        oracle,
+        reserves: (reserve_x, reserve_y),
+        lp_supply,
+        is_token_x,
+        amount,
+        input,
+        output,
+    } = leg;
+    let now = Clock::get()?.unix_timestamp;
+    config.update_price_accumulators(oracle, reserve_x, reserve_y)?;
+
+    let (amount_in, quote) = match amount {
+        SwapAmount::ExactIn(amount_in) => {
+            // Only what reaches the vault is traded against the curve.
+            let amount_in_received = match &input {
+                Some(input) => amount_received(input.mint, amount_in)?,
+                None => amount_in,
+            };
+            let quote = config.quote_swap(
+                reserve_x,
+                reserve_y,
+                lp_supply,
+                is_token_x,
+                amount_in_received,
+                now,
+            )?;
+            (amount_in, quote)
+        }
+        SwapAmount::ExactOut(amount_out) => {
+            // The vault has to send enough for `amount_out` to be received after any
+            // transfer fee, and receive enough to cover the curve input.
+            let amount_out_sent = match &output {
+                Some(output) => amount_to_send(output.mint, amount_out)?,
+                None => amount_out,
+            };
+            let quote = config.quote_swap_exact_out(
+                reserve_x,
+                reserve_y,
+                is_token_x,
+                amount_out_sent,
+                now,
+            )?;
+            let amount_in = match &input {
+                Some(input) => amount_to_send(input.mint, quote.amount_in)?,
+                None => quote.amount_in,
+            };
+            (amount_in, quote)
+        }
+    };
+    require!(amount_in != 0, AmmError::InvalidAmount);
+    require!(quote.amount_out != 0, AmmError::InvalidAmount);
+
+    // Set the protocol's cut of the fee aside, the rest stays with the LPs.
+    let protocol_fee = config.accrue_protocol_fee(is_token_x, quote.fee)?;
+    config.record_swap(reserve_x, reserve_y, is_token_x, &quote, protocol_fee, now)?;
+
+    if let Some(input) = input {
+        let transfer_accounts = TransferChecked {
+            from: input.from,
+            to: input.to,
+            mint: input.mint.to_account_info(),
+            authority: input.authority,
+        };
+        let cpi_ctx =
+            CpiContext::new_with_signer(input.token_program, transfer_accounts, input.signer_seeds);
+        transfer_checked(cpi_ctx, amount_in, input.mint.decimals)?;
+    }
+    if let Some(output) = output {
+        let transfer_accounts = TransferChecked {
+            from: output.from,
+            to: output.to,
+            mint: output.mint.to_account_info(),
+            authority: config.to_account_info(),
+        };
+        let signer_seeds = &[
+            b"config",
+            &config.seed.to_le_bytes()[..],
+            &[config.config_bump],
+        ];
+        let signer_seeds = &[&signer_seeds[..]];
+        let cpi_ctx =
+            CpiContext::new_with_signer(output.token_program, transfer_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, quote.amount_out, output.mint.decimals)?;
+    }
+
+    Ok(SettledSwap {
+        amount_in,
+        quote,
+        protocol_fee,
+    })
+}
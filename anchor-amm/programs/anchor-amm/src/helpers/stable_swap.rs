@@ -0,0 +1,255 @@
+//! StableSwap invariant for two-token pools:
+//! `A·n^n·(x + y) + D = A·n^n·D + D^(n+1) / (n^n·x·y)` with `n = 2`.
+//!
+//! Intermediate products go up to `D^3`, so the math runs on 256-bit integers.
+
+use anchor_lang::prelude::*;
+
+use crate::error::AmmError;
+
+pub use u256::U256;
+
+// Kept in its own module: the macro expansion expects `Result` to be the std one,
+// not the alias from the Anchor prelude.
+#[allow(clippy::assign_op_pattern, clippy::manual_div_ceil)]
+mod u256 {
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+
+/// Number of tokens in a pool.
+const N_COINS: u64 = 2;
+
+/// Maximum Newton iterations before giving up on convergence.
+const MAX_ITERATIONS: u8 = 255;
+
+/// Computes the invariant `D` of the pool for amplification coefficient `amp`.
+pub fn compute_d(amp: u64, reserve_x: u64, reserve_y: u64) -> Result<u128> {
+    let sum = U256::from(reserve_x) + U256::from(reserve_y);
+    if sum.is_zero() {
+        return Ok(0);
+    }
+    require!(reserve_x != 0 && reserve_y != 0, AmmError::ZeroBalance);
+
+    let ann = U256::from(amp) * U256::from(N_COINS * N_COINS);
+    let (x, y) = (U256::from(reserve_x), U256::from(reserve_y));
+    let mut d = sum;
+
+    for _ in 0..MAX_ITERATIONS {
+        // D^3 / (n^n·x·y), computed in steps to keep the intermediates small.
+        let d_p = d * d / (x * N_COINS) * d / (y * N_COINS);
+        let d_prev = d;
+
+        let numerator = (ann * sum + d_p * N_COINS) * d;
+        let denominator = (ann - 1) * d + d_p * (N_COINS + 1);
+        d = numerator / denominator;
+
+        if abs_diff(d, d_prev) <= U256::one() {
+            return to_u128(d);
+        }
+    }
+    err!(AmmError::CurveError)
+}
+
+/// Solves the invariant for the reserve of one token given the reserve `other`
+/// of the other token.
+pub fn compute_y(amp: u64, other: u64, d: u128) -> Result<u64> {
+    require!(other != 0, AmmError::ZeroBalance);
+
+    let ann = U256::from(amp) * U256::from(N_COINS * N_COINS);
+    let d = U256::from(d);
+    let other = U256::from(other);
+
+    // c = D^3 / (n^n·other·Ann), b = other + D / Ann
+    let c = d * d / (other * N_COINS) * d / (ann * N_COINS);
+    let b = other + d / ann;
+    let mut y = d;
+
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        let denominator = y * 2 + b;
+        require!(denominator > d, AmmError::CurveError);
+        y = (y * y + c) / (denominator - d);
+
+        if abs_diff(y, y_prev) <= U256::one() {
+            return u64::try_from(to_u128(y)?).map_err(|_| error!(AmmError::Overflow));
+        }
+    }
+    err!(AmmError::CurveError)
+}
+
+/// Output paid for `amount_in` (fee already deducted) sent into `reserve_in`.
+/// Rounds down by one unit so the invariant never decreases.
+pub fn amount_out(amp: u64, reserve_in: u64, reserve_out: u64, amount_in: u64) -> Result<u64> {
+    let d = compute_d(amp, reserve_in, reserve_out)?;
+    let new_reserve_in = reserve_in
+        .checked_add(amount_in)
+        .ok_or(AmmError::Overflow)?;
+    let new_reserve_out = compute_y(amp, new_reserve_in, d)?;
+
+    Ok(reserve_out
+        .saturating_sub(new_reserve_out)
+        .saturating_sub(1))
+}
+
+/// Input, before fees, needed to take exactly `amount_out` out of `reserve_out`.
+/// Rounds up by one unit so the invariant never decreases.
+pub fn amount_in_for_exact_out(
+    amp: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+) -> Result<u64> {
+    require!(amount_out < reserve_out, AmmError::InsufficientBalance);
+    let d = compute_d(amp, reserve_in, reserve_out)?;
+    let new_reserve_in = compute_y(amp, reserve_out - amount_out, d)?;
+
+    new_reserve_in
+        .checked_sub(reserve_in)
+        .and_then(|amount_in| amount_in.checked_add(1))
+        .ok_or(error!(AmmError::Underflow))
+}
+
+/// Marginal price of the base token in units of the quote token, as a Q64.64 number.
+///
+/// From the implicit derivative of the invariant, with `Ann = A·n^n` and
+/// `t = D^3 / (n^n·x·y)`: `price = quote·(Ann·base + t) / (base·(Ann·quote + t))`.
+pub fn spot_price_q64(amp: u64, reserve_base: u64, reserve_quote: u64) -> Result<u128> {
+    require!(
+        reserve_base != 0 && reserve_quote != 0,
+        AmmError::ZeroBalance
+    );
+    let d = U256::from(compute_d(amp, reserve_base, reserve_quote)?);
+    let ann = U256::from(amp) * U256::from(N_COINS * N_COINS);
+    let (base, quote) = (U256::from(reserve_base), U256::from(reserve_quote));
+
+    let t = d * d / (base * N_COINS) * d / (quote * N_COINS);
+    let numerator = (quote * (ann * base + t)) << 64;
+    let denominator = base * (ann * quote + t);
+    to_u128(numerator / denominator)
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+fn to_u128(value: U256) -> Result<u128> {
+    require!(value.bits() <= 128, AmmError::Overflow);
+    Ok(value.as_u128())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MAX_AMP, MIN_AMP};
+
+    const AMPS: [u64; 4] = [MIN_AMP, 10, 100, MAX_AMP];
+    const RESERVES: [(u64, u64); 4] = [
+        (1_000_000, 1_000_000),
+        (1_000_000, 3_000_000),
+        (10_000_000_000, 7_000_000_000),
+        (u64::MAX / 4, u64::MAX / 4),
+    ];
+
+    #[test]
+    fn d_of_balanced_pool_is_sum_of_reserves() {
+        for amp in AMPS {
+            let d = compute_d(amp, 1_000_000, 1_000_000).unwrap();
+            assert!(d.abs_diff(2_000_000) <= 1, "amp {amp}: {d}");
+        }
+        assert_eq!(compute_d(100, 0, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn compute_y_inverts_compute_d() {
+        for amp in AMPS {
+            for (x, y) in RESERVES {
+                let d = compute_d(amp, x, y).unwrap();
+                let solved = compute_y(amp, x, d).unwrap();
+                assert!(solved.abs_diff(y) <= 2, "amp {amp}, ({x}, {y}): {solved}");
+            }
+        }
+    }
+
+    #[test]
+    fn swap_does_not_decrease_d() {
+        for amp in AMPS {
+            for (x, y) in RESERVES {
+                for amount_in in [1, 1_000, x / 100, x] {
+                    let d_before = compute_d(amp, x, y).unwrap();
+                    let out = amount_out(amp, x, y, amount_in).unwrap();
+                    assert!(out < y);
+                    let d_after = compute_d(amp, x + amount_in, y - out).unwrap();
+                    assert!(d_after >= d_before, "amp {amp}, ({x}, {y}), in {amount_in}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exact_out_round_trip_does_not_lose_value() {
+        for amp in AMPS {
+            for (x, y) in RESERVES {
+                for wanted in [1, 1_000, y / 100, y / 2] {
+                    let d_before = compute_d(amp, x, y).unwrap();
+                    let amount_in = amount_in_for_exact_out(amp, x, y, wanted).unwrap();
+
+                    // Selling the quoted input pays at least what was asked for...
+                    assert!(amount_out(amp, x, y, amount_in).unwrap() >= wanted);
+                    // ...and the pool keeps its invariant taking exactly `wanted` out.
+                    let d_after = compute_d(amp, x + amount_in, y - wanted).unwrap();
+                    assert!(d_after >= d_before, "amp {amp}, ({x}, {y}), out {wanted}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exact_out_rejects_draining_the_pool() {
+        assert!(amount_in_for_exact_out(100, 1_000, 1_000, 1_000).is_err());
+    }
+
+    #[test]
+    fn spot_price_follows_reserves() {
+        let one = 1u128 << 64;
+        for amp in AMPS {
+            let price = spot_price_q64(amp, 1_000_000, 1_000_000).unwrap();
+            assert!(price.abs_diff(one) <= one / 1_000_000, "amp {amp}: {price}");
+
+            // More of the base token makes it cheaper, and a higher amp flattens that.
+            assert!(spot_price_q64(amp, 3_000_000, 1_000_000).unwrap() < one);
+        }
+        assert!(
+            spot_price_q64(MAX_AMP, 3_000_000, 1_000_000).unwrap()
+                > spot_price_q64(MIN_AMP, 3_000_000, 1_000_000).unwrap()
+        );
+        assert!(spot_price_q64(100, 0, 1_000_000).is_err());
+    }
+
+    #[test]
+    fn extreme_reserves_do_not_panic() {
+        let extremes = [
+            (1, 1),
+            (1, u64::MAX),
+            (u64::MAX, 1),
+            (1_000, u64::MAX / 2),
+            (u64::MAX / 2, u64::MAX / 2),
+            (u64::MAX, u64::MAX),
+        ];
+        for amp in AMPS {
+            for (x, y) in extremes {
+                // Errors are fine, panics and overflowing arithmetic are not.
+                let _ = compute_d(amp, x, y);
+                let _ = amount_out(amp, x, y, 1);
+                let _ = amount_out(amp, x, y, u64::MAX - x);
+                let _ = amount_in_for_exact_out(amp, x, y, y / 2);
+                let _ = spot_price_q64(amp, x, y);
+            }
+        }
+    }
+}
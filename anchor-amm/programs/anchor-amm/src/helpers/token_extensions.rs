@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    spl_token_2022::{
+        extension::{
+            transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType,
+            StateWithExtensions,
+        },
+        state::Mint as MintState,
+    },
+    Mint,
+};
+
+use crate::error::AmmError;
+
+/// Token-2022 mint extensions a pool cannot list.
+///
+/// - `NonTransferable`: tokens could never leave the vaults.
+/// - `PermanentDelegate`: the delegate could drain the vaults at any time.
+/// - `TransferHook`: every transfer would need extra accounts the pool does not pass.
+pub const UNSUPPORTED_MINT_EXTENSIONS: [ExtensionType; 3] = [
+    ExtensionType::NonTransferable,
+    ExtensionType::PermanentDelegate,
+    ExtensionType::TransferHook,
+];
+
+/// Rejects mints carrying any of the [`UNSUPPORTED_MINT_EXTENSIONS`].
+/// Legacy SPL Token mints have no extensions and always pass.
+pub fn require_supported_mint(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+
+    for extension in mint_state.get_extension_types()? {
+        require!(
+            !UNSUPPORTED_MINT_EXTENSIONS.contains(&extension),
+            AmmError::UnsupportedMint
+        );
+    }
+    Ok(())
+}
+
+/// Returns the amount withheld by the mint's transfer fee extension when
+/// `amount` tokens are sent. Zero for mints without the extension.
+pub fn transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+
+    match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) => {
+            let epoch = Clock::get()?.epoch;
+            Ok(fee_config
+                .calculate_epoch_fee(epoch, amount)
+                .ok_or(AmmError::Overflow)?)
+        }
+        Err(_) => Ok(0),
+    }
+}
+
+/// Amount the destination ends up with when `amount` tokens are sent.
+pub fn amount_received(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let fee = transfer_fee(mint, amount)?;
+    Ok(amount.checked_sub(fee).ok_or(AmmError::Underflow)?)
+}
+
+/// Amount that has to be sent so the destination ends up with exactly `received` tokens.
+pub fn amount_to_send(mint: &InterfaceAccount<Mint>, received: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+
+    match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) => {
+            let epoch = Clock::get()?.epoch;
+            let fee = fee_config
+                .calculate_inverse_epoch_fee(epoch, received)
+                .ok_or(AmmError::Overflow)?;
+            Ok(received.checked_add(fee).ok_or(AmmError::Overflow)?)
+        }
+        Err(_) => Ok(received),
+    }
+}
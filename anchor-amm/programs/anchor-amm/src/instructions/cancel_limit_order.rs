@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::LimitOrder;
+
+/// Accounts required for cancelling a limit order. Only the owner can cancel.
+#[derive(Accounts)]
+pub struct CancelLimitOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        address=order.token_in_mint,
+        mint::token_program=token_in_program
+    )]
+    pub token_in_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        close=owner,
+        has_one=owner,
+        seeds=[
+            b"order",
+            order.config.as_ref(),
+            owner.key().as_ref(),
+            order.seed.to_le_bytes().as_ref()
+        ],
+        bump= order.bump
+    )]
+    pub order: Account<'info, LimitOrder>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_in_mint,
+        associated_token::authority=order,
+        associated_token::token_program=token_in_program
+    )]
+    pub order_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer=owner,
+        associated_token::mint=token_in_mint,
+        associated_token::authority=owner,
+        associated_token::token_program=token_in_program
+    )]
+    pub owner_token_in_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_in_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelLimitOrder<'info> {
+    /// Refunds the escrow and closes the order. The keeper tip and the rent go back
+    /// to the owner. Expired orders can be cancelled as well.
+    pub fn cancel_limit_order(&mut self) -> Result<()> {
+        release_escrow(
+            &self.order,
+            &self.order_escrow,
+            &self.token_in_mint,
+            self.owner_token_in_account.to_account_info(),
+            self.owner.to_account_info(),
+            &self.token_in_program,
+        )?;
+        Ok(())
+    }
+}
+
+/// Sends the whole escrow of `order` to `to` and closes it, its rent going to
+/// `rent_destination`. Returns the amount sent.
+pub(crate) fn release_escrow<'info>(
+    order: &Account<'info, LimitOrder>,
+    order_escrow: &InterfaceAccount<'info, TokenAccount>,
+    token_in_mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    rent_destination: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<u64> {
+    let seed = order.seed.to_le_bytes();
+    let signer_seeds = &[
+        b"order",
+        order.config.as_ref(),
+        order.owner.as_ref(),
+        &seed[..],
+        &[order.bump],
+    ];
+    let signer_seeds = &[&signer_seeds[..]];
+
+    let amount = order_escrow.amount;
+    if amount > 0 {
+        let transfer_accounts = TransferChecked {
+            from: order_escrow.to_account_info(),
+            to,
+            mint: token_in_mint.to_account_info(),
+            authority: order.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+        transfer_checked(cpi_ctx, amount, token_in_mint.decimals)?;
+    }
+
+    close_escrow(order, order_escrow, rent_destination, token_program)?;
+    Ok(amount)
+}
+
+/// Closes the emptied escrow of `order`, its rent going to `rent_destination`.
+pub(crate) fn close_escrow<'info>(
+    order: &Account<'info, LimitOrder>,
+    order_escrow: &InterfaceAccount<'info, TokenAccount>,
+    rent_destination: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    let seed = order.seed.to_le_bytes();
+    let signer_seeds = &[
+        b"order",
+        order.config.as_ref(),
+        order.owner.as_ref(),
+        &seed[..],
+        &[order.bump],
+    ];
+    let signer_seeds = &[&signer_seeds[..]];
+
+    let close_accounts = CloseAccount {
+        account: order_escrow.to_account_info(),
+        destination: rent_destination,
+        authority: order.to_account_info(),
+    };
+    let cpi_ctx =
+        CpiContext::new_with_signer(token_program.to_account_info(), close_accounts, signer_seeds);
+    close_account(cpi_ctx)
+}
@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{error::AmmError, require_has_update_authority, require_not_locked, Config};
+
+/// Accounts required for moving the accrued protocol fees out of the pool vaults.
+///
+/// Only the pool authority can claim. The fees are sent to the treasury token
+/// accounts chosen by the authority, one per side of the pair.
+#[derive(Accounts)]
+pub struct ClaimProtocolFees<'info> {
+    /// The current pool authority.
+    pub authority: Signer<'info>,
+
+    #[account(mint::token_program=token_x_program)]
+    pub token_x_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program=token_y_program)]
+    pub token_y_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one=token_x_mint,
+        has_one=token_y_mint,
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump= config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_x_mint,
+        associated_token::authority=config,
+        associated_token::token_program=token_x_program
+    )]
+    pub pool_token_x_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_y_mint,
+        associated_token::authority=config,
+        associated_token::token_program=token_y_program
+    )]
+    pub pool_token_y_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Treasury account receiving the token X fees.
+    #[account(
+        mut,
+        token::mint=token_x_mint,
+        token::token_program=token_x_program
+    )]
+    pub treasury_token_x_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Treasury account receiving the token Y fees.
+    #[account(
+        mut,
+        token::mint=token_y_mint,
+        token::token_program=token_y_program
+    )]
+    pub treasury_token_y_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_x_program: Interface<'info, TokenInterface>,
+    pub token_y_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ClaimProtocolFees<'info> {
+    /// Transfers both accrued protocol fee balances to the treasury and resets them.
+    ///
+    /// Fails with `PoolLocked` while the pool is locked or a flash loan is outstanding,
+    /// so the vaults cannot move under a loan before `flash_repay` checks them.
+    pub fn claim_protocol_fees(&mut self) -> Result<()> {
+        require_has_update_authority!(self);
+        require_not_locked!(self.config.locked || self.config.flash_loan.is_some());
+
+        let (fees_x, fees_y) = (self.config.protocol_fees_x, self.config.protocol_fees_y);
+        require!(fees_x > 0 || fees_y > 0, AmmError::ZeroBalance);
+
+        self.config.protocol_fees_x = 0;
+        self.config.protocol_fees_y = 0;
+
+        if fees_x > 0 {
+            self.transfer_fees(true, fees_x)?;
+        }
+        if fees_y > 0 {
+            self.transfer_fees(false, fees_y)?;
+        }
+        Ok(())
+    }
+
+    fn transfer_fees(&self, is_token_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals, cpi_program) = match is_token_x {
+            true => (
+                self.pool_token_x_vault.to_account_info(),
+                self.treasury_token_x_account.to_account_info(),
+                self.token_x_mint.to_account_info(),
+                self.token_x_mint.decimals,
+                self.token_x_program.to_account_info(),
+            ),
+            false => (
+                self.pool_token_y_vault.to_account_info(),
+                self.treasury_token_y_account.to_account_info(),
+                self.token_y_mint.to_account_info(),
+                self.token_y_mint.decimals,
+                self.token_y_program.to_account_info(),
+            ),
+        };
+        let transfer_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.config.to_account_info(),
+        };
+        let signer_seeds = &[
+            b"config",
+            &self.config.seed.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&signer_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, transfer_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, amount, decimals)?;
+        Ok(())
+    }
+}
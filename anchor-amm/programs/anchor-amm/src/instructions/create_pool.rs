@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+
+// Glob import: nesting `Initialize` also needs the client modules its derive generates.
+use super::initialize::*;
+use crate::{error::AmmError, Factory, MintRegistry, PoolKey, RegistryEntry, FEE_TIERS};
+
+/// Accounts required for creating the canonical pool of a mint pair and fee tier.
+///
+/// Wraps the `Initialize` accounts and adds:
+/// - The `factory`, whose authority becomes the authority of the pool.
+/// - The `pool_key` marker, whose `init` fails if the pair already has a pool at this tier.
+/// - One `MintRegistry` per mint, created on first use, counting the pools of that mint.
+/// - One `RegistryEntry` per mint, at the next index of its registry, pointing to the pool.
+///
+/// ## PDA Seeds
+/// - `factory` PDA: `["factory"]`
+/// - `pool_key` PDA: `["pool", token_x_mint, token_y_mint, fee.to_le_bytes()]`
+/// - `token_x_registry` / `token_y_registry` PDA: `["registry", mint]`
+/// - `token_x_registry_entry` / `token_y_registry_entry` PDA:
+///   `["registry", mint, registry.pool_count.to_le_bytes()]`
+#[derive(Accounts)]
+#[instruction(args: InitArgs)]
+pub struct CreatePool<'info> {
+    pub initialize: Initialize<'info>,
+
+    #[account(seeds=[b"factory"], bump= factory.bump)]
+    pub factory: Account<'info, Factory>,
+
+    #[account(
+        init,
+        payer=initialize.admin,
+        seeds=[
+            b"pool",
+            initialize.token_x_mint.key().as_ref(),
+            initialize.token_y_mint.key().as_ref(),
+            args.fee.to_le_bytes().as_ref()
+        ],
+        bump,
+        space= 8+PoolKey::INIT_SPACE
+    )]
+    pub pool_key: Account<'info, PoolKey>,
+
+    #[account(
+        init_if_needed,
+        payer=initialize.admin,
+        seeds=[b"registry", initialize.token_x_mint.key().as_ref()],
+        bump,
+        space= 8+MintRegistry::INIT_SPACE
+    )]
+    pub token_x_registry: Box<Account<'info, MintRegistry>>,
+
+    #[account(
+        init,
+        payer=initialize.admin,
+        seeds=[
+            b"registry",
+            initialize.token_x_mint.key().as_ref(),
+            token_x_registry.pool_count.to_le_bytes().as_ref()
+        ],
+        bump,
+        space= 8+RegistryEntry::INIT_SPACE
+    )]
+    pub token_x_registry_entry: Box<Account<'info, RegistryEntry>>,
+
+    #[account(
+        init_if_needed,
+        payer=initialize.admin,
+        seeds=[b"registry", initialize.token_y_mint.key().as_ref()],
+        bump,
+        space= 8+MintRegistry::INIT_SPACE
+    )]
+    pub token_y_registry: Box<Account<'info, MintRegistry>>,
+
+    #[account(
+        init,
+        payer=initialize.admin,
+        seeds=[
+            b"registry",
+            initialize.token_y_mint.key().as_ref(),
+            token_y_registry.pool_count.to_le_bytes().as_ref()
+        ],
+        bump,
+        space= 8+RegistryEntry::INIT_SPACE
+    )]
+    pub token_y_registry_entry: Box<Account<'info, RegistryEntry>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreatePool<'info> {
+    /// Creates the canonical pool and registers it under both mints.
+    ///
+    /// The mints must be sorted (`token_x_mint < token_y_mint`), `args.fee` must be one
+    /// of the `FEE_TIERS` and `args.seed` must be `PoolKey::pool_seed` of the pair and
+    /// tier. The pool authority is the `Factory` authority: `args.authority` must be
+    /// `None`, so whoever creates the pool first cannot lock it or change its fee and
+    /// hold the pair hostage.
+    pub fn create_pool(&mut self, args: InitArgs, bumps: &CreatePoolBumps) -> Result<()> {
+        let token_x_mint = self.initialize.token_x_mint.key();
+        let token_y_mint = self.initialize.token_y_mint.key();
+        require!(token_x_mint < token_y_mint, AmmError::InvalidMintOrder);
+        require!(FEE_TIERS.contains(&args.fee), AmmError::InvalidFeeTier);
+        require!(
+            args.seed == PoolKey::pool_seed(&token_x_mint, &token_y_mint, args.fee),
+            AmmError::InvalidPoolSeed
+        );
+        require!(args.authority.is_none(), AmmError::InvalidAuthority);
+
+        let fee = args.fee;
+        let args = InitArgs {
+            authority: Some(self.factory.authority),
+            ..args
+        };
+        self.initialize.init_pool(args, &bumps.initialize)?;
+
+        let config = self.initialize.config.key();
+        self.pool_key.set_inner(PoolKey {
+            config,
+            token_x_mint,
+            token_y_mint,
+            fee,
+            bump: bumps.pool_key,
+        });
+
+        register_pool(
+            &mut self.token_x_registry,
+            &mut self.token_x_registry_entry,
+            token_x_mint,
+            config,
+            (bumps.token_x_registry, bumps.token_x_registry_entry),
+        )?;
+        register_pool(
+            &mut self.token_y_registry,
+            &mut self.token_y_registry_entry,
+            token_y_mint,
+            config,
+            (bumps.token_y_registry, bumps.token_y_registry_entry),
+        )
+    }
+}
+
+/// Points the next entry of the registry of `mint` to `config` and counts it.
+fn register_pool(
+    registry: &mut MintRegistry,
+    entry: &mut RegistryEntry,
+    mint: Pubkey,
+    config: Pubkey,
+    (registry_bump, entry_bump): (u8, u8),
+) -> Result<()> {
+    // Freshly created by `init_if_needed`.
+    if registry.mint == Pubkey::default() {
+        registry.mint = mint;
+        registry.bump = registry_bump;
+    }
+
+    *entry = RegistryEntry {
+        mint,
+        index: registry.pool_count,
+        config,
+        bump: entry_bump,
+    };
+    registry.pool_count = registry.pool_count.checked_add(1).ok_or(AmmError::Overflow)?;
+    Ok(())
+}
@@ -1,11 +1,21 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{mint_to, transfer, Mint, MintTo, Token, TokenAccount, Transfer},
+    token_interface::{
+        mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+    },
 };
-use constant_product_curve::ConstantProduct;
 
-use crate::{error::AmmError, require_non_zero, require_not_locked, state::Config};
+use crate::{
+    error::AmmError,
+    helpers::{
+        curve::initial_liquidity,
+        token_extensions::{amount_received, amount_to_send},
+    },
+    require_non_zero, require_not_expired, require_not_locked,
+    state::{Config, Oracle},
+    DepositEvent, MINIMUM_LIQUIDITY,
+};
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
@@ -13,15 +23,16 @@ pub struct Deposit<'info> {
     #[account(mut)]
     pub depositor: Signer<'info>,
     /// The mint address of Token X (e.g., USDC).
-    #[account(mint::token_program=token_program)]
-    pub token_x_mint: Account<'info, Mint>,
+    #[account(mint::token_program=token_x_program)]
+    pub token_x_mint: InterfaceAccount<'info, Mint>,
     /// The mint address of Token Y (e.g., SOL)
-    #[account(mint::token_program=token_program)]
-    pub token_y_mint: Account<'info, Mint>,
+    #[account(mint::token_program=token_y_program)]
+    pub token_y_mint: InterfaceAccount<'info, Mint>,
 
     /// Global AMM configuration PDA that holds metadata like fees and bumps.
     /// - PDA derived from seed `[b"config", config.seed.to_le_bytes()]`
     #[account(
+        mut,
         has_one=token_x_mint,
         has_one=token_y_mint,
         seeds=[b"config", config.seed.to_le_bytes().as_ref()],
@@ -29,23 +40,33 @@ pub struct Deposit<'info> {
      )]
     pub config: Account<'info, Config>,
 
+    /// Price oracle of the pool, updated before the reserves change.
+    #[account(
+        mut,
+        seeds=[b"oracle", config.key().as_ref()],
+        bump= config.oracle_bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
     /// The vault PDA account that stores Token X liquidity inside the pool.
     /// - Owned by the AMM (authority = config)
     #[account(
         mut,
         associated_token::mint=token_x_mint,
-        associated_token::authority=config
+        associated_token::authority=config,
+        associated_token::token_program=token_x_program
     )]
-    pub pool_token_x_vault: Account<'info, TokenAccount>,
+    pub pool_token_x_vault: InterfaceAccount<'info, TokenAccount>,
 
     /// The vault PDA account that stores Token Y liquidity inside the pool.
     /// - Owned by the AMM (authority = config)
     #[account(
         mut,
         associated_token::mint=token_y_mint,
-        associated_token::authority=config
+        associated_token::authority=config,
+        associated_token::token_program=token_y_program
     )]
-    pub pool_token_y_vault: Account<'info, TokenAccount>,
+    pub pool_token_y_vault: InterfaceAccount<'info, TokenAccount>,
 
     /// needs to be mut to change total supply of mint as
     /// Every time a user deposits a trading pair. Addition of users causes the total
@@ -54,25 +75,28 @@ pub struct Deposit<'info> {
         mut,
         seeds= [b"lp",config.key().as_ref()],
         bump= config.lp_bump,
+        mint::token_program=token_program
     )]
-    pub lp_token_mint: Account<'info, Mint>,
+    pub lp_token_mint: InterfaceAccount<'info, Mint>,
 
+    /// The depositor’s associated token account for Token X.
+    /// - Tokens will be debited from here and transferred into `pool_token_x_vault`.
     #[account(
         mut,
         associated_token::mint=token_x_mint,
-        associated_token::authority=config
+        associated_token::authority=depositor,
+        associated_token::token_program=token_x_program
     )]
-    /// The depositor’s associated token account for Token X.
-    /// - Tokens will be debited from here and transferred into `pool_token_x_vault`.
-    pub depositor_token_x_account: Account<'info, TokenAccount>,
+    pub depositor_token_x_account: InterfaceAccount<'info, TokenAccount>,
     /// The depositor’s associated token account for Token Y.
     /// - Tokens will be debited from here and transferred into `pool_token_y_vault`.
     #[account(
         mut,
         associated_token::mint=token_y_mint,
-        associated_token::authority=config
+        associated_token::authority=depositor,
+        associated_token::token_program=token_y_program
     )]
-    pub depositor_token_y_account: Account<'info, TokenAccount>,
+    pub depositor_token_y_account: InterfaceAccount<'info, TokenAccount>,
 
     /// The depositor’s associated token account for the LP token.
     /// - Will receive newly minted LP tokens in exchange for providing liquidity.
@@ -81,74 +105,170 @@ pub struct Deposit<'info> {
         init_if_needed,
         payer= depositor,
         associated_token::mint=lp_token_mint,
-        associated_token::authority=depositor
+        associated_token::authority=depositor,
+        associated_token::token_program=token_program
 
     )]
-    pub depositor_lp_token_account: Account<'info, TokenAccount>,
+    pub depositor_lp_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    /// LP token account of the pool itself, holding the `MINIMUM_LIQUIDITY` locked by
+    /// the first deposit. No instruction can move tokens out of it.
+    #[account(
+        init_if_needed,
+        payer= depositor,
+        associated_token::mint=lp_token_mint,
+        associated_token::authority=config,
+        associated_token::token_program=token_program
+    )]
+    pub locked_lp_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_x_program: Interface<'info, TokenInterface>,
+    pub token_y_program: Interface<'info, TokenInterface>,
+    /// Token program of the LP mint.
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Deposit<'info> {
-    pub fn deposit(&mut self, lp_amount_to_be_minted: u64, max_x: u64, max_y: u64) -> Result<()> {
+    /// Adds liquidity to the pool.
+    ///
+    /// - `lp_amount`: LP tokens to mint. On the first deposit, the minimum LP tokens
+    ///   the depositor accepts instead.
+    /// - `max_x` and `max_y`: The most token X and Y the depositor is willing to send.
+    ///   The first deposit sends exactly these amounts.
+    /// - `expires_at`: Optional unix timestamp after which the deposit fails with `OfferExpired`.
+    pub fn deposit(
+        &mut self,
+        lp_amount: u64,
+        max_x: u64,
+        max_y: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        require_not_expired!(expires_at);
         require_not_locked!(self.config.locked);
-        require_non_zero!([lp_amount_to_be_minted, max_x, max_y]);
-        let (x, y) = match self.lp_token_mint.supply == 0
-            && self.pool_token_x_vault.amount == 0
-            && self.pool_token_y_vault.amount == 0
-        {
-            true => (max_x, max_y),
-            false => {
-                let amount = ConstantProduct::xy_deposit_amounts_from_l(
-                    self.pool_token_x_vault.amount,
-                    self.pool_token_y_vault.amount,
-                    self.lp_token_mint.supply,
-                    lp_amount_to_be_minted,
-                    6,
-                )
-                .map_err(AmmError::from)?;
-                (amount.x, amount.y)
-            }
-        };
+        require_non_zero!([lp_amount, max_x, max_y]);
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
+        self.config
+            .update_price_accumulators(&mut self.oracle, reserve_x, reserve_y)?;
+
+        if self.lp_token_mint.supply == 0 {
+            return self.deposit_initial_liquidity(lp_amount, max_x, max_y);
+        }
+
+        // Amounts the depositor has to send. For transfer fee mints this is more than
+        // what the vaults end up receiving, so the fee is paid by the depositor.
+        let (x, y) = self.config.deposit_amounts(
+            reserve_x,
+            reserve_y,
+            self.lp_token_mint.supply,
+            lp_amount,
+        )?;
+        let x = amount_to_send(&self.token_x_mint, x)?;
+        let y = amount_to_send(&self.token_y_mint, y)?;
         require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
         // deposit x and y tokens
         self.deposit_tokens(true, x)?;
         self.deposit_tokens(false, y)?;
 
-        self.mint_lp_tokens(lp_amount_to_be_minted)?;
+        self.mint_lp_tokens(false, lp_amount)?;
+        self.emit_deposit_event(x, y, lp_amount)
+    }
+
+    /// Seeds an empty pool with `max_x` and `max_y`.
+    ///
+    /// The LP supply is derived from what the vaults receive, `sqrt(x * y)`, and
+    /// `MINIMUM_LIQUIDITY` of it is locked in the pool for good. The depositor gets
+    /// the rest, which has to be at least `min_lp_amount`.
+    fn deposit_initial_liquidity(
+        &mut self,
+        min_lp_amount: u64,
+        max_x: u64,
+        max_y: u64,
+    ) -> Result<()> {
+        let liquidity = initial_liquidity(
+            amount_received(&self.token_x_mint, max_x)?,
+            amount_received(&self.token_y_mint, max_y)?,
+        );
+        require!(
+            liquidity > MINIMUM_LIQUIDITY,
+            AmmError::LiquidityLessThanMinimum
+        );
+        let lp_amount = liquidity - MINIMUM_LIQUIDITY;
+        require!(lp_amount >= min_lp_amount, AmmError::SlippageExceeded);
+
+        self.deposit_tokens(true, max_x)?;
+        self.deposit_tokens(false, max_y)?;
+
+        self.mint_lp_tokens(true, MINIMUM_LIQUIDITY)?;
+        self.mint_lp_tokens(false, lp_amount)?;
+        self.emit_deposit_event(max_x, max_y, lp_amount)
+    }
+
+    /// Logs the deposit with the pool state it left behind.
+    fn emit_deposit_event(&mut self, amount_x: u64, amount_y: u64, lp_amount: u64) -> Result<()> {
+        self.pool_token_x_vault.reload()?;
+        self.pool_token_y_vault.reload()?;
+        self.lp_token_mint.reload()?;
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
+        emit!(DepositEvent {
+            config: self.config.key(),
+            depositor: self.depositor.key(),
+            amount_x,
+            amount_y,
+            lp_amount,
+            reserve_x,
+            reserve_y,
+            lp_supply: self.lp_token_mint.supply,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
+
     pub fn deposit_tokens(&mut self, is_token_x: bool, amount: u64) -> Result<()> {
-        let (from, to) = match is_token_x {
+        let (from, to, mint, decimals, cpi_program) = match is_token_x {
             true => (
                 self.depositor_token_x_account.to_account_info(),
                 self.pool_token_x_vault.to_account_info(),
+                self.token_x_mint.to_account_info(),
+                self.token_x_mint.decimals,
+                self.token_x_program.to_account_info(),
             ),
             false => (
                 self.depositor_token_y_account.to_account_info(),
                 self.pool_token_y_vault.to_account_info(),
+                self.token_y_mint.to_account_info(),
+                self.token_y_mint.decimals,
+                self.token_y_program.to_account_info(),
             ),
         };
-        let cpi_program = self.token_program.to_account_info();
 
-        let cpi_accounts = Transfer {
+        let cpi_accounts = TransferChecked {
             from,
             to,
+            mint,
             authority: self.depositor.to_account_info(),
         };
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        transfer(cpi_ctx, amount)?;
+        transfer_checked(cpi_ctx, amount, decimals)?;
         Ok(())
     }
 
-    pub fn mint_lp_tokens(&mut self, amount: u64) -> Result<()> {
+    /// Mints LP tokens to the depositor, or to the pool when `is_locked` is set.
+    pub fn mint_lp_tokens(&mut self, is_locked: bool, amount: u64) -> Result<()> {
+        let to = match is_locked {
+            true => self.locked_lp_token_account.to_account_info(),
+            false => self.depositor_lp_token_account.to_account_info(),
+        };
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = MintTo {
             mint: self.lp_token_mint.to_account_info(),
-            to: self.depositor_lp_token_account.to_account_info(),
+            to,
             authority: self.config.to_account_info(),
         };
         let signer_seeds = &[
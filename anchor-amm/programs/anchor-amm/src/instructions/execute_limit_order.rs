@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::cancel_limit_order::close_escrow;
+use crate::{
+    error::AmmError,
+    helpers::settlement::{settle_swap, SwapAmount, SwapInput, SwapLeg, SwapOutput},
+    require_not_expired, require_not_locked, Config, LimitOrder, Oracle, SwapEvent,
+};
+
+/// Accounts required for executing a limit order. Anyone can act as the keeper.
+///
+/// The escrowed input goes straight from the `order_escrow` into the pool vault, signed
+/// by the order, and the output straight from the other vault to the owner. The keeper
+/// only signs and collects the tip, none of the tokens go through its accounts.
+///
+/// ## PDA Seeds
+/// - `config` PDA: `["config", config.seed.to_le_bytes()]`
+/// - `order` PDA: `["order", config.key(), owner.key(), order.seed.to_le_bytes()]`
+#[derive(Accounts)]
+pub struct ExecuteLimitOrder<'info> {
+    /// Executes the order and receives the keeper tip.
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(mint::token_program=token_x_program)]
+    pub token_x_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program=token_y_program)]
+    pub token_y_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one=token_x_mint,
+        has_one=token_y_mint,
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump= config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Price oracle of the pool, updated before the reserves change.
+    #[account(
+        mut,
+        seeds=[b"oracle", config.key().as_ref()],
+        bump= config.oracle_bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
+    #[account(
+        seeds=[b"lp", config.key().as_ref()],
+        bump= config.lp_bump
+    )]
+    pub lp_token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_x_mint,
+        associated_token::authority=config,
+        associated_token::token_program=token_x_program
+    )]
+    pub pool_token_x_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_y_mint,
+        associated_token::authority=config,
+        associated_token::token_program=token_y_program
+    )]
+    pub pool_token_y_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close=owner,
+        has_one=owner,
+        has_one=config,
+        seeds=[
+            b"order",
+            config.key().as_ref(),
+            owner.key().as_ref(),
+            order.seed.to_le_bytes().as_ref()
+        ],
+        bump= order.bump
+    )]
+    pub order: Account<'info, LimitOrder>,
+
+    /// Owner of the order, receiving the output and the rent of the closed accounts.
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address=get_associated_token_address_with_program_id(
+            &order.key(),
+            &order.token_in_mint,
+            &match order.is_token_x {
+                true => token_x_program.key(),
+                false => token_y_program.key(),
+            }
+        )
+    )]
+    pub order_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address=get_associated_token_address_with_program_id(
+            &owner.key(),
+            &order.token_out_mint,
+            &match order.is_token_x {
+                true => token_y_program.key(),
+                false => token_x_program.key(),
+            }
+        )
+    )]
+    pub owner_token_out_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_x_program: Interface<'info, TokenInterface>,
+    pub token_y_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ExecuteLimitOrder<'info> {
+    /// Sells the whole escrow to the pool, pays the output to the owner and the tip to
+    /// the keeper, then closes the order and its escrow.
+    ///
+    /// Fees, the price impact limit and the dynamic fee apply as for any swap. Fails with
+    /// `SlippageExceeded` while what the owner receives, after any transfer fee, is below
+    /// `min_amount_out`, and with `OfferExpired` after `expires_at`.
+    pub fn execute_limit_order(&mut self) -> Result<()> {
+        require_not_expired!(Some(self.order.expires_at));
+        require_not_locked!(self.config.locked);
+        let is_token_x = self.order.is_token_x;
+
+        let escrow_amount = self.order_escrow.amount;
+        require!(escrow_amount != 0, AmmError::ZeroBalance);
+        let reserves = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
+        let (mint_in, vault_in, token_program_in, mint_out, vault_out, token_program_out) =
+            match is_token_x {
+                true => (
+                    &self.token_x_mint,
+                    &self.pool_token_x_vault,
+                    &self.token_x_program,
+                    &self.token_y_mint,
+                    &self.pool_token_y_vault,
+                    &self.token_y_program,
+                ),
+                false => (
+                    &self.token_y_mint,
+                    &self.pool_token_y_vault,
+                    &self.token_y_program,
+                    &self.token_x_mint,
+                    &self.pool_token_x_vault,
+                    &self.token_x_program,
+                ),
+            };
+        let seed = self.order.seed.to_le_bytes();
+        let signer_seeds = &[
+            b"order",
+            self.order.config.as_ref(),
+            self.order.owner.as_ref(),
+            &seed[..],
+            &[self.order.bump],
+        ];
+        let owner_amount_before = self.owner_token_out_account.amount;
+
+        // The escrow pays the pool, signed by the order, and the pool pays the owner.
+        let settled = settle_swap(SwapLeg {
+            config: &mut self.config,
+            oracle: &mut self.oracle,
+            reserves,
+            lp_supply: self.lp_token_mint.supply,
+            is_token_x,
+            amount: SwapAmount::ExactIn(escrow_amount),
+            input: Some(SwapInput {
+                from: self.order_escrow.to_account_info(),
+                to: vault_in.to_account_info(),
+                authority: self.order.to_account_info(),
+                signer_seeds: &[&signer_seeds[..]],
+                mint: mint_in,
+                token_program: token_program_in.to_account_info(),
+            }),
+            output: Some(SwapOutput {
+                from: vault_out.to_account_info(),
+                to: self.owner_token_out_account.to_account_info(),
+                mint: mint_out,
+                token_program: token_program_out.to_account_info(),
+            }),
+        })?;
+        close_escrow(
+            &self.order,
+            &self.order_escrow,
+            self.owner.to_account_info(),
+            token_program_in,
+        )?;
+
+        self.owner_token_out_account.reload()?;
+        let amount_received = self.owner_token_out_account.amount - owner_amount_before;
+        require!(
+            amount_received >= self.order.min_amount_out,
+            AmmError::SlippageExceeded
+        );
+
+        // The order account is program owned, so its lamports can be moved directly.
+        // `close` hands the rest back to the owner.
+        let keeper_tip = self.order.keeper_tip;
+        **self.order.to_account_info().try_borrow_mut_lamports()? -= keeper_tip;
+        **self.keeper.to_account_info().try_borrow_mut_lamports()? += keeper_tip;
+
+        self.pool_token_x_vault.reload()?;
+        self.pool_token_y_vault.reload()?;
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
+        emit!(SwapEvent {
+            config: self.config.key(),
+            swapper: self.owner.key(),
+            is_token_x,
+            amount_in: settled.amount_in,
+            amount_out: settled.quote.amount_out,
+            fee: settled.quote.fee,
+            protocol_fee: settled.protocol_fee,
+            reserve_x,
+            reserve_y,
+            lp_supply: self.lp_token_mint.supply,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::instructions::{
+        self, get_instruction_relative, load_current_index_checked, load_instruction_at_checked,
+    },
+    Discriminator,
+};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{
+    error::AmmError,
+    helpers::{curve::fee_amount_rounded_up, token_extensions::amount_to_send},
+    require_non_zero, require_not_locked, Config, Oracle, PendingFlashLoan,
+};
+
+/// Position of `config` in the accounts of a `flash_repay` instruction.
+const FLASH_REPAY_CONFIG_INDEX: usize = 3;
+
+/// Accounts shared by `flash_borrow` and `flash_repay`.
+///
+/// A transaction borrowing from the pool must call `flash_borrow`, then any
+/// instructions using the funds, then `flash_repay` on the same pool. The pool stays
+/// locked in between, so it cannot be swapped against, deposited into or have its TWAP
+/// read mid-loan.
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    /// The account borrowing, then repaying, the pool tokens.
+    pub borrower: Signer<'info>,
+
+    #[account(mint::token_program=token_x_program)]
+    pub token_x_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program=token_y_program)]
+    pub token_y_mint: InterfaceAccount<'info, Mint>,
+
+    /// Must stay at `FLASH_REPAY_CONFIG_INDEX`, `flash_borrow` looks it up there.
+    #[account(
+        mut,
+        has_one=token_x_mint,
+        has_one=token_y_mint,
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump= config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Price oracle of the pool, updated before the vault is lent out.
+    #[account(
+        mut,
+        seeds=[b"oracle", config.key().as_ref()],
+        bump= config.oracle_bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_x_mint,
+        associated_token::authority=config,
+        associated_token::token_program=token_x_program
+    )]
+    pub pool_token_x_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_y_mint,
+        associated_token::authority=config,
+        associated_token::token_program=token_y_program
+    )]
+    pub pool_token_y_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_x_mint,
+        associated_token::authority=borrower,
+        associated_token::token_program=token_x_program
+    )]
+    pub borrower_token_x_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_y_mint,
+        associated_token::authority=borrower,
+        associated_token::token_program=token_y_program
+    )]
+    pub borrower_token_y_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: address checked, read to find the matching `flash_repay`.
+    #[account(address=instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+
+    pub token_x_program: Interface<'info, TokenInterface>,
+    pub token_y_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FlashLoan<'info> {
+    /// Lends `amount` of token X or Y out of the pool and locks it.
+    ///
+    /// Fails with `FlashLoanNotRepaid` unless a `flash_repay` on the same pool comes
+    /// later in the transaction. The loan costs the current pool swap fee, rounded up.
+    ///
+    /// The price accumulators are brought up to date first, so the drained vault never
+    /// weighs on the TWAP.
+    pub fn flash_borrow(&mut self, is_token_x: bool, amount: u64) -> Result<()> {
+        require_not_locked!(self.config.locked);
+        require_non_zero!([amount]);
+        self.require_top_level()?;
+        self.require_repay_follows()?;
+
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
+        self.config
+            .update_price_accumulators(&mut self.oracle, reserve_x, reserve_y)?;
+        let (reserve, vault_amount) = match is_token_x {
+            true => (reserve_x, self.pool_token_x_vault.amount),
+            false => (reserve_y, self.pool_token_y_vault.amount),
+        };
+        require!(amount <= reserve, AmmError::InsufficientBalance);
+
+        let fee = self.config.swap_fee(Clock::get()?.unix_timestamp);
+        self.config.flash_loan = Some(PendingFlashLoan {
+            is_token_x,
+            amount,
+            fee: fee_amount_rounded_up(amount, fee)?,
+            vault_amount,
+        });
+        self.config.locked = true;
+
+        self.withdraw_tokens(is_token_x, amount)
+    }
+
+    /// Pays back the outstanding loan plus its fee, then unlocks the pool.
+    ///
+    /// For transfer fee mints the borrower sends enough for the vault to receive the
+    /// full amount. The vault balance is checked after the transfer either way.
+    pub fn flash_repay(&mut self) -> Result<()> {
+        let loan = self.config.flash_loan.ok_or(AmmError::InvalidFlashLoan)?;
+        let owed = loan.amount.checked_add(loan.fee).ok_or(AmmError::Overflow)?;
+
+        self.deposit_tokens(loan.is_token_x, owed)?;
+
+        let vault = match loan.is_token_x {
+            true => &mut self.pool_token_x_vault,
+            false => &mut self.pool_token_y_vault,
+        };
+        vault.reload()?;
+        require!(
+            vault.amount >= loan.vault_amount.checked_add(loan.fee).ok_or(AmmError::Overflow)?,
+            AmmError::FlashLoanNotRepaid
+        );
+
+        self.config.accrue_protocol_fee(loan.is_token_x, loan.fee)?;
+        self.config.flash_loan = None;
+        self.config.locked = false;
+        Ok(())
+    }
+
+    /// Rejects `flash_borrow` invoked through CPI, where the instructions sysvar
+    /// would describe the calling program instead.
+    fn require_top_level(&self) -> Result<()> {
+        let current_ix = get_instruction_relative(0, &self.instruction_sysvar)?;
+        require_keys_eq!(current_ix.program_id, crate::ID, AmmError::InvalidFlashLoan);
+        Ok(())
+    }
+
+    /// Scans the instructions after the current one for a `flash_repay` on this pool.
+    fn require_repay_follows(&self) -> Result<()> {
+        let current_index = load_current_index_checked(&self.instruction_sysvar)? as usize;
+
+        let mut index = current_index + 1;
+        while let Ok(ix) = load_instruction_at_checked(index, &self.instruction_sysvar) {
+            if ix.program_id == crate::ID
+                && ix.data.starts_with(crate::instruction::FlashRepay::DISCRIMINATOR)
+                && ix
+                    .accounts
+                    .get(FLASH_REPAY_CONFIG_INDEX)
+                    .is_some_and(|account| account.pubkey == self.config.key())
+            {
+                return Ok(());
+            }
+            index += 1;
+        }
+        err!(AmmError::FlashLoanNotRepaid)
+    }
+
+    /// Sends the borrowed tokens from the pool vault to the borrower.
+    fn withdraw_tokens(&self, is_token_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals, token_program) = match is_token_x {
+            true => (
+                self.pool_token_x_vault.to_account_info(),
+                self.borrower_token_x_account.to_account_info(),
+                self.token_x_mint.to_account_info(),
+                self.token_x_mint.decimals,
+                self.token_x_program.to_account_info(),
+            ),
+            false => (
+                self.pool_token_y_vault.to_account_info(),
+                self.borrower_token_y_account.to_account_info(),
+                self.token_y_mint.to_account_info(),
+                self.token_y_mint.decimals,
+                self.token_y_program.to_account_info(),
+            ),
+        };
+        let transfer_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.config.to_account_info(),
+        };
+        let signer_seeds = &[
+            b"config",
+            &self.config.seed.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&signer_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(token_program, transfer_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, amount, decimals)?;
+        Ok(())
+    }
+
+    /// Sends `received` tokens from the borrower back to the pool vault, grossed up
+    /// for any transfer fee.
+    fn deposit_tokens(&self, is_token_x: bool, received: u64) -> Result<()> {
+        let (from, to, mint, token_program) = match is_token_x {
+            true => (
+                &self.borrower_token_x_account,
+                &self.pool_token_x_vault,
+                &self.token_x_mint,
+                &self.token_x_program,
+            ),
+            false => (
+                &self.borrower_token_y_account,
+                &self.pool_token_y_vault,
+                &self.token_y_mint,
+                &self.token_y_program,
+            ),
+        };
+        let amount = amount_to_send(mint, received)?;
+        let transfer_accounts = TransferChecked {
+            from: from.to_account_info(),
+            to: to.to_account_info(),
+            mint: mint.to_account_info(),
+            authority: self.borrower.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program.to_account_info(), transfer_accounts);
+        transfer_checked(cpi_ctx, amount, mint.decimals)?;
+        Ok(())
+    }
+}
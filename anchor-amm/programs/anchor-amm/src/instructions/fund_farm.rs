@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{error::AmmError, require_non_zero, Farm};
+
+/// Accounts required for adding reward tokens to a farm. Anyone can fund a farm.
+#[derive(Accounts)]
+pub struct FundFarm<'info> {
+    pub funder: Signer<'info>,
+
+    #[account(
+        seeds=[b"farm", farm.config.as_ref(), farm.reward_mint.as_ref()],
+        bump= farm.bump
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        address=farm.reward_mint,
+        mint::token_program=reward_token_program
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint=reward_mint,
+        associated_token::authority=farm,
+        associated_token::token_program=reward_token_program
+    )]
+    pub farm_reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint=reward_mint,
+        token::authority=funder,
+        token::token_program=reward_token_program
+    )]
+    pub funder_reward_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub reward_token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FundFarm<'info> {
+    /// Moves `amount` reward tokens into the farm reward vault.
+    ///
+    /// The farm should hold `reward_rate` times its remaining duration. Harvests pay out
+    /// at most the vault balance and keep the rest owed until the farm is funded again.
+    pub fn fund_farm(&mut self, amount: u64) -> Result<()> {
+        require_non_zero!([amount]);
+
+        let transfer_accounts = TransferChecked {
+            from: self.funder_reward_account.to_account_info(),
+            to: self.farm_reward_vault.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            authority: self.funder.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(
+            self.reward_token_program.to_account_info(),
+            transfer_accounts,
+        );
+        transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)
+    }
+}
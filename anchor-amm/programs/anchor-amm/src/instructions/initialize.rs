@@ -1,15 +1,29 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    metadata::{
+        create_metadata_accounts_v3, mpl_token_metadata::types::DataV2,
+        CreateMetadataAccountsV3, Metadata,
+    },
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::Config;
+use crate::{
+    error::AmmError,
+    helpers::{
+        metadata::{lp_name_and_symbol, mint_symbol},
+        token_extensions::require_supported_mint,
+    },
+    require_valid_fee, Config, CurveType, Observation, Oracle, CANONICAL_SEED_FLAG, MAX_AMP,
+    MIN_AMP, OBSERVATION_COUNT,
+};
 /// Accounts required for initializing the liquidity pool.
 ///
 /// This instruction performs the following actions:
 /// - Creates and initializes the config account using the provided `seed`.
 /// - Creates a new LP token mint controlled by the config.
+/// - Creates the Metaplex metadata of the LP mint, named after the X/Y symbols.
+/// - Creates the price oracle ring buffer of the pool.
 /// - Initializes token vaults (associated token accounts) for token X and token Y, 
 ///   owned by the config.
 /// - Allocates space for the config account and seeds the LP mint and config PDAs.
@@ -19,10 +33,17 @@ use crate::Config;
 /// ## PDA Seeds
 /// - `config` PDA: `["config", seed.to_le_bytes()]`
 /// - `lp_token_mint` PDA: `["lp", config.key()]`
+/// - `oracle` PDA: `["oracle", config.key()]`
+/// - `*_metadata` PDA: `["metadata", metadata_program, mint]`, owned by the metadata program
 ///
 /// ## Constraints
-/// - `token_x_mint` and `token_y_mint` must be valid SPL token mints.
-/// - The LP mint is created with 6 decimal places and authority set to the config.
+/// - `token_x_mint` and `token_y_mint` must be SPL Token or Token-2022 mints owned by
+///   `token_x_program` and `token_y_program`, so a legacy mint can be paired with a
+///   Token-2022 one. Token-2022 mints with non-transferable, permanent delegate or
+///   transfer hook extensions are rejected.
+/// - The LP mint is created under `token_program` with 6 decimal places and authority set
+///   to the config.
+/// - The config is update authority of the LP metadata, see `UpdateLpMetadata`.
 /// - The token vaults are created as associated token accounts with the config as the owner.
 
 #[derive(Accounts)]
@@ -30,18 +51,21 @@ use crate::Config;
 pub struct Initialize<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
-    pub token_x_mint: Account<'info, Mint>,
-    pub token_y_mint: Account<'info, Mint>,
+    #[account(mint::token_program=token_x_program)]
+    pub token_x_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program=token_y_program)]
+    pub token_y_mint: InterfaceAccount<'info, Mint>,
     #[account(
         init,
         payer=admin,
         seeds=[b"lp",config.key().as_ref()],
         bump,
         mint::decimals=6,
-        mint::authority=config
+        mint::authority=config,
+        mint::token_program=token_program
 
     )]
-    pub lp_token_mint: Account<'info, Mint>,
+    pub lp_token_mint: InterfaceAccount<'info, Mint>,
     #[account(
         init,
         payer=admin,
@@ -50,44 +74,191 @@ pub struct Initialize<'info> {
         space= 8+Config::INIT_SPACE
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer=admin,
+        seeds=[b"oracle", config.key().as_ref()],
+        bump,
+        space= 8+Oracle::INIT_SPACE
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
     #[account(
         init,
         payer= admin,
         associated_token::mint=token_x_mint,
         associated_token::authority=config,
+        associated_token::token_program=token_x_program
     )]
-    pub pool_token_x_vault: Account<'info, TokenAccount>,
+    pub pool_token_x_vault: InterfaceAccount<'info, TokenAccount>,
     #[account(
         init, 
         payer= admin,
         associated_token::mint=token_y_mint,
-        associated_token::authority=config
+        associated_token::authority=config,
+        associated_token::token_program=token_y_program
+    )]
+    pub pool_token_y_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Metaplex metadata of token X, only read for its symbol. May not exist.
+    #[account(
+        seeds=[b"metadata", metadata_program.key().as_ref(), token_x_mint.key().as_ref()],
+        seeds::program=metadata_program.key(),
+        bump
+    )]
+    pub token_x_metadata: UncheckedAccount<'info>,
+    /// CHECK: Metaplex metadata of token Y, only read for its symbol. May not exist.
+    #[account(
+        seeds=[b"metadata", metadata_program.key().as_ref(), token_y_mint.key().as_ref()],
+        seeds::program=metadata_program.key(),
+        bump
     )]
-    pub pool_token_y_vault: Account<'info, TokenAccount>,
+    pub token_y_metadata: UncheckedAccount<'info>,
+    /// CHECK: Metaplex metadata of the LP mint, created by the metadata program.
+    #[account(
+        mut,
+        seeds=[b"metadata", metadata_program.key().as_ref(), lp_token_mint.key().as_ref()],
+        seeds::program=metadata_program.key(),
+        bump
+    )]
+    pub lp_token_metadata: UncheckedAccount<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_x_program: Interface<'info, TokenInterface>,
+    pub token_y_program: Interface<'info, TokenInterface>,
+    /// Token program of the LP mint.
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 impl<'info> Initialize<'info> {
-    pub fn initialize(
-        &mut self,
-        seed: u64,
-        fee: u16,
-        authority: Option<Pubkey>,
-        bumps: &InitializeBumps,
-    ) -> Result<()> {
-        self.config.set_inner(Config {
+    /// Creates a pool under any seed outside of the canonical range, see `CreatePool`.
+    pub fn initialize(&mut self, args: InitArgs, bumps: &InitializeBumps) -> Result<()> {
+        require!(
+            args.seed & CANONICAL_SEED_FLAG == 0,
+            AmmError::InvalidPoolSeed
+        );
+        self.init_pool(args, bumps)
+    }
+
+    /// Initializes the config, the oracle and the LP metadata of the pool.
+    pub fn init_pool(&mut self, args: InitArgs, bumps: &InitializeBumps) -> Result<()> {
+        let InitArgs {
             seed,
+            fee,
+            protocol_fee,
+            curve_type,
+            amp,
             authority,
+        } = args;
+        require_valid_fee!(fee);
+        require_valid_fee!(protocol_fee);
+        require_supported_mint(&self.token_x_mint)?;
+        require_supported_mint(&self.token_y_mint)?;
+        if curve_type == CurveType::StableSwap {
+            require!((MIN_AMP..=MAX_AMP).contains(&amp), AmmError::InvalidAmp);
+            // The invariant runs on raw amounts, so a 1:1 peg needs equal decimals.
+            require!(
+                self.token_x_mint.decimals == self.token_y_mint.decimals,
+                AmmError::MismatchedDecimals
+            );
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        self.config.set_inner(Config {
+            seed,
+            authority: authority.or(Some(self.admin.key())),
             token_x_mint: self.token_x_mint.key(),
             token_y_mint: self.token_y_mint.key(),
             fee,
+            dynamic_fee: None,
+            max_price_impact_bps: 0,
+            curve_type,
+            initial_amp: amp,
+            target_amp: amp,
+            ramp_start_timestamp: now,
+            ramp_stop_timestamp: now,
+            protocol_fee,
+            protocol_fees_x: 0,
+            protocol_fees_y: 0,
             locked: false,
+            flash_loan: None,
+            price_x_cumulative: 0,
+            price_y_cumulative: 0,
+            last_update_timestamp: now,
             config_bump: bumps.config,
             lp_bump: bumps.lp_token_mint,
+            oracle_bump: bumps.oracle,
+        });
+
+        let mut observations = [Observation::default(); OBSERVATION_COUNT];
+        observations[0].timestamp = now;
+        self.oracle.set_inner(Oracle {
+            config: self.config.key(),
+            index: 0,
+            observations,
+            bump: bumps.oracle,
         });
-        Ok(())
+
+        self.create_lp_metadata()
     }
+
+    /// Creates the metadata of the LP mint, `"X-Y LP"` with symbol `"X-Y"` and an empty
+    /// URI. The config signs as mint and update authority.
+    pub fn create_lp_metadata(&mut self) -> Result<()> {
+        let symbol_x = mint_symbol(&self.token_x_mint, &self.token_x_metadata);
+        let symbol_y = mint_symbol(&self.token_y_mint, &self.token_y_metadata);
+        let (name, symbol) = lp_name_and_symbol(&symbol_x, &symbol_y);
+
+        let cpi_program = self.metadata_program.to_account_info();
+        let cpi_accounts = CreateMetadataAccountsV3 {
+            metadata: self.lp_token_metadata.to_account_info(),
+            mint: self.lp_token_mint.to_account_info(),
+            mint_authority: self.config.to_account_info(),
+            payer: self.admin.to_account_info(),
+            update_authority: self.config.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            rent: self.rent.to_account_info(),
+        };
+        let signer_seeds = &[
+            b"config",
+            &self.config.seed.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&signer_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        let data = DataV2 {
+            name,
+            symbol,
+            uri: String::new(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        };
+        create_metadata_accounts_v3(cpi_ctx, data, true, true, None)
+    }
+}
+
+/// Arguments used to initialize a new liquidity pool.
+/// - `seed`: Unique seed used to derive the config PDA. Seeds with `CANONICAL_SEED_FLAG`
+///   set are reserved for `create_pool`, which requires `PoolKey::pool_seed`.
+/// - `fee`: Fee in basis points (e.g. 30 = 0.3%) applied on swaps.
+/// - `protocol_fee`: Share of the swap fee kept for the protocol, in basis points of the fee.
+/// - `curve_type`: Pricing invariant of the pool, constant product or StableSwap.
+/// - `amp`: Amplification coefficient of a StableSwap pool. Ignored for constant product pools.
+///   StableSwap pools require both mints to have the same decimals.
+/// - `authority`: Optional admin override. If `None`, the initializer becomes authority.
+///
+/// This struct is passed to the `initialize` instruction to configure the pool.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitArgs {
+    pub seed: u64,
+    pub fee: u16,
+    pub protocol_fee: u16,
+    pub curve_type: CurveType,
+    pub amp: u64,
+    pub authority: Option<Pubkey>,
 }
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::Factory;
+
+/// Accounts required for creating the factory, once per program.
+///
+/// Whoever creates it picks the authority of the canonical pools, so it should be
+/// created right after the program is deployed, before any `create_pool`.
+///
+/// ## PDA Seeds
+/// - `factory` PDA: `["factory"]`
+#[derive(Accounts)]
+pub struct InitializeFactory<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer=admin,
+        seeds=[b"factory"],
+        bump,
+        space= 8+Factory::INIT_SPACE
+    )]
+    pub factory: Account<'info, Factory>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeFactory<'info> {
+    /// Creates the factory with `authority` as the authority of future canonical pools.
+    pub fn initialize_factory(
+        &mut self,
+        authority: Pubkey,
+        bumps: &InitializeFactoryBumps,
+    ) -> Result<()> {
+        self.factory.set_inner(Factory {
+            authority,
+            bump: bumps.factory,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{error::AmmError, require_has_update_authority, Config, Farm, MAX_REWARD_RATE};
+
+/// Accounts required for creating a farm rewarding the LPs of a pool.
+///
+/// Only the pool authority can create a farm. A pool can have one farm per reward mint.
+///
+/// ## PDA Seeds
+/// - `farm` PDA: `["farm", config.key(), reward_mint.key()]`
+///
+/// The staked LP tokens and the reward tokens are held in associated token accounts
+/// owned by the farm.
+#[derive(Accounts)]
+pub struct InitializeFarm<'info> {
+    /// The current pool authority, paying for the new accounts.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump= config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds=[b"lp", config.key().as_ref()],
+        bump= config.lp_bump,
+        mint::token_program=token_program
+    )]
+    pub lp_token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program=reward_token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer=authority,
+        seeds=[b"farm", config.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+        space= 8+Farm::INIT_SPACE
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        init,
+        payer=authority,
+        associated_token::mint=lp_token_mint,
+        associated_token::authority=farm,
+        associated_token::token_program=token_program
+    )]
+    pub farm_lp_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer=authority,
+        associated_token::mint=reward_mint,
+        associated_token::authority=farm,
+        associated_token::token_program=reward_token_program
+    )]
+    pub farm_reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program of the pool, owning the LP mint.
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program owning the reward mint.
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeFarm<'info> {
+    /// Creates the farm, streaming `reward_rate` reward tokens per second from now until
+    /// `end_timestamp`. The rewards must be provided with `fund_farm`.
+    ///
+    /// `reward_rate` is capped at `MAX_REWARD_RATE`.
+    pub fn initialize_farm(
+        &mut self,
+        reward_rate: u64,
+        end_timestamp: i64,
+        bumps: &InitializeFarmBumps,
+    ) -> Result<()> {
+        require_has_update_authority!(self);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            end_timestamp > now && reward_rate <= MAX_REWARD_RATE,
+            AmmError::InvalidRewardSchedule
+        );
+
+        self.farm.set_inner(Farm {
+            config: self.config.key(),
+            lp_token_mint: self.lp_token_mint.key(),
+            reward_mint: self.reward_mint.key(),
+            reward_rate,
+            end_timestamp,
+            last_update_timestamp: now,
+            reward_per_share: 0,
+            total_staked: 0,
+            bump: bumps.farm,
+        });
+        Ok(())
+    }
+}
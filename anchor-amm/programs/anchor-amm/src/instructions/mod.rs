@@ -1,9 +1,43 @@
+pub mod cancel_limit_order;
+pub mod claim_protocol_fees;
+pub mod create_pool;
 pub mod deposit;
+pub mod execute_limit_order;
+pub mod flash_loan;
+pub mod fund_farm;
 pub mod initialize;
+pub mod initialize_factory;
+pub mod initialize_farm;
+pub mod place_limit_order;
+pub mod read_twap;
+pub mod route_swap;
+pub mod stake;
 pub mod withdraw;
 pub mod swap;
+pub mod update;
+pub mod update_factory;
+pub mod update_farm;
+pub mod update_lp_metadata;
+pub mod zap;
 
+pub use cancel_limit_order::*;
+pub use claim_protocol_fees::*;
+pub use create_pool::*;
 pub use deposit::*;
+pub use execute_limit_order::*;
+pub use flash_loan::*;
+pub use fund_farm::*;
 pub use initialize::*;
+pub use initialize_factory::*;
+pub use initialize_farm::*;
+pub use place_limit_order::*;
+pub use read_twap::*;
+pub use route_swap::*;
+pub use stake::*;
 pub use withdraw::*;
 pub use  swap::*;
+pub use update::*;
+pub use update_factory::*;
+pub use update_farm::*;
+pub use update_lp_metadata::*;
+pub use zap::*;
@@ -0,0 +1,149 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::AmmError, require_non_zero, Config, LimitOrder};
+
+/// Accounts required for placing a limit order on a pool.
+///
+/// ## PDA Seeds
+/// - `order` PDA: `["order", config.key(), owner.key(), seed.to_le_bytes()]`
+///
+/// The input tokens move to the `order_escrow` associated token account owned by the
+/// order. The owner's account for the output token is created if needed, as it
+/// receives the output when a keeper executes the order.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct PlaceLimitOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mint::token_program=token_in_program)]
+    pub token_in_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program=token_out_program)]
+    pub token_out_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump= config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer=owner,
+        seeds=[
+            b"order",
+            config.key().as_ref(),
+            owner.key().as_ref(),
+            seed.to_le_bytes().as_ref()
+        ],
+        bump,
+        space= 8+LimitOrder::INIT_SPACE
+    )]
+    pub order: Account<'info, LimitOrder>,
+
+    #[account(
+        init,
+        payer=owner,
+        associated_token::mint=token_in_mint,
+        associated_token::authority=order,
+        associated_token::token_program=token_in_program
+    )]
+    pub order_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_in_mint,
+        associated_token::authority=owner,
+        associated_token::token_program=token_in_program
+    )]
+    pub owner_token_in_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer=owner,
+        associated_token::mint=token_out_mint,
+        associated_token::authority=owner,
+        associated_token::token_program=token_out_program
+    )]
+    pub owner_token_out_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_in_program: Interface<'info, TokenInterface>,
+    pub token_out_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PlaceLimitOrder<'info> {
+    /// Escrows `amount_in` of `token_in_mint` to be sold for at least `min_amount_out`
+    /// of `token_out_mint` before `expires_at`, and `keeper_tip` lamports for the keeper.
+    pub fn place_limit_order(
+        &mut self,
+        seed: u64,
+        amount_in: u64,
+        min_amount_out: u64,
+        expires_at: i64,
+        keeper_tip: u64,
+        bumps: &PlaceLimitOrderBumps,
+    ) -> Result<()> {
+        require_non_zero!([amount_in, min_amount_out]);
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            AmmError::OfferExpired
+        );
+
+        let (token_in_mint, token_out_mint) = (self.token_in_mint.key(), self.token_out_mint.key());
+        let (token_x_mint, token_y_mint) = (self.config.token_x_mint, self.config.token_y_mint);
+        let is_token_x = if token_in_mint == token_x_mint && token_out_mint == token_y_mint {
+            true
+        } else if token_in_mint == token_y_mint && token_out_mint == token_x_mint {
+            false
+        } else {
+            return err!(AmmError::InvalidToken);
+        };
+
+        let transfer_accounts = TransferChecked {
+            from: self.owner_token_in_account.to_account_info(),
+            to: self.order_escrow.to_account_info(),
+            mint: self.token_in_mint.to_account_info(),
+            authority: self.owner.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_in_program.to_account_info(), transfer_accounts);
+        transfer_checked(cpi_ctx, amount_in, self.token_in_mint.decimals)?;
+
+        if keeper_tip > 0 {
+            let cpi_ctx = CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.owner.to_account_info(),
+                    to: self.order.to_account_info(),
+                },
+            );
+            transfer(cpi_ctx, keeper_tip)?;
+        }
+
+        // What reached the escrow, after any transfer fee.
+        self.order_escrow.reload()?;
+        self.order.set_inner(LimitOrder {
+            owner: self.owner.key(),
+            config: self.config.key(),
+            seed,
+            token_in_mint,
+            token_out_mint,
+            is_token_x,
+            amount_in: self.order_escrow.amount,
+            min_amount_out,
+            keeper_tip,
+            expires_at,
+            bump: bumps.order,
+        });
+        Ok(())
+    }
+}
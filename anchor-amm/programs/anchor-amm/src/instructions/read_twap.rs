@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{error::AmmError, require_not_locked, Config, Observation, Oracle, TwapPrice};
+
+/// Read-only accounts used to compute a time-weighted average price for a pool.
+#[derive(Accounts)]
+pub struct ReadTwap<'info> {
+    #[account(mint::token_program=token_x_program)]
+    pub token_x_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program=token_y_program)]
+    pub token_y_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        has_one=token_x_mint,
+        has_one=token_y_mint,
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump= config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds=[b"oracle", config.key().as_ref()],
+        bump= config.oracle_bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
+    #[account(
+        associated_token::mint=token_x_mint,
+        associated_token::authority=config,
+        associated_token::token_program=token_x_program
+    )]
+    pub pool_token_x_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        associated_token::mint=token_y_mint,
+        associated_token::authority=config,
+        associated_token::token_program=token_y_program
+    )]
+    pub pool_token_y_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_x_program: Interface<'info, TokenInterface>,
+    pub token_y_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ReadTwap<'info> {
+    /// Returns the time-weighted average prices over at least the last `window` seconds.
+    ///
+    /// The accumulators are extended to the current time with the current spot price,
+    /// then compared with the most recent observation at or before `now - window`.
+    /// The actual averaging window is returned in `TwapPrice::elapsed`.
+    ///
+    /// Fails with `PoolLocked` while a flash loan is outstanding, when the vault balances
+    /// do not reflect the pool price. An admin lock freezes the reserves, so reads go on.
+    pub fn read_twap(&self, window: u32) -> Result<TwapPrice> {
+        require_not_locked!(self.config.flash_loan.is_some());
+        require!(window > 0, AmmError::InvalidObservationWindow);
+        let now = Clock::get()?.unix_timestamp;
+
+        let mut current = Observation {
+            timestamp: now,
+            price_x_cumulative: self.config.price_x_cumulative,
+            price_y_cumulative: self.config.price_y_cumulative,
+        };
+        let elapsed = now.saturating_sub(self.config.last_update_timestamp);
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
+        if elapsed > 0 && reserve_x != 0 && reserve_y != 0 {
+            let (price_x, price_y) = self.config.spot_prices(reserve_x, reserve_y, now)?;
+            current.price_x_cumulative = current
+                .price_x_cumulative
+                .wrapping_add(price_x.wrapping_mul(elapsed as u128));
+            current.price_y_cumulative = current
+                .price_y_cumulative
+                .wrapping_add(price_y.wrapping_mul(elapsed as u128));
+        }
+
+        let older = self
+            .oracle
+            .observation_at_or_before(now - window as i64)
+            .ok_or(AmmError::InvalidObservationWindow)?;
+        current.twap_since(older)
+    }
+}
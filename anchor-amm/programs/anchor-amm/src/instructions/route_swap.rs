@@ -0,0 +1,316 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::AmmError,
+    helpers::{
+        settlement::{settle_swap, SettledSwap, SwapAmount, SwapInput, SwapLeg, SwapOutput},
+        token_extensions::amount_received,
+    },
+    require_not_expired, require_not_locked, Config, Oracle, SwapEvent, MAX_ROUTE_HOPS,
+    ROUTE_HOP_ACCOUNTS,
+};
+
+/// Swaps through an ordered list of pools in a single instruction, e.g. X → Y → Z.
+///
+/// Each hop is described by `ROUTE_HOP_ACCOUNTS` remaining accounts, in order:
+/// 1. `config` (writable): the pool configuration PDA.
+/// 2. `oracle` (writable): the pool price oracle, `["oracle", config]`.
+/// 3. `lp_token_mint`: the pool LP mint, `["lp", config]`.
+/// 4. `pool_token_in_vault` (writable): the pool vault of the token going in.
+/// 5. `pool_token_out_vault` (writable): the pool vault of the token coming out.
+/// 6. `token_out_mint`: the mint of the token coming out, which is the input of the next hop.
+/// 7. `token_out_program`: the token program owning `token_out_mint`.
+///
+/// Intermediate tokens go straight from one pool vault to the next and never touch the
+/// swapper's accounts. `min_amount_out` is only enforced on the final leg, and any failing
+/// hop reverts the whole route.
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    #[account(mut)]
+    pub token_pair_swapper: Signer<'info>,
+
+    /// Mint of the token the route starts from.
+    #[account(mint::token_program=source_token_program)]
+    pub source_mint: InterfaceAccount<'info, Mint>,
+
+    /// Mint of the token the route ends with.
+    #[account(mint::token_program=destination_token_program)]
+    pub destination_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint=source_mint,
+        associated_token::authority=token_pair_swapper,
+        associated_token::token_program=source_token_program
+    )]
+    pub swapper_source_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer=token_pair_swapper,
+        associated_token::mint=destination_mint,
+        associated_token::authority=token_pair_swapper,
+        associated_token::token_program=destination_token_program
+    )]
+    pub swapper_destination_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub source_token_program: Interface<'info, TokenInterface>,
+    pub destination_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// A validated leg of a route, with the pool state it is priced against.
+struct RouteHop<'info> {
+    config: Account<'info, Config>,
+    oracle: Box<Account<'info, Oracle>>,
+    pool_token_in_vault: &'info AccountInfo<'info>,
+    pool_token_out_vault: &'info AccountInfo<'info>,
+    token_in_mint: InterfaceAccount<'info, Mint>,
+    token_out_mint: InterfaceAccount<'info, Mint>,
+    token_out_program: &'info AccountInfo<'info>,
+    is_token_x: bool,
+    reserves: (u64, u64),
+    lp_supply: u64,
+}
+
+impl<'info> RouteSwap<'info> {
+    /// Validates every hop first, then settles them in order.
+    ///
+    /// - `amount_in`: The amount of the source token sent by the swapper.
+    /// - `min_amount_out`: The minimum amount of the destination token the swapper must receive.
+    /// - `expires_at`: Optional unix timestamp after which the route fails with `OfferExpired`.
+    /// - `hop_accounts`: The remaining accounts describing the route.
+    pub fn route_swap(
+        &mut self,
+        amount_in: u64,
+        min_amount_out: u64,
+        expires_at: Option<i64>,
+        hop_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require_not_expired!(expires_at);
+        require!(amount_in > 0, AmmError::InvalidAmount);
+        require!(
+            !hop_accounts.is_empty() && hop_accounts.len() % ROUTE_HOP_ACCOUNTS == 0,
+            AmmError::InvalidRoute
+        );
+        require!(
+            hop_accounts.len() / ROUTE_HOP_ACCOUNTS <= MAX_ROUTE_HOPS,
+            AmmError::InvalidRoute
+        );
+
+        // Every pool is read before any token moves, so each hop is priced on the
+        // reserves from before the route.
+        let mut hops: Vec<RouteHop<'info>> = Vec::with_capacity(MAX_ROUTE_HOPS);
+        let mut token_in_mint = self.source_mint.clone();
+        for accounts in hop_accounts.chunks(ROUTE_HOP_ACCOUNTS) {
+            let hop = self.load_hop(accounts, token_in_mint)?;
+
+            // The same pool twice would be priced on stale reserves.
+            require!(
+                hops.iter().all(|h| h.config.key() != hop.config.key()),
+                AmmError::InvalidRoute
+            );
+
+            token_in_mint = hop.token_out_mint.clone();
+            hops.push(hop);
+        }
+        require_keys_eq!(
+            token_in_mint.key(),
+            self.destination_mint.key(),
+            AmmError::InvalidRoute
+        );
+
+        // Each hop pays straight into the vault of the next one, the last into the
+        // swapper destination account.
+        let destinations: Vec<AccountInfo<'info>> = hops
+            .iter()
+            .skip(1)
+            .map(|hop| hop.pool_token_in_vault.clone())
+            .chain([self.swapper_destination_account.to_account_info()])
+            .collect();
+        let mut settled_hops: Vec<SettledSwap> = Vec::with_capacity(hops.len());
+        let mut amount_sent = amount_in;
+        for (hop, to) in hops.iter_mut().zip(destinations) {
+            // The first hop is paid by the swapper. The others already hold the output of
+            // the previous hop, minus any transfer fee, and receive it as the amount sent.
+            let (amount, input) = match settled_hops.is_empty() {
+                true => (
+                    amount_sent,
+                    Some(SwapInput {
+                        from: self.swapper_source_account.to_account_info(),
+                        to: hop.pool_token_in_vault.clone(),
+                        authority: self.token_pair_swapper.to_account_info(),
+                        signer_seeds: &[],
+                        mint: &self.source_mint,
+                        token_program: self.source_token_program.to_account_info(),
+                    }),
+                ),
+                false => (amount_received(&hop.token_in_mint, amount_sent)?, None),
+            };
+            let mut settled = settle_swap(SwapLeg {
+                config: &mut hop.config,
+                oracle: &mut hop.oracle,
+                reserves: hop.reserves,
+                lp_supply: hop.lp_supply,
+                is_token_x: hop.is_token_x,
+                amount: SwapAmount::ExactIn(amount),
+                input,
+                output: Some(SwapOutput {
+                    from: hop.pool_token_out_vault.clone(),
+                    to,
+                    mint: &hop.token_out_mint,
+                    token_program: hop.token_out_program.clone(),
+                }),
+            })?;
+            hop.config.exit(&crate::ID)?;
+            hop.oracle.exit(&crate::ID)?;
+
+            // Logged as sent into the pool, transfer fee included, as for the first hop.
+            settled.amount_in = amount_sent;
+            amount_sent = settled.quote.amount_out;
+            settled_hops.push(settled);
+        }
+
+        require!(
+            amount_received(&self.destination_mint, amount_sent)? >= min_amount_out,
+            AmmError::SlippageExceeded
+        );
+        hops.iter()
+            .zip(settled_hops)
+            .try_for_each(|(hop, settled)| self.emit_swap_event(hop, settled))
+    }
+
+    /// Validates the accounts of one hop and reads the reserves it is priced against.
+    fn load_hop(
+        &self,
+        accounts: &'info [AccountInfo<'info>],
+        token_in_mint: InterfaceAccount<'info, Mint>,
+    ) -> Result<RouteHop<'info>> {
+        let [config_info, oracle_info, lp_token_mint_info, pool_token_in_vault, pool_token_out_vault, token_out_mint_info, token_out_program] =
+            accounts
+        else {
+            return err!(AmmError::InvalidRoute);
+        };
+
+        let config = Account::<Config>::try_from(config_info)?;
+        let config_key = Pubkey::create_program_address(
+            &[
+                b"config",
+                &config.seed.to_le_bytes()[..],
+                &[config.config_bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| AmmError::BumpError)?;
+        require_keys_eq!(config_key, config.key(), AmmError::InvalidRoute);
+        require_not_locked!(config.locked);
+
+        let oracle_key = Pubkey::create_program_address(
+            &[b"oracle", config.key().as_ref(), &[config.oracle_bump]],
+            &crate::ID,
+        )
+        .map_err(|_| AmmError::BumpError)?;
+        require_keys_eq!(oracle_key, oracle_info.key(), AmmError::InvalidRoute);
+        let oracle = Box::new(Account::<Oracle>::try_from(oracle_info)?);
+
+        let lp_token_mint_key = Pubkey::create_program_address(
+            &[b"lp", config.key().as_ref(), &[config.lp_bump]],
+            &crate::ID,
+        )
+        .map_err(|_| AmmError::BumpError)?;
+        require_keys_eq!(
+            lp_token_mint_key,
+            lp_token_mint_info.key(),
+            AmmError::InvalidRoute
+        );
+        let lp_token_mint = InterfaceAccount::<Mint>::try_from(lp_token_mint_info)?;
+
+        let token_out_mint = InterfaceAccount::<Mint>::try_from(token_out_mint_info)?;
+        let is_token_x = if token_in_mint.key() == config.token_x_mint {
+            require_keys_eq!(token_out_mint.key(), config.token_y_mint, AmmError::InvalidToken);
+            true
+        } else {
+            require_keys_eq!(token_in_mint.key(), config.token_y_mint, AmmError::InvalidToken);
+            require_keys_eq!(token_out_mint.key(), config.token_x_mint, AmmError::InvalidToken);
+            false
+        };
+
+        // Each side of a pool may live under a different token program.
+        require_keys_eq!(
+            token_out_program.key(),
+            *token_out_mint_info.owner,
+            AmmError::InvalidRoute
+        );
+        require_keys_eq!(
+            pool_token_in_vault.key(),
+            get_associated_token_address_with_program_id(
+                &config.key(),
+                &token_in_mint.key(),
+                token_in_mint.to_account_info().owner
+            ),
+            AmmError::InvalidRoute
+        );
+        require_keys_eq!(
+            pool_token_out_vault.key(),
+            get_associated_token_address_with_program_id(
+                &config.key(),
+                &token_out_mint.key(),
+                &token_out_program.key()
+            ),
+            AmmError::InvalidRoute
+        );
+        let vault_in_amount = InterfaceAccount::<TokenAccount>::try_from(pool_token_in_vault)?.amount;
+        let vault_out_amount = InterfaceAccount::<TokenAccount>::try_from(pool_token_out_vault)?.amount;
+
+        let (vault_x_amount, vault_y_amount) = match is_token_x {
+            true => (vault_in_amount, vault_out_amount),
+            false => (vault_out_amount, vault_in_amount),
+        };
+        let reserves = config.reserves(vault_x_amount, vault_y_amount)?;
+
+        Ok(RouteHop {
+            config,
+            oracle,
+            pool_token_in_vault,
+            pool_token_out_vault,
+            token_in_mint,
+            token_out_mint,
+            token_out_program,
+            is_token_x,
+            reserves,
+            lp_supply: lp_token_mint.supply,
+        })
+    }
+
+    /// Logs a settled hop with the reserves its pool was left with.
+    fn emit_swap_event(&self, hop: &RouteHop<'info>, settled: SettledSwap) -> Result<()> {
+        let vault_in_amount =
+            InterfaceAccount::<TokenAccount>::try_from(hop.pool_token_in_vault)?.amount;
+        let vault_out_amount =
+            InterfaceAccount::<TokenAccount>::try_from(hop.pool_token_out_vault)?.amount;
+        let (vault_x_amount, vault_y_amount) = match hop.is_token_x {
+            true => (vault_in_amount, vault_out_amount),
+            false => (vault_out_amount, vault_in_amount),
+        };
+        let (reserve_x, reserve_y) = hop.config.reserves(vault_x_amount, vault_y_amount)?;
+        emit!(SwapEvent {
+            config: hop.config.key(),
+            swapper: self.token_pair_swapper.key(),
+            is_token_x: hop.is_token_x,
+            amount_in: settled.amount_in,
+            amount_out: settled.quote.amount_out,
+            fee: settled.quote.fee,
+            protocol_fee: settled.protocol_fee,
+            reserve_x,
+            reserve_y,
+            lp_supply: hop.lp_supply,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::AmmError, require_non_zero, Farm, StakePosition};
+
+/// Accounts shared by `stake`, `unstake` and `harvest`.
+///
+/// ## PDA Seeds
+/// - `position` PDA: `["stake", farm.key(), owner.key()]`, created on first use.
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds=[b"farm", farm.config.as_ref(), farm.reward_mint.as_ref()],
+        bump= farm.bump
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        init_if_needed,
+        payer=owner,
+        seeds=[b"stake", farm.key().as_ref(), owner.key().as_ref()],
+        bump,
+        space= 8+StakePosition::INIT_SPACE
+    )]
+    pub position: Account<'info, StakePosition>,
+
+    #[account(
+        address=farm.lp_token_mint,
+        mint::token_program=token_program
+    )]
+    pub lp_token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address=farm.reward_mint,
+        mint::token_program=reward_token_program
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint=lp_token_mint,
+        associated_token::authority=farm,
+        associated_token::token_program=token_program
+    )]
+    pub farm_lp_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint=reward_mint,
+        associated_token::authority=farm,
+        associated_token::token_program=reward_token_program
+    )]
+    pub farm_reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint=lp_token_mint,
+        associated_token::authority=owner,
+        associated_token::token_program=token_program
+    )]
+    pub owner_lp_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer=owner,
+        associated_token::mint=reward_mint,
+        associated_token::authority=owner,
+        associated_token::token_program=reward_token_program
+    )]
+    pub owner_reward_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program of the pool, owning the LP mint.
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program owning the reward mint.
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Stake<'info> {
+    /// Moves `amount` LP tokens from the owner into the farm.
+    pub fn stake(&mut self, amount: u64, bumps: &StakeBumps) -> Result<()> {
+        require_non_zero!([amount]);
+        self.settle_rewards(bumps)?;
+
+        self.position.amount = self
+            .position
+            .amount
+            .checked_add(amount)
+            .ok_or(AmmError::Overflow)?;
+        self.farm.total_staked = self
+            .farm
+            .total_staked
+            .checked_add(amount)
+            .ok_or(AmmError::Overflow)?;
+
+        let transfer_accounts = TransferChecked {
+            from: self.owner_lp_token_account.to_account_info(),
+            to: self.farm_lp_vault.to_account_info(),
+            mint: self.lp_token_mint.to_account_info(),
+            authority: self.owner.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+        transfer_checked(cpi_ctx, amount, self.lp_token_mint.decimals)
+    }
+
+    /// Moves `amount` staked LP tokens back to the owner. Earned rewards stay owed
+    /// until harvested.
+    pub fn unstake(&mut self, amount: u64, bumps: &StakeBumps) -> Result<()> {
+        require_non_zero!([amount]);
+        self.settle_rewards(bumps)?;
+
+        self.position.amount = self
+            .position
+            .amount
+            .checked_sub(amount)
+            .ok_or(AmmError::InsufficientBalance)?;
+        self.farm.total_staked = self
+            .farm
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(AmmError::Overflow)?;
+
+        self.transfer_from_farm(true, amount)
+    }
+
+    /// Pays the owed rewards out to the owner.
+    ///
+    /// Pays at most the reward vault balance. Whatever it cannot cover stays owed.
+    pub fn harvest(&mut self, bumps: &StakeBumps) -> Result<()> {
+        self.settle_rewards(bumps)?;
+
+        let amount = self.position.rewards_owed.min(self.farm_reward_vault.amount);
+        require!(amount > 0, AmmError::ZeroBalance);
+        self.position.rewards_owed -= amount;
+
+        self.transfer_from_farm(false, amount)
+    }
+
+    /// Brings the farm accumulator up to date and books the rewards the position
+    /// earned since it was last touched. Sets up a position created by this instruction.
+    fn settle_rewards(&mut self, bumps: &StakeBumps) -> Result<()> {
+        if self.position.owner == Pubkey::default() {
+            self.position.farm = self.farm.key();
+            self.position.owner = self.owner.key();
+            self.position.bump = bumps.position;
+        }
+
+        self.farm.accrue_rewards(Clock::get()?.unix_timestamp)?;
+        self.position.settle_rewards(self.farm.reward_per_share)
+    }
+
+    /// Transfers staked LP tokens (`is_lp_token`) or reward tokens to the owner,
+    /// signed by the farm.
+    fn transfer_from_farm(&self, is_lp_token: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals, token_program) = match is_lp_token {
+            true => (
+                self.farm_lp_vault.to_account_info(),
+                self.owner_lp_token_account.to_account_info(),
+                self.lp_token_mint.to_account_info(),
+                self.lp_token_mint.decimals,
+                self.token_program.to_account_info(),
+            ),
+            false => (
+                self.farm_reward_vault.to_account_info(),
+                self.owner_reward_account.to_account_info(),
+                self.reward_mint.to_account_info(),
+                self.reward_mint.decimals,
+                self.reward_token_program.to_account_info(),
+            ),
+        };
+        let transfer_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.farm.to_account_info(),
+        };
+        let signer_seeds = &[
+            b"farm",
+            self.farm.config.as_ref(),
+            self.farm.reward_mint.as_ref(),
+            &[self.farm.bump],
+        ];
+        let signer_seeds = &[&signer_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(token_program, transfer_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+}
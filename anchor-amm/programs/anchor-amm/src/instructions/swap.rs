@@ -1,24 +1,31 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
-use constant_product_curve::{ConstantProduct, LiquidityPair};
 
-use crate::{error::AmmError, require_not_locked, Config};
+use crate::{
+    error::AmmError,
+    helpers::{
+        settlement::{settle_swap, SettledSwap, SwapAmount, SwapInput, SwapLeg, SwapOutput},
+        token_extensions::amount_received,
+    },
+    require_not_expired, require_not_locked, Config, Oracle, SwapEvent,
+};
 
 #[derive(Accounts)]
 pub struct Swap<'info> {
     #[account(mut)]
     pub token_pair_swapper: Signer<'info>,
 
-    #[account(mint::token_program=token_program)]
-    pub token_x_mint: Account<'info, Mint>,
+    #[account(mint::token_program=token_x_program)]
+    pub token_x_mint: InterfaceAccount<'info, Mint>,
 
-    #[account(mint::token_program=token_program)]
-    pub token_y_mint: Account<'info, Mint>,
+    #[account(mint::token_program=token_y_program)]
+    pub token_y_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
+        mut,
         has_one=token_x_mint,
         has_one=token_y_mint,
         seeds=[b"config", config.seed.to_le_bytes().as_ref()],
@@ -26,47 +33,57 @@ pub struct Swap<'info> {
     )]
     pub config: Account<'info, Config>,
 
+    /// Price oracle of the pool, updated before the reserves change.
+    #[account(
+        mut,
+        seeds=[b"oracle", config.key().as_ref()],
+        bump= config.oracle_bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
     #[account(
         seeds=[b"lp", config.key().as_ref()],
         bump=config.lp_bump
-
     )]
-    pub lp_token_mint: Account<'info, Mint>,
+    pub lp_token_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         mut,
         associated_token::mint=token_x_mint,
-        associated_token::authority=config
+        associated_token::authority=config,
+        associated_token::token_program=token_x_program
     )]
-    pub pool_token_x_vault: Account<'info, TokenAccount>,
+    pub pool_token_x_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         associated_token::mint=token_y_mint,
-        associated_token::authority=config
+        associated_token::authority=config,
+        associated_token::token_program=token_y_program
     )]
-    pub pool_token_y_vault: Account<'info, TokenAccount>,
+    pub pool_token_y_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         init_if_needed,
         payer=token_pair_swapper,
         associated_token::mint= token_x_mint,
         associated_token::authority=token_pair_swapper,
-        associated_token::token_program=token_program
+        associated_token::token_program=token_x_program
 
     )]
-    pub swapper_token_x_account: Account<'info, TokenAccount>,
+    pub swapper_token_x_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         init_if_needed,
         payer= token_pair_swapper,
         associated_token::mint=token_y_mint,
         associated_token::authority=token_pair_swapper,
-        associated_token::token_program=token_program
+        associated_token::token_program=token_y_program
     )]
-    pub swapper_token_y_account: Account<'info, TokenAccount>,
+    pub swapper_token_y_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_x_program: Interface<'info, TokenInterface>,
+    pub token_y_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
@@ -77,118 +94,139 @@ impl<'info> Swap<'info> {
     /// - `is_token_x`: If true, user is swapping token X for token Y.
     /// - `amount_in`: The amount of the input token to swap.
     /// - `min_amount_out`: The minimum acceptable amount of the output token (slippage protection).
+    /// - `expires_at`: Optional unix timestamp after which the swap fails with `OfferExpired`.
+    ///
+    /// For Token-2022 mints with a transfer fee, the curve is priced on the amount the vault
+    /// actually receives, and `min_amount_out` is checked against what the swapper receives.
+    ///
+    /// Fails with `PriceImpactExceeded` when the trade moves the spot price by more than
+    /// `config.max_price_impact_bps`.
     pub fn swap_tokens(
         &mut self,
         is_token_x: bool,
         amount_in: u64,
         min_amount_out: u64,
+        expires_at: Option<i64>,
     ) -> Result<()> {
+        require_not_expired!(expires_at);
         require_not_locked!(self.config.locked);
         require!(amount_in > 0, AmmError::InvalidAmount);
 
-         // Initialize the curve with current pool state: balances, supply, fee, etc.
-        let mut curve = ConstantProduct::init(
-            self.pool_token_x_vault.amount,
-            self.pool_token_y_vault.amount,
-            self.lp_token_mint.supply,
-            self.config.fee,
-            None,
-        )
-        .map_err(AmmError::from)?;
-
-    // Determine swap direction: X to Y or Y to X.
-        let pair = if is_token_x {
-            LiquidityPair::X
-        } else {
-            LiquidityPair::Y
+        let settled = self.settle(is_token_x, SwapAmount::ExactIn(amount_in))?;
+        let mint_out = match is_token_x {
+            true => &self.token_y_mint,
+            false => &self.token_x_mint,
         };
+        require!(
+            amount_received(mint_out, settled.quote.amount_out)? >= min_amount_out,
+            AmmError::SlippageExceeded
+        );
 
-        // Perform the swap on the curve, passing direction, input, and slippage constraint.
-        let swap_results = curve
-            .swap(pair, amount_in, min_amount_out)
-            .map_err(AmmError::from)?;
-
-        require!(swap_results.deposit!=0, AmmError::InvalidAmount);
-        require!(swap_results.deposit!=0, AmmError::InvalidAmount);
-
-        self.deposit_tokens(is_token_x, swap_results.deposit)?;
-        self.withdraw_tokens(!is_token_x, swap_results.withdraw)?;
-
-
-        Ok(())
+        self.emit_swap_event(is_token_x, settled)
     }
 
-
-    /// Transfers the input token from the swapper to the pool vault.
+    /// Performs a swap that pays out an exact amount of the output token.
     ///
-    /// - `is_token_x`: true if input token is token X; false if it's token Y.
-    /// - `deposit_amount`: the amount of tokens to deposit.
-    pub fn deposit_tokens(&mut self, is_token_x: bool, deposit_amount: u64) -> Result<()> {
-        let (from, to, mint, decimals) = match is_token_x {
-            true => (
-                self.token_pair_swapper.to_account_info(),
-                self.pool_token_x_vault.to_account_info(),
-                self.token_x_mint.to_account_info(),
-                self.token_x_mint.decimals,
-            ),
-            false => (
-                self.token_pair_swapper.to_account_info(),
-                self.pool_token_y_vault.to_account_info(),
-                self.token_y_mint.to_account_info(),
-                self.token_y_mint.decimals,
-            ),
-        };
-        let cpi_program = self.token_program.to_account_info();
-
-        let transfer_accounts = TransferChecked {
-            from,
-            to,
-            mint,
-            authority: self.token_pair_swapper.to_account_info(),
-        };
+    /// - `is_token_x`: If true, user is swapping token X for token Y.
+    /// - `amount_out`: The exact amount of the output token the swapper receives.
+    /// - `max_amount_in`: The maximum amount of the input token the swapper is willing to spend.
+    /// - `expires_at`: Optional unix timestamp after which the swap fails with `OfferExpired`.
+    ///
+    /// The required input is derived from the pool curve, fee included.
+    /// Fails with `SlippageExceeded` when it is above `max_amount_in`.
+    pub fn swap_tokens_exact_out(
+        &mut self,
+        is_token_x: bool,
+        amount_out: u64,
+        max_amount_in: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        require_not_expired!(expires_at);
+        require_not_locked!(self.config.locked);
+        require!(amount_out > 0, AmmError::InvalidAmount);
 
-        let cpi_ctx = CpiContext::new(cpi_program, transfer_accounts);
+        let settled = self.settle(is_token_x, SwapAmount::ExactOut(amount_out))?;
+        require!(settled.amount_in <= max_amount_in, AmmError::SlippageExceeded);
 
-        transfer_checked(cpi_ctx, deposit_amount, decimals)?;
-        Ok(())
+        self.emit_swap_event(is_token_x, settled)
     }
 
-    /// Transfers the output token from the pool vault to the swapper.
+    /// Settles the swap between the swapper accounts and the pool vaults.
     ///
-    /// - `is_token_x`: true if output token is token X; false if it's token Y.
-    /// - `withdraw_amount`: the amount of tokens to withdraw to the user.
-    pub fn withdraw_tokens(&mut self, is_token_x: bool,withdraw_amount: u64) -> Result<()> {
-        let (from, to, mint, decimals) = match is_token_x {
+    /// It is priced against the current reserves. Unclaimed protocol fees sit in the
+    /// vaults but are not part of the reserves.
+    fn settle(&mut self, is_token_x: bool, amount: SwapAmount) -> Result<SettledSwap> {
+        let reserves = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
+        let swapper = self.token_pair_swapper.to_account_info();
+        let (input, output) = match is_token_x {
             true => (
-                self.pool_token_x_vault.to_account_info(),
-                self.token_pair_swapper.to_account_info(),
-                self.token_x_mint.to_account_info(),
-                self.token_x_mint.decimals,
+                SwapInput {
+                    from: self.swapper_token_x_account.to_account_info(),
+                    to: self.pool_token_x_vault.to_account_info(),
+                    authority: swapper,
+                    signer_seeds: &[],
+                    mint: &self.token_x_mint,
+                    token_program: self.token_x_program.to_account_info(),
+                },
+                SwapOutput {
+                    from: self.pool_token_y_vault.to_account_info(),
+                    to: self.swapper_token_y_account.to_account_info(),
+                    mint: &self.token_y_mint,
+                    token_program: self.token_y_program.to_account_info(),
+                },
             ),
             false => (
-                self.pool_token_y_vault.to_account_info(),
-                self.token_pair_swapper.to_account_info(),
-                self.token_y_mint.to_account_info(),
-                self.token_y_mint.decimals,
+                SwapInput {
+                    from: self.swapper_token_y_account.to_account_info(),
+                    to: self.pool_token_y_vault.to_account_info(),
+                    authority: swapper,
+                    signer_seeds: &[],
+                    mint: &self.token_y_mint,
+                    token_program: self.token_y_program.to_account_info(),
+                },
+                SwapOutput {
+                    from: self.pool_token_x_vault.to_account_info(),
+                    to: self.swapper_token_x_account.to_account_info(),
+                    mint: &self.token_x_mint,
+                    token_program: self.token_x_program.to_account_info(),
+                },
             ),
         };
-        let cpi_program = self.token_program.to_account_info();
-        let withdraw_accounts = TransferChecked {
-            from,
-            to,
-            mint,
-            authority: self.config.to_account_info(),
-        };
-        let signer_seeds = &[
-            b"config",
-            &self.config.seed.to_be_bytes()[..],
-            &[self.config.config_bump],
-        ];
-        let signer_seeds = &[&signer_seeds[..]];
 
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, withdraw_accounts, signer_seeds);
+        settle_swap(SwapLeg {
+            config: &mut self.config,
+            oracle: &mut self.oracle,
+            reserves,
+            lp_supply: self.lp_token_mint.supply,
+            is_token_x,
+            amount,
+            input: Some(input),
+            output: Some(output),
+        })
+    }
 
-        transfer_checked(cpi_ctx, withdraw_amount, decimals)?;
+    /// Logs a settled swap with the reserves it left the pool with.
+    fn emit_swap_event(&mut self, is_token_x: bool, settled: SettledSwap) -> Result<()> {
+        self.pool_token_x_vault.reload()?;
+        self.pool_token_y_vault.reload()?;
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
+        emit!(SwapEvent {
+            config: self.config.key(),
+            swapper: self.token_pair_swapper.key(),
+            is_token_x,
+            amount_in: settled.amount_in,
+            amount_out: settled.quote.amount_out,
+            fee: settled.quote.fee,
+            protocol_fee: settled.protocol_fee,
+            reserve_x,
+            reserve_y,
+            lp_supply: self.lp_token_mint.supply,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 }
@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::AmmError, require_has_update_authority, require_valid_fee, Config, CurveType,
+    DynamicFee, MAX_AMP, MAX_AMP_CHANGE, MIN_AMP, MIN_RAMP_DURATION,
+};
+
+/// Accounts required for the pool administration instructions.
+///
+/// Every instruction using this context must be signed by the pool authority
+/// stored in `config.authority`. Once the authority has been renounced, none of
+/// these instructions can be executed anymore.
+#[derive(Accounts)]
+pub struct Update<'info> {
+    /// The current pool authority.
+    pub authority: Signer<'info>,
+
+    /// The pool configuration being updated.
+    /// - PDA derived from seed `[b"config", config.seed.to_le_bytes()]`
+    #[account(
+        mut,
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump= config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> Update<'info> {
+    /// Pauses the pool. Deposits, withdrawals and swaps fail with `PoolLocked`
+    /// until the pool is unlocked again. Fails while a flash loan is outstanding, since
+    /// `flash_repay` unlocks the pool.
+    pub fn lock(&mut self) -> Result<()> {
+        require_has_update_authority!(self);
+        require!(self.config.flash_loan.is_none(), AmmError::InvalidFlashLoan);
+        self.config.locked = true;
+        Ok(())
+    }
+
+    /// Resumes a paused pool. Fails while a flash loan is outstanding, the pool is then
+    /// unlocked by `flash_repay`.
+    pub fn unlock(&mut self) -> Result<()> {
+        require_has_update_authority!(self);
+        require!(self.config.flash_loan.is_none(), AmmError::InvalidFlashLoan);
+        self.config.locked = false;
+        Ok(())
+    }
+
+    /// Sets the swap fee charged by the pool, in basis points.
+    pub fn update_fee(&mut self, fee: u16) -> Result<()> {
+        require_has_update_authority!(self);
+        require_valid_fee!(fee);
+        self.config.fee = fee;
+        Ok(())
+    }
+
+    /// Sets the share of the swap fee kept for the protocol, in basis points of the fee.
+    pub fn update_protocol_fee(&mut self, protocol_fee: u16) -> Result<()> {
+        require_has_update_authority!(self);
+        require_valid_fee!(protocol_fee);
+        self.config.protocol_fee = protocol_fee;
+        Ok(())
+    }
+
+    /// Starts moving the amplification coefficient of a StableSwap pool linearly from
+    /// its current value to `target_amp`, reached at `ramp_stop_timestamp`.
+    ///
+    /// The ramp must last at least `MIN_RAMP_DURATION` and cannot change the coefficient
+    /// by more than a factor of `MAX_AMP_CHANGE`, so LPs have time to react.
+    pub fn ramp_amp(&mut self, target_amp: u64, ramp_stop_timestamp: i64) -> Result<()> {
+        require_has_update_authority!(self);
+        require!(
+            self.config.curve_type == CurveType::StableSwap,
+            AmmError::InvalidCurve
+        );
+        require!((MIN_AMP..=MAX_AMP).contains(&target_amp), AmmError::InvalidAmp);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ramp_stop_timestamp >= now + MIN_RAMP_DURATION,
+            AmmError::InvalidRamp
+        );
+
+        let current_amp = self.config.current_amp(now);
+        require!(
+            target_amp <= current_amp.saturating_mul(MAX_AMP_CHANGE)
+                && target_amp.saturating_mul(MAX_AMP_CHANGE) >= current_amp,
+            AmmError::InvalidRamp
+        );
+
+        self.config.initial_amp = current_amp;
+        self.config.target_amp = target_amp;
+        self.config.ramp_start_timestamp = now;
+        self.config.ramp_stop_timestamp = ramp_stop_timestamp;
+        Ok(())
+    }
+
+    /// Freezes the amplification coefficient at its current value.
+    pub fn stop_ramp_amp(&mut self) -> Result<()> {
+        require_has_update_authority!(self);
+        require!(
+            self.config.curve_type == CurveType::StableSwap,
+            AmmError::InvalidCurve
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let current_amp = self.config.current_amp(now);
+        self.config.initial_amp = current_amp;
+        self.config.target_amp = current_amp;
+        self.config.ramp_start_timestamp = now;
+        self.config.ramp_stop_timestamp = now;
+        Ok(())
+    }
+
+    /// Switches the pool to the volatility based fee, or updates its parameters.
+    ///
+    /// The fee stays between `min_fee` and `max_fee`. The accumulated volatility is
+    /// kept when the dynamic fee was already enabled and starts at zero otherwise.
+    pub fn set_dynamic_fee(
+        &mut self,
+        min_fee: u16,
+        max_fee: u16,
+        volatility_factor: u16,
+        decay_period: u32,
+    ) -> Result<()> {
+        require_has_update_authority!(self);
+        require_valid_fee!(max_fee);
+        require!(
+            min_fee <= max_fee && decay_period > 0,
+            AmmError::InvalidDynamicFee
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let volatility = self
+            .config
+            .dynamic_fee
+            .map_or(0, |dynamic_fee| dynamic_fee.decayed_volatility(now));
+        self.config.dynamic_fee = Some(DynamicFee {
+            min_fee,
+            max_fee,
+            volatility_factor,
+            decay_period,
+            volatility,
+            last_update_timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Goes back to charging the fixed `fee`.
+    pub fn disable_dynamic_fee(&mut self) -> Result<()> {
+        require_has_update_authority!(self);
+        self.config.dynamic_fee = None;
+        Ok(())
+    }
+
+    /// Caps the move of the spot price a single swap may cause, in basis points.
+    /// Zero removes the cap.
+    pub fn update_max_price_impact(&mut self, max_price_impact_bps: u16) -> Result<()> {
+        require_has_update_authority!(self);
+        self.config.max_price_impact_bps = max_price_impact_bps;
+        Ok(())
+    }
+
+    /// Hands control of the pool over to `new_authority`.
+    pub fn transfer_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        require_has_update_authority!(self);
+        self.config.authority = Some(new_authority);
+        Ok(())
+    }
+
+    /// Permanently removes the pool authority. The pool keeps its current fee and
+    /// lock state forever after this call.
+    ///
+    /// Only the authority can claim protocol fees, so the accrued ones must be claimed
+    /// first and the protocol fee drops to zero: from then on the whole swap fee goes
+    /// to the LPs instead of piling up in the vaults.
+    pub fn renounce_authority(&mut self) -> Result<()> {
+        require_has_update_authority!(self);
+        require!(
+            self.config.protocol_fees_x == 0 && self.config.protocol_fees_y == 0,
+            AmmError::UnclaimedProtocolFees
+        );
+        self.config.protocol_fee = 0;
+        self.config.authority = None;
+        Ok(())
+    }
+}
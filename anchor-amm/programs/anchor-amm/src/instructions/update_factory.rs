@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, Factory};
+
+/// Accounts required for changing the factory settings.
+#[derive(Accounts)]
+pub struct UpdateFactory<'info> {
+    /// The current factory authority.
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one=authority @ AmmError::InvalidAuthority,
+        seeds=[b"factory"],
+        bump= factory.bump
+    )]
+    pub factory: Account<'info, Factory>,
+}
+
+impl<'info> UpdateFactory<'info> {
+    /// Makes `new_authority` the authority of the canonical pools created from now on.
+    ///
+    /// Existing canonical pools are handed over one by one with `transfer_authority`.
+    pub fn transfer_factory_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        self.factory.authority = new_authority;
+        Ok(())
+    }
+}
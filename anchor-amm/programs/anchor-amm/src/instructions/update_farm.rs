@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, require_has_update_authority, Config, Farm, MAX_REWARD_RATE};
+
+/// Accounts required for changing the reward schedule of a farm.
+///
+/// Must be signed by the authority of the pool the farm belongs to.
+#[derive(Accounts)]
+pub struct UpdateFarm<'info> {
+    /// The current pool authority.
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump= config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        has_one=config,
+        seeds=[b"farm", config.key().as_ref(), farm.reward_mint.as_ref()],
+        bump= farm.bump
+    )]
+    pub farm: Account<'info, Farm>,
+}
+
+impl<'info> UpdateFarm<'info> {
+    /// Streams `reward_rate` reward tokens per second from now until `end_timestamp`.
+    ///
+    /// Rewards accrued so far are kept at the previous rate. An `end_timestamp` equal to
+    /// the current time stops the farm. `reward_rate` is capped at `MAX_REWARD_RATE`.
+    pub fn update_farm(&mut self, reward_rate: u64, end_timestamp: i64) -> Result<()> {
+        require_has_update_authority!(self);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            end_timestamp >= now && reward_rate <= MAX_REWARD_RATE,
+            AmmError::InvalidRewardSchedule
+        );
+
+        self.farm.accrue_rewards(now)?;
+        // Nothing accrued after the previous end, so a restarted farm does not pay for it.
+        self.farm.last_update_timestamp = now;
+        self.farm.reward_rate = reward_rate;
+        self.farm.end_timestamp = end_timestamp;
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::{
+        mpl_token_metadata::{accounts::Metadata as MetadataAccount, types::DataV2, MAX_URI_LENGTH},
+        update_metadata_accounts_v2, Metadata, UpdateMetadataAccountsV2,
+    },
+    token_interface::Mint,
+};
+
+use crate::{error::AmmError, require_has_update_authority, Config};
+
+/// Accounts required for updating the metadata of the LP mint.
+///
+/// Must be signed by the pool authority. The config PDA is update authority of the
+/// metadata and signs the metadata program CPI.
+///
+/// ## PDA Seeds
+/// - `config` PDA: `["config", config.seed.to_le_bytes()]`
+/// - `lp_token_mint` PDA: `["lp", config.key()]`
+/// - `lp_token_metadata` PDA: `["metadata", metadata_program, lp_token_mint]`
+#[derive(Accounts)]
+pub struct UpdateLpMetadata<'info> {
+    /// The current pool authority.
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump= config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds=[b"lp", config.key().as_ref()],
+        bump= config.lp_bump
+    )]
+    pub lp_token_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: Metaplex metadata of the LP mint, validated by the metadata program.
+    #[account(
+        mut,
+        seeds=[b"metadata", metadata_program.key().as_ref(), lp_token_mint.key().as_ref()],
+        seeds::program=metadata_program.key(),
+        bump
+    )]
+    pub lp_token_metadata: UncheckedAccount<'info>,
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> UpdateLpMetadata<'info> {
+    /// Points the LP metadata to `uri`, keeping its name and symbol.
+    pub fn update_lp_metadata_uri(&mut self, uri: String) -> Result<()> {
+        require_has_update_authority!(self);
+        require!(uri.len() <= MAX_URI_LENGTH, AmmError::InvalidMetadataUri);
+
+        let metadata = {
+            let data = self.lp_token_metadata.try_borrow_data()?;
+            MetadataAccount::safe_deserialize(&data)
+                .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?
+        };
+
+        let cpi_program = self.metadata_program.to_account_info();
+        let cpi_accounts = UpdateMetadataAccountsV2 {
+            metadata: self.lp_token_metadata.to_account_info(),
+            update_authority: self.config.to_account_info(),
+        };
+        let signer_seeds = &[
+            b"config",
+            &self.config.seed.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&signer_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        let data = DataV2 {
+            name: metadata.name.trim_matches(char::from(0)).to_string(),
+            symbol: metadata.symbol.trim_matches(char::from(0)).to_string(),
+            uri,
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            creators: metadata.creators,
+            collection: metadata.collection,
+            uses: metadata.uses,
+        };
+        update_metadata_accounts_v2(cpi_ctx, None, Some(data), None, None)
+    }
+}
@@ -1,22 +1,26 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    mint,
-    token::{burn, transfer_checked, Burn, Mint, Token, TokenAccount, TransferChecked},
+    token_interface::{
+        burn, transfer_checked, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
 };
-use constant_product_curve::ConstantProduct;
 
-use crate::{error::AmmError, require_non_zero, require_not_locked, state, Config};
+use crate::{
+    error::AmmError, helpers::token_extensions::amount_received, require_non_zero,
+    require_not_expired, require_not_locked, Config, Oracle, WithdrawEvent,
+};
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
     pub withdrawer: Signer<'info>,
-    #[account(mint::token_program=token_program)]
-    pub token_x_mint: Account<'info, Mint>,
-    #[account(mint::token_program=token_program)]
-    pub token_y_mint: Account<'info, Mint>,
+    #[account(mint::token_program=token_x_program)]
+    pub token_x_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program=token_y_program)]
+    pub token_y_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
+        mut,
         has_one=token_x_mint,
         has_one=token_y_mint,
         seeds=[b"config", config.seed.to_le_bytes().as_ref()],
@@ -24,98 +28,150 @@ pub struct Withdraw<'info> {
     )]
     pub config: Account<'info, Config>,
 
+    /// Price oracle of the pool, updated before the reserves change.
+    #[account(
+        mut,
+        seeds=[b"oracle", config.key().as_ref()],
+        bump= config.oracle_bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
     #[account(
         mut,
         seeds=[b"lp",config.key().as_ref()],
-        bump= config.lp_bump
+        bump= config.lp_bump,
+        mint::token_program=token_program
     )]
-    pub lp_token_mint: Account<'info, Mint>,
+    pub lp_token_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         mut,
         associated_token::mint=token_x_mint,
-        associated_token::authority=config     
+        associated_token::authority=config,
+        associated_token::token_program=token_x_program
     )]
-    pub pool_token_x_vault: Account<'info, TokenAccount>,
+    pub pool_token_x_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         associated_token::mint=token_y_mint,
-        associated_token::authority=config     
+        associated_token::authority=config,
+        associated_token::token_program=token_y_program
     )]
-    pub pool_token_y_vault: Account<'info, TokenAccount>,
+    pub pool_token_y_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        associated_token::mint=token_y_mint,
-        associated_token::authority=config
+        associated_token::mint=token_x_mint,
+        associated_token::authority=withdrawer,
+        associated_token::token_program=token_x_program
     )]
-    pub withdrawer_token_x_account: Account<'info, TokenAccount>,
+    pub withdrawer_token_x_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         associated_token::mint=token_y_mint,
-        associated_token::authority=config
+        associated_token::authority=withdrawer,
+        associated_token::token_program=token_y_program
     )]
-    pub withdrawer_token_y_account: Account<'info, TokenAccount>,
+    pub withdrawer_token_y_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         associated_token::mint=lp_token_mint,
-        associated_token::authority=withdrawer
+        associated_token::authority=withdrawer,
+        associated_token::token_program=token_program
 
     )]
-    pub withdrawer_lp_token_account: Account<'info, TokenAccount>,
+    pub withdrawer_lp_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_x_program: Interface<'info, TokenInterface>,
+    pub token_y_program: Interface<'info, TokenInterface>,
+    /// Token program of the LP mint.
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info> Withdraw<'info> {
-    pub fn withdraw(&mut self, lp_amount_to_be_burned: u64, min_x: u64, min_y: u64) -> Result<()> {
+    /// Burns `lp_amount_to_be_burned` LP tokens for the matching share of both reserves.
+    ///
+    /// - `min_x` and `min_y`: The least token X and Y the withdrawer accepts to receive.
+    /// - `expires_at`: Optional unix timestamp after which the withdrawal fails with `OfferExpired`.
+    pub fn withdraw(
+        &mut self,
+        lp_amount_to_be_burned: u64,
+        min_x: u64,
+        min_y: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        require_not_expired!(expires_at);
         require_not_locked!(self.config.locked);
         require_non_zero!([lp_amount_to_be_burned]);
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
         require!(
-            !(self.lp_token_mint.supply==0&& self.pool_token_x_vault.amount==0&& self.pool_token_y_vault.amount==0),
+            !(self.lp_token_mint.supply==0&& reserve_x==0&& reserve_y==0),
             AmmError::NoLiquidityInPool,
         );
-
-
-        let (x, y) = {
-            let withdraw_amount = ConstantProduct::xy_withdraw_amounts_from_l(
-                self.pool_token_x_vault.amount,
-                self.pool_token_y_vault.amount,
-                self.lp_token_mint.supply,
-                lp_amount_to_be_burned,
-                6,
-            )
-            .map_err(AmmError::from)?;
-            (withdraw_amount.x, withdraw_amount.y)
-        };
-        require!(x>= min_x&& y>=min_y, AmmError::SlippageExceeded);
+        self.config
+            .update_price_accumulators(&mut self.oracle, reserve_x, reserve_y)?;
+
+
+        let (x, y) = self.config.withdraw_amounts(
+            reserve_x,
+            reserve_y,
+            self.lp_token_mint.supply,
+            lp_amount_to_be_burned,
+        )?;
+        // Slippage is checked against what the withdrawer actually receives after
+        // any transfer fee withheld by the mints.
+        require!(
+            amount_received(&self.token_x_mint, x)? >= min_x
+                && amount_received(&self.token_y_mint, y)? >= min_y,
+            AmmError::SlippageExceeded
+        );
         self.withdraw_tokens(x, true)?;
         self.withdraw_tokens(y, false)?;
         self.burn_lp_tokens(lp_amount_to_be_burned)?;
 
+        self.pool_token_x_vault.reload()?;
+        self.pool_token_y_vault.reload()?;
+        self.lp_token_mint.reload()?;
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
+        emit!(WithdrawEvent {
+            config: self.config.key(),
+            withdrawer: self.withdrawer.key(),
+            amount_x: x,
+            amount_y: y,
+            lp_amount: lp_amount_to_be_burned,
+            reserve_x,
+            reserve_y,
+            lp_supply: self.lp_token_mint.supply,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
     pub fn withdraw_tokens(&mut self, amount: u64, is_token_x: bool) -> Result<()> {
-        let (from, to, mint, decimals) = match is_token_x {
+        let (from, to, mint, decimals, cpi_program) = match is_token_x {
             true => (
                 self.pool_token_x_vault.to_account_info(),
                 self.withdrawer_token_x_account.to_account_info(),
                 self.token_x_mint.to_account_info(),
                 self.token_x_mint.decimals,
+                self.token_x_program.to_account_info(),
             ),
             false => (
                 self.pool_token_y_vault.to_account_info(),
                 self.withdrawer_token_y_account.to_account_info(),
                 self.token_y_mint.to_account_info(),
                 self.token_y_mint.decimals,
+                self.token_y_program.to_account_info(),
             ),
         };
-        let cpi_program = self.token_program.to_account_info();
         let transfer_accounts = TransferChecked {
             from,
             to,
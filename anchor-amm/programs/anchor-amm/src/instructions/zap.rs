@@ -0,0 +1,438 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        burn, mint_to, transfer_checked, Burn, Mint, MintTo, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{
+    error::AmmError,
+    helpers::{
+        curve::{liquidity_for_amount, zap_swap_amount, SwapQuote},
+        settlement::{settle_swap, SwapAmount, SwapLeg},
+        token_extensions::amount_received,
+    },
+    require_non_zero, require_not_expired, require_not_locked, Config, CurveType, DepositEvent,
+    Oracle, SwapEvent, WithdrawEvent,
+};
+
+/// Accounts for providing or removing liquidity with a single token of the pair.
+///
+/// The swap between the two sides happens inside the pool: only the token the user
+/// provides or receives is transferred.
+#[derive(Accounts)]
+pub struct Zap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mint::token_program=token_x_program)]
+    pub token_x_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program=token_y_program)]
+    pub token_y_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one=token_x_mint,
+        has_one=token_y_mint,
+        seeds=[b"config", config.seed.to_le_bytes().as_ref()],
+        bump= config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Price oracle of the pool, updated before the reserves change.
+    #[account(
+        mut,
+        seeds=[b"oracle", config.key().as_ref()],
+        bump= config.oracle_bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
+    #[account(
+        mut,
+        seeds=[b"lp", config.key().as_ref()],
+        bump= config.lp_bump,
+        mint::token_program=token_program
+    )]
+    pub lp_token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_x_mint,
+        associated_token::authority=config,
+        associated_token::token_program=token_x_program
+    )]
+    pub pool_token_x_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint=token_y_mint,
+        associated_token::authority=config,
+        associated_token::token_program=token_y_program
+    )]
+    pub pool_token_y_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer= user,
+        associated_token::mint=token_x_mint,
+        associated_token::authority=user,
+        associated_token::token_program=token_x_program
+    )]
+    pub user_token_x_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer= user,
+        associated_token::mint=token_y_mint,
+        associated_token::authority=user,
+        associated_token::token_program=token_y_program
+    )]
+    pub user_token_y_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer= user,
+        associated_token::mint=lp_token_mint,
+        associated_token::authority=user,
+        associated_token::token_program=token_program
+    )]
+    pub user_lp_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_x_program: Interface<'info, TokenInterface>,
+    pub token_y_program: Interface<'info, TokenInterface>,
+    /// Token program of the LP mint.
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Zap<'info> {
+    /// Adds liquidity with token X or token Y only.
+    ///
+    /// - `is_token_x`: If true, the user deposits token X; otherwise token Y.
+    /// - `amount_in`: The amount of that token sent by the user.
+    /// - `min_lp_amount`: The least LP tokens the user accepts to receive.
+    /// - `expires_at`: Optional unix timestamp after which the deposit fails with `OfferExpired`.
+    ///
+    /// The share of the deposit that balances it is swapped on the constant product curve,
+    /// then LP tokens are minted for the balanced amounts. Rounding dust stays in the pool.
+    pub fn zap_deposit(
+        &mut self,
+        is_token_x: bool,
+        amount_in: u64,
+        min_lp_amount: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        require_not_expired!(expires_at);
+        require_not_locked!(self.config.locked);
+        require_non_zero!([amount_in]);
+        require!(
+            self.config.curve_type == CurveType::ConstantProduct,
+            AmmError::InvalidCurve
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let lp_supply = self.lp_token_mint.supply;
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
+        require!(lp_supply != 0, AmmError::NoLiquidityInPool);
+
+        let (mint_in, reserve_in, reserve_out) = match is_token_x {
+            true => (&self.token_x_mint, reserve_x, reserve_y),
+            false => (&self.token_y_mint, reserve_y, reserve_x),
+        };
+        let amount_in_received = amount_received(mint_in, amount_in)?;
+
+        let swap_amount = zap_swap_amount(reserve_in, amount_in_received, self.config.swap_fee(now))?;
+        // The swapped share is part of the deposit: its tokens never leave the vaults.
+        let settled = settle_swap(SwapLeg {
+            config: &mut self.config,
+            oracle: &mut self.oracle,
+            reserves: (reserve_x, reserve_y),
+            lp_supply,
+            is_token_x,
+            amount: SwapAmount::ExactIn(swap_amount),
+            input: None,
+            output: None,
+        })?;
+        let (quote, protocol_fee) = (settled.quote, settled.protocol_fee);
+
+        // Reserves once the swapped part has gone through the curve.
+        let reserve_in = reserve_in
+            .checked_add(swap_amount - protocol_fee)
+            .ok_or(AmmError::Overflow)?;
+        let reserve_out = reserve_out - quote.amount_out;
+
+        let lp_amount =
+            liquidity_for_amount(amount_in_received - swap_amount, reserve_in, lp_supply)?
+                .min(liquidity_for_amount(quote.amount_out, reserve_out, lp_supply)?);
+        require!(lp_amount != 0, AmmError::InvalidAmount);
+        require!(lp_amount >= min_lp_amount, AmmError::SlippageExceeded);
+
+        self.deposit_tokens(is_token_x, amount_in)?;
+        self.mint_lp_tokens(lp_amount)?;
+
+        let (amount_x, amount_y) = match is_token_x {
+            true => (amount_in, 0),
+            false => (0, amount_in),
+        };
+        let (reserve_x, reserve_y) = self.reload_reserves()?;
+        self.emit_swap_event(is_token_x, quote, protocol_fee, reserve_x, reserve_y)?;
+        emit!(DepositEvent {
+            config: self.config.key(),
+            depositor: self.user.key(),
+            amount_x,
+            amount_y,
+            lp_amount,
+            reserve_x,
+            reserve_y,
+            lp_supply: self.lp_token_mint.supply,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Removes liquidity and pays it out in token X or token Y only.
+    ///
+    /// - `is_token_x`: If true, the user receives token X; otherwise token Y.
+    /// - `lp_amount`: The LP tokens to burn.
+    /// - `min_amount_out`: The least amount of the token the user accepts to receive.
+    /// - `expires_at`: Optional unix timestamp after which the withdrawal fails with `OfferExpired`.
+    ///
+    /// The share of the other token released by the burn is swapped back into the pool
+    /// on its curve, against the reserves left after the withdrawal. Fails with
+    /// `InvalidAmount` when that share is too small to buy anything.
+    pub fn zap_withdraw(
+        &mut self,
+        is_token_x: bool,
+        lp_amount: u64,
+        min_amount_out: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        require_not_expired!(expires_at);
+        require_not_locked!(self.config.locked);
+        require_non_zero!([lp_amount]);
+        let now = Clock::get()?.unix_timestamp;
+        let lp_supply = self.lp_token_mint.supply;
+        let (reserve_x, reserve_y) = self.update_oracle()?;
+        require!(lp_supply != 0, AmmError::NoLiquidityInPool);
+
+        let (x, y) = self
+            .config
+            .withdraw_amounts(reserve_x, reserve_y, lp_supply, lp_amount)?;
+        let (amount_kept, amount_swapped) = match is_token_x {
+            true => (x, y),
+            false => (y, x),
+        };
+
+        // The other token is sold to the pool as it stands after the withdrawal, without
+        // leaving the vaults. The accumulators already saw the reserves from before it.
+        let (quote, protocol_fee) = match amount_swapped {
+            0 => (
+                SwapQuote {
+                    amount_in: 0,
+                    fee: 0,
+                    amount_out: 0,
+                },
+                0,
+            ),
+            _ => {
+                let settled = settle_swap(SwapLeg {
+                    config: &mut self.config,
+                    oracle: &mut self.oracle,
+                    reserves: (reserve_x - x, reserve_y - y),
+                    lp_supply: lp_supply
+                        .checked_sub(lp_amount)
+                        .ok_or(AmmError::InsufficientBalance)?,
+                    is_token_x: !is_token_x,
+                    amount: SwapAmount::ExactIn(amount_swapped),
+                    input: None,
+                    output: None,
+                })?;
+                (settled.quote, settled.protocol_fee)
+            }
+        };
+
+        let amount_out = amount_kept
+            .checked_add(quote.amount_out)
+            .ok_or(AmmError::Overflow)?;
+        require!(amount_out != 0, AmmError::InvalidAmount);
+        let mint_out = match is_token_x {
+            true => &self.token_x_mint,
+            false => &self.token_y_mint,
+        };
+        require!(
+            amount_received(mint_out, amount_out)? >= min_amount_out,
+            AmmError::SlippageExceeded
+        );
+
+        self.burn_lp_tokens(lp_amount)?;
+        self.withdraw_tokens(is_token_x, amount_out)?;
+
+        let (amount_x, amount_y) = match is_token_x {
+            true => (amount_out, 0),
+            false => (0, amount_out),
+        };
+        let (reserve_x, reserve_y) = self.reload_reserves()?;
+        self.emit_swap_event(!is_token_x, quote, protocol_fee, reserve_x, reserve_y)?;
+        emit!(WithdrawEvent {
+            config: self.config.key(),
+            withdrawer: self.user.key(),
+            amount_x,
+            amount_y,
+            lp_amount,
+            reserve_x,
+            reserve_y,
+            lp_supply: self.lp_token_mint.supply,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Feeds the pre-withdrawal reserves into the price accumulators and the oracle,
+    /// and returns them.
+    fn update_oracle(&mut self) -> Result<(u64, u64)> {
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)?;
+        self.config
+            .update_price_accumulators(&mut self.oracle, reserve_x, reserve_y)?;
+        Ok((reserve_x, reserve_y))
+    }
+
+    /// Reloads the vaults and the LP mint after the transfers and returns the new reserves.
+    fn reload_reserves(&mut self) -> Result<(u64, u64)> {
+        self.pool_token_x_vault.reload()?;
+        self.pool_token_y_vault.reload()?;
+        self.lp_token_mint.reload()?;
+        self.config
+            .reserves(self.pool_token_x_vault.amount, self.pool_token_y_vault.amount)
+    }
+
+    /// Logs the swap made inside the pool, unless there was nothing to swap.
+    fn emit_swap_event(
+        &self,
+        is_token_x: bool,
+        quote: SwapQuote,
+        protocol_fee: u64,
+        reserve_x: u64,
+        reserve_y: u64,
+    ) -> Result<()> {
+        if quote.amount_in == 0 {
+            return Ok(());
+        }
+        emit!(SwapEvent {
+            config: self.config.key(),
+            swapper: self.user.key(),
+            is_token_x,
+            amount_in: quote.amount_in,
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+            protocol_fee,
+            reserve_x,
+            reserve_y,
+            lp_supply: self.lp_token_mint.supply,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Transfers the deposited token from the user to its pool vault.
+    fn deposit_tokens(&self, is_token_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals, token_program) = match is_token_x {
+            true => (
+                self.user_token_x_account.to_account_info(),
+                self.pool_token_x_vault.to_account_info(),
+                self.token_x_mint.to_account_info(),
+                self.token_x_mint.decimals,
+                self.token_x_program.to_account_info(),
+            ),
+            false => (
+                self.user_token_y_account.to_account_info(),
+                self.pool_token_y_vault.to_account_info(),
+                self.token_y_mint.to_account_info(),
+                self.token_y_mint.decimals,
+                self.token_y_program.to_account_info(),
+            ),
+        };
+        let transfer_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.user.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program, transfer_accounts);
+        transfer_checked(cpi_ctx, amount, decimals)?;
+        Ok(())
+    }
+
+    /// Transfers the withdrawn token from its pool vault to the user.
+    fn withdraw_tokens(&self, is_token_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals, token_program) = match is_token_x {
+            true => (
+                self.pool_token_x_vault.to_account_info(),
+                self.user_token_x_account.to_account_info(),
+                self.token_x_mint.to_account_info(),
+                self.token_x_mint.decimals,
+                self.token_x_program.to_account_info(),
+            ),
+            false => (
+                self.pool_token_y_vault.to_account_info(),
+                self.user_token_y_account.to_account_info(),
+                self.token_y_mint.to_account_info(),
+                self.token_y_mint.decimals,
+                self.token_y_program.to_account_info(),
+            ),
+        };
+        let transfer_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.config.to_account_info(),
+        };
+        let signer_seeds = &[
+            b"config",
+            &self.config.seed.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&signer_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(token_program, transfer_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, amount, decimals)?;
+        Ok(())
+    }
+
+    fn mint_lp_tokens(&self, amount: u64) -> Result<()> {
+        let cpi_accounts = MintTo {
+            mint: self.lp_token_mint.to_account_info(),
+            to: self.user_lp_token_account.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+        let signer_seeds = &[
+            b"config",
+            &self.config.seed.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&signer_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        mint_to(cpi_ctx, amount)?;
+        Ok(())
+    }
+
+    fn burn_lp_tokens(&self, amount: u64) -> Result<()> {
+        let burn_accounts = Burn {
+            mint: self.lp_token_mint.to_account_info(),
+            from: self.user_lp_token_account.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), burn_accounts);
+        burn(cpi_ctx, amount)?;
+        Ok(())
+    }
+}
@@ -1,5 +1,10 @@
+// `#[program]` generates the IDL instructions with the deprecated `AccountInfo::realloc`
+// in Anchor 0.31, and an `allow` on the module does not reach them.
+#![allow(deprecated)]
+
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod helpers;
 pub mod instructions;
 pub mod state;
@@ -7,6 +12,7 @@ pub mod state;
 use anchor_lang::prelude::*;
 
 pub use constants::*;
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -18,34 +24,171 @@ pub mod anchor_amm {
     use super::*;
    
     
-    /// Arguments used to initialize a new liquidity pool.
-    /// - `seed`: Unique seed used to derive the config PDA.
-    /// - `fee`: Fee in basis points (e.g. 30 = 0.3%) applied on swaps.
-    /// - `authority`: Optional admin override. If `None`, the initializer becomes authority.
-    ///
-    /// This struct is passed to the `initialize` instruction to configure the pool.
-    #[derive(AnchorSerialize,AnchorDeserialize)]
-    pub struct InitArgs{
-        pub seed: u64,
-        pub fee: u16,
-        pub authority: Option<Pubkey>
+    pub fn initialize(ctx: Context<Initialize>,args: InitArgs)-> Result<()>{
+        ctx.accounts.initialize(args, &ctx.bumps)
+        
+    }
 
+    pub fn initialize_factory(ctx: Context<InitializeFactory>, authority: Pubkey) -> Result<()> {
+        ctx.accounts.initialize_factory(authority, &ctx.bumps)
     }
 
-    pub fn initialize(ctx: Context<Initialize>,args: InitArgs)-> Result<()>{
-        ctx.accounts.initialize(args.seed,args.fee,args.authority, &ctx.bumps)
-        
+    pub fn transfer_factory_authority(
+        ctx: Context<UpdateFactory>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.transfer_factory_authority(new_authority)
+    }
+
+    pub fn create_pool(ctx: Context<CreatePool>, args: InitArgs) -> Result<()> {
+        ctx.accounts.create_pool(args, &ctx.bumps)
+    }
+
+    pub fn deposit(ctx: Context<Deposit>,lp_claim_amount:u64,max_x:u64,max_y:u64,expires_at:Option<i64>)->Result<()>{
+        ctx.accounts.deposit(lp_claim_amount, max_x, max_y, expires_at)
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, lp_burn_amount:u64, min_x:u64,min_y:u64,expires_at:Option<i64>)->Result<()>{
+        ctx.accounts.withdraw(lp_burn_amount, min_x, min_y, expires_at)
+    }
+
+    pub fn swap(ctx: Context<Swap>, is_token_x:bool, amount_in:u64,min_amount_out:u64,expires_at:Option<i64>)-> Result<()>{
+        ctx.accounts.swap_tokens(is_token_x, amount_in, min_amount_out, expires_at)
+    }
+
+    pub fn swap_exact_out(ctx: Context<Swap>, is_token_x:bool, amount_out:u64, max_amount_in:u64, expires_at:Option<i64>)-> Result<()>{
+        ctx.accounts.swap_tokens_exact_out(is_token_x, amount_out, max_amount_in, expires_at)
+    }
+
+    pub fn zap_deposit(ctx: Context<Zap>, is_token_x: bool, amount_in: u64, min_lp_amount: u64, expires_at: Option<i64>) -> Result<()> {
+        ctx.accounts.zap_deposit(is_token_x, amount_in, min_lp_amount, expires_at)
+    }
+
+    pub fn zap_withdraw(ctx: Context<Zap>, is_token_x: bool, lp_amount: u64, min_amount_out: u64, expires_at: Option<i64>) -> Result<()> {
+        ctx.accounts.zap_withdraw(is_token_x, lp_amount, min_amount_out, expires_at)
+    }
+
+    pub fn route_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts
+            .route_swap(amount_in, min_amount_out, expires_at, ctx.remaining_accounts)
+    }
+
+    pub fn flash_borrow(ctx: Context<FlashLoan>, is_token_x: bool, amount: u64) -> Result<()> {
+        ctx.accounts.flash_borrow(is_token_x, amount)
+    }
+
+    pub fn flash_repay(ctx: Context<FlashLoan>) -> Result<()> {
+        ctx.accounts.flash_repay()
+    }
+
+    pub fn initialize_farm(ctx: Context<InitializeFarm>, reward_rate: u64, end_timestamp: i64) -> Result<()> {
+        ctx.accounts.initialize_farm(reward_rate, end_timestamp, &ctx.bumps)
+    }
+
+    pub fn update_farm(ctx: Context<UpdateFarm>, reward_rate: u64, end_timestamp: i64) -> Result<()> {
+        ctx.accounts.update_farm(reward_rate, end_timestamp)
+    }
+
+    pub fn fund_farm(ctx: Context<FundFarm>, amount: u64) -> Result<()> {
+        ctx.accounts.fund_farm(amount)
+    }
+
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        ctx.accounts.stake(amount, &ctx.bumps)
+    }
+
+    pub fn unstake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        ctx.accounts.unstake(amount, &ctx.bumps)
+    }
+
+    pub fn harvest(ctx: Context<Stake>) -> Result<()> {
+        ctx.accounts.harvest(&ctx.bumps)
+    }
+
+    pub fn place_limit_order(
+        ctx: Context<PlaceLimitOrder>,
+        seed: u64,
+        amount_in: u64,
+        min_amount_out: u64,
+        expires_at: i64,
+        keeper_tip: u64,
+    ) -> Result<()> {
+        ctx.accounts.place_limit_order(seed, amount_in, min_amount_out, expires_at, keeper_tip, &ctx.bumps)
+    }
+
+    pub fn execute_limit_order(ctx: Context<ExecuteLimitOrder>) -> Result<()> {
+        ctx.accounts.execute_limit_order()
+    }
+
+    pub fn cancel_limit_order(ctx: Context<CancelLimitOrder>) -> Result<()> {
+        ctx.accounts.cancel_limit_order()
+    }
+
+    pub fn read_twap(ctx: Context<ReadTwap>, window: u32) -> Result<TwapPrice> {
+        ctx.accounts.read_twap(window)
+    }
+
+    pub fn lock(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.lock()
+    }
+
+    pub fn unlock(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.unlock()
+    }
+
+    pub fn update_fee(ctx: Context<Update>, fee: u16) -> Result<()> {
+        ctx.accounts.update_fee(fee)
+    }
+
+    pub fn update_protocol_fee(ctx: Context<Update>, protocol_fee: u16) -> Result<()> {
+        ctx.accounts.update_protocol_fee(protocol_fee)
+    }
+
+    pub fn claim_protocol_fees(ctx: Context<ClaimProtocolFees>) -> Result<()> {
+        ctx.accounts.claim_protocol_fees()
+    }
+
+    pub fn ramp_amp(ctx: Context<Update>, target_amp: u64, ramp_stop_timestamp: i64) -> Result<()> {
+        ctx.accounts.ramp_amp(target_amp, ramp_stop_timestamp)
+    }
+
+    pub fn stop_ramp_amp(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.stop_ramp_amp()
+    }
+
+    pub fn set_dynamic_fee(
+        ctx: Context<Update>,
+        min_fee: u16,
+        max_fee: u16,
+        volatility_factor: u16,
+        decay_period: u32,
+    ) -> Result<()> {
+        ctx.accounts
+            .set_dynamic_fee(min_fee, max_fee, volatility_factor, decay_period)
+    }
+
+    pub fn disable_dynamic_fee(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.disable_dynamic_fee()
+    }
+
+    pub fn update_max_price_impact(ctx: Context<Update>, max_price_impact_bps: u16) -> Result<()> {
+        ctx.accounts.update_max_price_impact(max_price_impact_bps)
     }
 
-    pub fn deposit(ctx: Context<Deposit>,lp_claim_amount:u64,max_x:u64,max_y:u64)->Result<()>{
-        ctx.accounts.deposit(lp_claim_amount, max_x, max_y)
+    pub fn update_lp_metadata_uri(ctx: Context<UpdateLpMetadata>, uri: String) -> Result<()> {
+        ctx.accounts.update_lp_metadata_uri(uri)
     }
 
-    pub fn withdraw(ctx: Context<Withdraw>, lp_burn_amount:u64, min_x:u64,min_y:u64)->Result<()>{
-        ctx.accounts.withdraw(lp_burn_amount, min_x, min_y)
+    pub fn transfer_authority(ctx: Context<Update>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.transfer_authority(new_authority)
     }
 
-    pub fn swap(ctx: Context<Swap>, is_token_x:bool, amount_in:u64,min_amount_out:u64)-> Result<()>{
-        ctx.accounts.swap_tokens(is_token_x, amount_in, min_amount_out)
+    pub fn renounce_authority(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.renounce_authority()
     }
 }
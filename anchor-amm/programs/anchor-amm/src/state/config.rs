@@ -0,0 +1,428 @@
+use anchor_lang::prelude::*;
+
+use constant_product_curve::{ConstantProduct, LiquidityPair};
+
+use crate::{
+    error::AmmError,
+    helpers::{
+        curve::{self, proportional_amount, SwapQuote},
+        stable_swap,
+    },
+    Observation, Oracle, BASIS_POINTS,
+};
+
+/// Pricing invariant of a pool, chosen at initialization.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CurveType {
+    /// `x * y = k`, for uncorrelated pairs.
+    ConstantProduct,
+    /// Curve's StableSwap invariant, for pegged pairs. Uses the amplification coefficient.
+    StableSwap,
+}
+
+/// Loan taken by `flash_borrow`, cleared by the `flash_repay` later in the same transaction.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PendingFlashLoan {
+    pub is_token_x: bool,
+    pub amount: u64,
+    pub fee: u64,
+    /// Balance of the lent vault before the loan. It must reach `vault_amount + fee` on repayment.
+    pub vault_amount: u64,
+}
+
+/// Swap fee that follows recent price volatility, set by the pool authority.
+///
+/// The effective fee is `min_fee` plus `volatility * volatility_factor / BASIS_POINTS`,
+/// capped at `max_fee`. Every swap adds the price move it caused to `volatility`, which
+/// decays linearly to zero over `decay_period` seconds without trading.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DynamicFee {
+    /// Fee charged in a calm market, in basis points.
+    pub min_fee: u16,
+    /// Highest fee charged whatever the volatility, in basis points.
+    pub max_fee: u16,
+    /// Fee added per basis point of volatility, in hundredths of a basis point
+    /// (`BASIS_POINTS` adds one basis point of fee per basis point of volatility).
+    pub volatility_factor: u16,
+    /// Seconds for the volatility to decay from any level back to zero.
+    pub decay_period: u32,
+    /// Accumulated price moves of recent swaps, in basis points, as of `last_update_timestamp`.
+    pub volatility: u64,
+    pub last_update_timestamp: i64,
+}
+
+impl DynamicFee {
+    /// Volatility left at `now` once the linear decay is applied.
+    pub fn decayed_volatility(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.last_update_timestamp).max(0) as u128;
+        let decay_period = self.decay_period as u128;
+        if elapsed >= decay_period {
+            return 0;
+        }
+        (self.volatility as u128 * (decay_period - elapsed) / decay_period) as u64
+    }
+
+    /// Effective swap fee at `now`, in basis points.
+    pub fn fee(&self, now: i64) -> u16 {
+        let variable_fee = self.decayed_volatility(now) as u128
+            * self.volatility_factor as u128
+            / BASIS_POINTS as u128;
+        (self.min_fee as u128 + variable_fee).min(self.max_fee as u128) as u16
+    }
+
+    /// Adds a price move, in basis points, to the decayed volatility.
+    pub fn record_price_change(&mut self, price_change_bps: u64, now: i64) {
+        self.volatility = self
+            .decayed_volatility(now)
+            .saturating_add(price_change_bps);
+        self.last_update_timestamp = now;
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub seed: u64,
+    pub authority: Option<Pubkey>,
+    pub token_x_mint: Pubkey,
+    pub token_y_mint: Pubkey,
+    /// Swap fee in basis points. Not used while `dynamic_fee` is set.
+    pub fee: u16,
+    /// Volatility based swap fee replacing `fee` when set.
+    pub dynamic_fee: Option<DynamicFee>,
+    /// Largest move of the spot price a single swap may cause, in basis points.
+    /// Zero disables the check.
+    pub max_price_impact_bps: u16,
+    pub curve_type: CurveType,
+    /// Amplification coefficient at `ramp_start_timestamp`. Only used by StableSwap pools.
+    pub initial_amp: u64,
+    /// Amplification coefficient reached at `ramp_stop_timestamp`.
+    pub target_amp: u64,
+    pub ramp_start_timestamp: i64,
+    pub ramp_stop_timestamp: i64,
+    /// Share of every swap fee kept for the protocol, in basis points of the fee.
+    pub protocol_fee: u16,
+    /// Token X owed to the protocol. Held in the X vault but not part of the reserves.
+    pub protocol_fees_x: u64,
+    /// Token Y owed to the protocol. Held in the Y vault but not part of the reserves.
+    pub protocol_fees_y: u64,
+    pub locked: bool,
+    /// Outstanding flash loan. The pool stays locked while it is set.
+    pub flash_loan: Option<PendingFlashLoan>,
+    /// Sum of the Q64.64 price of token X in token Y, weighted by seconds.
+    pub price_x_cumulative: u128,
+    /// Sum of the Q64.64 price of token Y in token X, weighted by seconds.
+    pub price_y_cumulative: u128,
+    /// Unix timestamp of the last price accumulator update.
+    pub last_update_timestamp: i64,
+    pub config_bump: u8,
+    pub lp_bump: u8,
+    pub oracle_bump: u8,
+}
+
+impl Config {
+    /// Returns the pool reserves backing the LP supply, i.e. the vault balances
+    /// minus the protocol fees that have not been claimed yet.
+    pub fn reserves(&self, vault_x_amount: u64, vault_y_amount: u64) -> Result<(u64, u64)> {
+        let x = vault_x_amount
+            .checked_sub(self.protocol_fees_x)
+            .ok_or(AmmError::Underflow)?;
+        let y = vault_y_amount
+            .checked_sub(self.protocol_fees_y)
+            .ok_or(AmmError::Underflow)?;
+        Ok((x, y))
+    }
+
+    /// Books the protocol's cut of a swap fee charged in token X or token Y.
+    /// Returns the amount set aside for the protocol.
+    pub fn accrue_protocol_fee(&mut self, is_token_x: bool, swap_fee: u64) -> Result<u64> {
+        let protocol_fee = self.protocol_fee_amount(swap_fee)?;
+
+        let accrued = match is_token_x {
+            true => &mut self.protocol_fees_x,
+            false => &mut self.protocol_fees_y,
+        };
+        *accrued = accrued
+            .checked_add(protocol_fee)
+            .ok_or(AmmError::Overflow)?;
+        Ok(protocol_fee)
+    }
+
+    /// Protocol's cut of a swap fee, rounded down.
+    pub fn protocol_fee_amount(&self, swap_fee: u64) -> Result<u64> {
+        Ok((swap_fee as u128)
+            .checked_mul(self.protocol_fee as u128)
+            .ok_or(AmmError::Overflow)?
+            .checked_div(BASIS_POINTS as u128)
+            .ok_or(AmmError::Overflow)? as u64)
+    }
+
+    /// Amplification coefficient at `now`, linearly interpolated while a ramp is running.
+    pub fn current_amp(&self, now: i64) -> u64 {
+        if now >= self.ramp_stop_timestamp || self.ramp_stop_timestamp <= self.ramp_start_timestamp
+        {
+            return self.target_amp;
+        }
+        let elapsed = now.saturating_sub(self.ramp_start_timestamp).max(0) as u128;
+        let duration = (self.ramp_stop_timestamp - self.ramp_start_timestamp) as u128;
+        let (initial, target) = (self.initial_amp as u128, self.target_amp as u128);
+
+        let amp = match target >= initial {
+            true => initial + (target - initial) * elapsed / duration,
+            false => initial - (initial - target) * elapsed / duration,
+        };
+        amp as u64
+    }
+
+    /// Swap fee charged at `now`, in basis points: the dynamic fee when enabled,
+    /// `fee` otherwise.
+    pub fn swap_fee(&self, now: i64) -> u16 {
+        match self.dynamic_fee {
+            Some(dynamic_fee) => dynamic_fee.fee(now),
+            None => self.fee,
+        }
+    }
+
+    /// Move of the token X spot price caused by a priced swap, in basis points.
+    ///
+    /// `reserve_x` and `reserve_y` are the pre-trade reserves, `quote` the priced swap and
+    /// `protocol_fee` the part of its fee set aside, which leaves the reserves.
+    pub fn swap_price_move_bps(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        is_token_x: bool,
+        quote: &SwapQuote,
+        protocol_fee: u64,
+        now: i64,
+    ) -> Result<u64> {
+        let (reserve_in, reserve_out) = match is_token_x {
+            true => (reserve_x, reserve_y),
+            false => (reserve_y, reserve_x),
+        };
+        let reserve_in_after = reserve_in
+            .checked_add(quote.amount_in)
+            .and_then(|reserve| reserve.checked_sub(protocol_fee))
+            .ok_or(AmmError::Overflow)?;
+        let reserve_out_after = reserve_out
+            .checked_sub(quote.amount_out)
+            .ok_or(AmmError::Underflow)?;
+        let (reserve_x_after, reserve_y_after) = match is_token_x {
+            true => (reserve_in_after, reserve_out_after),
+            false => (reserve_out_after, reserve_in_after),
+        };
+
+        let (price_before, _) = self.spot_prices(reserve_x, reserve_y, now)?;
+        let (price_after, _) = self.spot_prices(reserve_x_after, reserve_y_after, now)?;
+        Ok(curve::price_change_bps(price_before, price_after))
+    }
+
+    /// Checks the price move of a swap against `max_price_impact_bps` and feeds it into
+    /// the dynamic fee volatility. Arguments as in `swap_price_move_bps`.
+    pub fn record_swap(
+        &mut self,
+        reserve_x: u64,
+        reserve_y: u64,
+        is_token_x: bool,
+        quote: &SwapQuote,
+        protocol_fee: u64,
+        now: i64,
+    ) -> Result<()> {
+        if self.max_price_impact_bps == 0 && self.dynamic_fee.is_none() {
+            return Ok(());
+        }
+        let price_move =
+            self.swap_price_move_bps(reserve_x, reserve_y, is_token_x, quote, protocol_fee, now)?;
+
+        require!(
+            self.max_price_impact_bps == 0 || price_move <= self.max_price_impact_bps as u64,
+            AmmError::PriceImpactExceeded
+        );
+        if let Some(dynamic_fee) = self.dynamic_fee.as_mut() {
+            dynamic_fee.record_price_change(price_move, now);
+        }
+        Ok(())
+    }
+
+    /// Prices a swap of `amount_in` (fee included) against the pool curve.
+    pub fn quote_swap(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        lp_supply: u64,
+        is_token_x: bool,
+        amount_in: u64,
+        now: i64,
+    ) -> Result<SwapQuote> {
+        match self.curve_type {
+            CurveType::ConstantProduct => {
+                let pair = match is_token_x {
+                    true => LiquidityPair::X,
+                    false => LiquidityPair::Y,
+                };
+                let fee = self.swap_fee(now);
+                let swap_results =
+                    ConstantProduct::init(reserve_x, reserve_y, lp_supply, fee, None)
+                        .map_err(AmmError::from)?
+                        .swap(pair, amount_in, 0)
+                        .map_err(AmmError::from)?;
+                Ok(SwapQuote {
+                    amount_in: swap_results.deposit,
+                    fee: swap_results.fee,
+                    amount_out: swap_results.withdraw,
+                })
+            }
+            CurveType::StableSwap => {
+                let (reserve_in, reserve_out) = match is_token_x {
+                    true => (reserve_x, reserve_y),
+                    false => (reserve_y, reserve_x),
+                };
+                let fee = curve::fee_amount(amount_in, self.swap_fee(now))?;
+                let amount_out = stable_swap::amount_out(
+                    self.current_amp(now),
+                    reserve_in,
+                    reserve_out,
+                    amount_in - fee,
+                )?;
+                Ok(SwapQuote {
+                    amount_in,
+                    fee,
+                    amount_out,
+                })
+            }
+        }
+    }
+
+    /// Prices a swap paying out exactly `amount_out`. The returned quote holds the
+    /// input the pool needs, fee included, rounded in the pool's favour.
+    pub fn quote_swap_exact_out(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        is_token_x: bool,
+        amount_out: u64,
+        now: i64,
+    ) -> Result<SwapQuote> {
+        let (reserve_in, reserve_out) = match is_token_x {
+            true => (reserve_x, reserve_y),
+            false => (reserve_y, reserve_x),
+        };
+        let fee = self.swap_fee(now);
+        let amount_in = match self.curve_type {
+            CurveType::ConstantProduct => {
+                curve::amount_in_for_exact_out(reserve_in, reserve_out, amount_out, fee)?
+            }
+            CurveType::StableSwap => {
+                let amount_in_after_fee = stable_swap::amount_in_for_exact_out(
+                    self.current_amp(now),
+                    reserve_in,
+                    reserve_out,
+                    amount_out,
+                )?;
+                curve::gross_up_for_fee(amount_in_after_fee, fee)?
+            }
+        };
+        Ok(SwapQuote {
+            amount_in,
+            fee: curve::fee_amount(amount_in, fee)?,
+            amount_out,
+        })
+    }
+
+    /// Token amounts matching `lp_amount` of LP tokens when depositing into a pool
+    /// that already has liquidity.
+    ///
+    /// Liquidity is added in proportion to the reserves whatever the curve, rounded up
+    /// so the depositor never pays less than the share of the pool they receive.
+    pub fn deposit_amounts(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        lp_supply: u64,
+        lp_amount: u64,
+    ) -> Result<(u64, u64)> {
+        require!(
+            lp_supply != 0 && reserve_x != 0 && reserve_y != 0,
+            AmmError::NoLiquidityInPool
+        );
+        Ok((
+            proportional_amount(reserve_x, lp_amount, lp_supply, true)?,
+            proportional_amount(reserve_y, lp_amount, lp_supply, true)?,
+        ))
+    }
+
+    /// Token amounts released by burning `lp_amount` of LP tokens.
+    pub fn withdraw_amounts(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        lp_supply: u64,
+        lp_amount: u64,
+    ) -> Result<(u64, u64)> {
+        match self.curve_type {
+            CurveType::ConstantProduct => {
+                let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
+                    reserve_x, reserve_y, lp_supply, lp_amount, 6,
+                )
+                .map_err(AmmError::from)?;
+                Ok((amounts.x, amounts.y))
+            }
+            CurveType::StableSwap => Ok((
+                proportional_amount(reserve_x, lp_amount, lp_supply, false)?,
+                proportional_amount(reserve_y, lp_amount, lp_supply, false)?,
+            )),
+        }
+    }
+
+    /// Spot prices of token X in token Y and of token Y in token X, as Q64.64 numbers.
+    pub fn spot_prices(&self, reserve_x: u64, reserve_y: u64, now: i64) -> Result<(u128, u128)> {
+        match self.curve_type {
+            CurveType::ConstantProduct => Ok((
+                curve::spot_price_q64(reserve_x, reserve_y)?,
+                curve::spot_price_q64(reserve_y, reserve_x)?,
+            )),
+            CurveType::StableSwap => {
+                let amp = self.current_amp(now);
+                Ok((
+                    stable_swap::spot_price_q64(amp, reserve_x, reserve_y)?,
+                    stable_swap::spot_price_q64(amp, reserve_y, reserve_x)?,
+                ))
+            }
+        }
+    }
+
+    /// Accumulates the spot prices over the time elapsed since the last update and
+    /// records a new observation in the oracle, at most one per `OBSERVATION_INTERVAL`.
+    /// Must run before the reserves change, so the accumulators only ever see prices
+    /// that were live for a whole interval.
+    pub fn update_price_accumulators(
+        &mut self,
+        oracle: &mut Oracle,
+        reserve_x: u64,
+        reserve_y: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let elapsed = now.saturating_sub(self.last_update_timestamp);
+        if elapsed <= 0 {
+            return Ok(());
+        }
+
+        if reserve_x != 0 && reserve_y != 0 {
+            let (price_x, price_y) = self.spot_prices(reserve_x, reserve_y, now)?;
+            self.price_x_cumulative = self
+                .price_x_cumulative
+                .wrapping_add(price_x.wrapping_mul(elapsed as u128));
+            self.price_y_cumulative = self
+                .price_y_cumulative
+                .wrapping_add(price_y.wrapping_mul(elapsed as u128));
+        }
+        self.last_update_timestamp = now;
+
+        oracle.write(Observation {
+            timestamp: now,
+            price_x_cumulative: self.price_x_cumulative,
+            price_y_cumulative: self.price_y_cumulative,
+        });
+        Ok(())
+    }
+}
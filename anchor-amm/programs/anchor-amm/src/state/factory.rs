@@ -0,0 +1,73 @@
+use anchor_lang::{prelude::*, solana_program::hash::hashv};
+
+use crate::CANONICAL_SEED_FLAG;
+
+/// Settings shared by every canonical pool.
+/// - PDA derived from seed `[b"factory"]`
+///
+/// `create_pool` makes `authority` the authority of each new canonical pool, so those
+/// pools can still be locked, have their fees tuned and pay a protocol fee. Moving it
+/// only affects pools created afterwards; existing ones keep their own `Config.authority`.
+#[account]
+#[derive(InitSpace)]
+pub struct Factory {
+    pub authority: Pubkey,
+    pub bump: u8,
+}
+
+/// Marker of the canonical pool for a mint pair and fee tier.
+/// - PDA derived from seed `[b"pool", token_x_mint, token_y_mint, fee.to_le_bytes()]`,
+///   with `token_x_mint < token_y_mint`
+///
+/// Only one can exist per pair and tier, which is what makes the pool canonical.
+#[account]
+#[derive(InitSpace)]
+pub struct PoolKey {
+    pub config: Pubkey,
+    pub token_x_mint: Pubkey,
+    pub token_y_mint: Pubkey,
+    pub fee: u16,
+    pub bump: u8,
+}
+
+impl PoolKey {
+    /// Config seed of the canonical pool of a sorted mint pair and fee tier, so the
+    /// config PDA and its vaults follow from the pair and the tier alone.
+    /// Always has `CANONICAL_SEED_FLAG` set.
+    pub fn pool_seed(token_x_mint: &Pubkey, token_y_mint: &Pubkey, fee: u16) -> u64 {
+        let hash = hashv(&[
+            b"pool",
+            token_x_mint.as_ref(),
+            token_y_mint.as_ref(),
+            &fee.to_le_bytes(),
+        ]);
+        let mut seed = [0u8; 8];
+        seed.copy_from_slice(&hash.to_bytes()[..8]);
+        u64::from_le_bytes(seed) | CANONICAL_SEED_FLAG
+    }
+}
+
+/// Registry of the canonical pools listing a mint on either side.
+/// - PDA derived from seed `[b"registry", mint]`
+///
+/// Fixed size: each pool gets its own `RegistryEntry`, numbered from 0 to
+/// `pool_count - 1`.
+#[account]
+#[derive(InitSpace)]
+pub struct MintRegistry {
+    pub mint: Pubkey,
+    /// Number of pools registered, and index of the next entry.
+    pub pool_count: u64,
+    pub bump: u8,
+}
+
+/// One canonical pool of a mint.
+/// - PDA derived from seed `[b"registry", mint, index.to_le_bytes()]`
+#[account]
+#[derive(InitSpace)]
+pub struct RegistryEntry {
+    pub mint: Pubkey,
+    pub index: u64,
+    pub config: Pubkey,
+    pub bump: u8,
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, helpers::stable_swap::U256};
+
+/// Liquidity mining program paying `reward_mint` tokens to the LPs staking the pool LP token.
+/// - PDA derived from seed `[b"farm", config, reward_mint]`
+///
+/// Rewards are streamed at `reward_rate` per second until `end_timestamp` and shared pro
+/// rata by stake through `reward_per_share`. Nothing accrues while no LP tokens are staked.
+#[account]
+#[derive(InitSpace)]
+pub struct Farm {
+    pub config: Pubkey,
+    pub lp_token_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    /// Reward tokens distributed per second across all stakers.
+    pub reward_rate: u64,
+    /// Unix timestamp after which no more rewards accrue.
+    pub end_timestamp: i64,
+    /// Unix timestamp `reward_per_share` was last brought up to date.
+    pub last_update_timestamp: i64,
+    /// Rewards earned by one staked LP token since the farm started, as a Q64.64 number.
+    pub reward_per_share: u128,
+    /// LP tokens held in the farm vault.
+    pub total_staked: u64,
+    pub bump: u8,
+}
+
+impl Farm {
+    /// Adds the rewards streamed since the last update, up to `now` or the end of the
+    /// farm, to `reward_per_share`.
+    pub fn accrue_rewards(&mut self, now: i64) -> Result<()> {
+        let until = now.min(self.end_timestamp);
+        if until <= self.last_update_timestamp {
+            return Ok(());
+        }
+
+        if self.total_staked > 0 {
+            let rewards = (until - self.last_update_timestamp) as u128 * self.reward_rate as u128;
+            let increase = (U256::from(rewards) << 64) / U256::from(self.total_staked);
+            require!(increase <= U256::from(u128::MAX), AmmError::Overflow);
+            self.reward_per_share = self
+                .reward_per_share
+                .checked_add(increase.as_u128())
+                .ok_or(AmmError::Overflow)?;
+        }
+        self.last_update_timestamp = until;
+        Ok(())
+    }
+}
+
+/// LP tokens staked in a farm by one owner.
+/// - PDA derived from seed `[b"stake", farm, owner]`
+#[account]
+#[derive(InitSpace)]
+pub struct StakePosition {
+    pub farm: Pubkey,
+    pub owner: Pubkey,
+    /// LP tokens staked.
+    pub amount: u64,
+    /// `farm.reward_per_share` when the rewards of the position were last settled.
+    pub reward_per_share_paid: u128,
+    /// Rewards earned and not harvested yet.
+    pub rewards_owed: u64,
+    pub bump: u8,
+}
+
+impl StakePosition {
+    /// Moves the rewards earned since the last settlement into `rewards_owed`.
+    /// `reward_per_share` must be up to date, see `Farm::accrue_rewards`.
+    pub fn settle_rewards(&mut self, reward_per_share: u128) -> Result<()> {
+        let earned = (U256::from(self.amount)
+            * U256::from(reward_per_share - self.reward_per_share_paid))
+            >> 64;
+        require!(earned <= U256::from(u64::MAX), AmmError::Overflow);
+        self.rewards_owed = self
+            .rewards_owed
+            .checked_add(earned.as_u64())
+            .ok_or(AmmError::Overflow)?;
+        self.reward_per_share_paid = reward_per_share;
+        Ok(())
+    }
+}
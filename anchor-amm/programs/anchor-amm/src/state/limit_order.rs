@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+/// Swap waiting for the pool price to reach a limit, executed by any keeper.
+/// - PDA derived from seed `[b"order", config, owner, seed.to_le_bytes()]`
+///
+/// The input tokens are escrowed in the associated token account of the order for
+/// `token_in_mint`. The account also holds `keeper_tip` lamports on top of its rent.
+#[account]
+#[derive(InitSpace)]
+pub struct LimitOrder {
+    pub owner: Pubkey,
+    pub config: Pubkey,
+    pub seed: u64,
+    pub token_in_mint: Pubkey,
+    pub token_out_mint: Pubkey,
+    /// `true` when the order sells token X for token Y.
+    pub is_token_x: bool,
+    /// Input tokens held in escrow.
+    pub amount_in: u64,
+    /// Least output accepted for the whole escrow. The limit price is
+    /// `min_amount_out / amount_in`.
+    pub min_amount_out: u64,
+    /// Lamports paid to the keeper executing the order.
+    pub keeper_tip: u64,
+    /// Unix timestamp after which the order can no longer be executed.
+    pub expires_at: i64,
+    pub bump: u8,
+}
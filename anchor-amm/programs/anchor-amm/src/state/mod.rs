@@ -1,13 +1,11 @@
-use anchor_lang::prelude::*;
-#[account]
-#[derive(InitSpace)]
-pub struct Config {
-    pub seed: u64,
-    pub authority: Option<Pubkey>,
-    pub token_x_mint: Pubkey,
-    pub token_y_mint: Pubkey,
-    pub fee: u16,
-    pub locked: bool,
-    pub config_bump: u8,
-    pub lp_bump: u8,
-}
+pub mod config;
+pub mod factory;
+pub mod farm;
+pub mod limit_order;
+pub mod oracle;
+
+pub use config::*;
+pub use factory::*;
+pub use farm::*;
+pub use limit_order::*;
+pub use oracle::*;
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, OBSERVATION_COUNT, OBSERVATION_INTERVAL};
+
+/// Snapshot of the pool price accumulators at a point in time.
+///
+/// Accumulators are sums of Q64.64 spot prices weighted by the seconds they were
+/// live. They wrap on overflow, so differences must be taken with `wrapping_sub`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Default, Debug)]
+pub struct Observation {
+    pub timestamp: i64,
+    /// Cumulative price of token X in token Y.
+    pub price_x_cumulative: u128,
+    /// Cumulative price of token Y in token X.
+    pub price_y_cumulative: u128,
+}
+
+impl Observation {
+    /// Time-weighted average prices between an older observation and this one.
+    pub fn twap_since(&self, older: &Observation) -> Result<TwapPrice> {
+        let elapsed = self.timestamp.saturating_sub(older.timestamp);
+        require!(elapsed > 0, AmmError::InvalidObservationWindow);
+
+        Ok(TwapPrice {
+            price_x: self
+                .price_x_cumulative
+                .wrapping_sub(older.price_x_cumulative)
+                / elapsed as u128,
+            price_y: self
+                .price_y_cumulative
+                .wrapping_sub(older.price_y_cumulative)
+                / elapsed as u128,
+            elapsed,
+        })
+    }
+}
+
+/// Time-weighted average prices returned by `read_twap`, as Q64.64 fixed point numbers.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct TwapPrice {
+    /// Average price of token X in token Y.
+    pub price_x: u128,
+    /// Average price of token Y in token X.
+    pub price_y: u128,
+    /// Length of the averaging window, in seconds.
+    pub elapsed: i64,
+}
+
+/// Ring buffer of price observations for a pool.
+/// - PDA derived from seed `[b"oracle", config.key()]`
+#[account]
+#[derive(InitSpace)]
+pub struct Oracle {
+    pub config: Pubkey,
+    /// Slot of `observations` holding the most recent observation.
+    pub index: u16,
+    pub observations: [Observation; OBSERVATION_COUNT],
+    pub bump: u8,
+}
+
+impl Oracle {
+    /// Stores `observation`, overwriting the oldest one once the buffer is full.
+    ///
+    /// Skipped until `OBSERVATION_INTERVAL` seconds have passed since the latest
+    /// observation. The accumulators keep counting in between, so nothing is lost: the
+    /// next observation covers the whole interval.
+    pub fn write(&mut self, observation: Observation) {
+        if observation.timestamp - self.latest().timestamp < OBSERVATION_INTERVAL {
+            return;
+        }
+        self.index = (self.index + 1) % OBSERVATION_COUNT as u16;
+        self.observations[self.index as usize] = observation;
+    }
+
+    /// Most recent observation.
+    pub fn latest(&self) -> &Observation {
+        &self.observations[self.index as usize]
+    }
+
+    /// Most recent observation taken at or before `timestamp`, if the buffer still holds one.
+    pub fn observation_at_or_before(&self, timestamp: i64) -> Option<&Observation> {
+        (0..OBSERVATION_COUNT)
+            .map(|i| {
+                let slot = (self.index as usize + OBSERVATION_COUNT - i) % OBSERVATION_COUNT;
+                &self.observations[slot]
+            })
+            .take_while(|observation| observation.timestamp != 0)
+            .find(|observation| observation.timestamp <= timestamp)
+    }
+}
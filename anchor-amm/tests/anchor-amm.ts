@@ -59,7 +59,10 @@ describe("anchor-amm", () => {
      const tx= await program.methods.initialize({
         authority:null,
         seed,
-        fee
+        fee,
+        protocolFee: 0,
+        curveType: { constantProduct: {} },
+        amp: new anchor.BN(0)
       }).accountsPartial({
         admin: admin.publicKey,
         tokenXMint: mintX,
@@ -67,8 +70,10 @@ describe("anchor-amm", () => {
         lpTokenMint: mintLp,
         poolTokenXVault: vaultX,
         poolTokenYVault: vaultY,
-        config: configPda
-        
+        config: configPda,
+        tokenXProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenYProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID
       }).rpc()
       console.log(`https://explorer.solana.com/tx/${tx}?cluster=devnet`);
       
@@ -97,12 +102,13 @@ describe("anchor-amm", () => {
 
   it("Deposit liquidity into the pool ", async () => {
 
-    const lpToBeMinted= new anchor.BN(1_000_000)
+    // First deposit: sqrt(500_000 * 500_000) minus the 1_000 locked LP tokens.
+    const lpToBeMinted= new anchor.BN(499_000)
     const maxX= new anchor.BN(500_000)
     const maxY= new anchor.BN(500_000);
     userAtaLp= (await getOrCreateAssociatedTokenAccount(connection,admin.payer, mintLp, admin.publicKey)).address;
 
-    const tx= await program.methods.deposit(lpToBeMinted, maxX,maxY).accountsPartial({
+    const tx= await program.methods.deposit(lpToBeMinted, maxX,maxY,null).accountsPartial({
       depositor: admin.publicKey,
       depositorTokenXAccount: userAtaX,
       depositorTokenYAccount: userAtaY,
@@ -111,6 +117,8 @@ describe("anchor-amm", () => {
       poolTokenXVault: vaultX,
       poolTokenYVault: vaultY,
       lpTokenMint: mintLp,
+      tokenXProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      tokenYProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
       tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID
     }) .rpc()
     console.log(`https://explorer.solana.com/tx/${tx}?cluster=devnet`);
@@ -2,3 +2,12 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+/// Longest lock of a voter escrow, 4 years. Tokens locked this long vote with full weight.
+#[constant]
+pub const MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60;
+
+/// Longest voting window of a proposal, 30 days. Votes keep escrows locked until the
+/// window ends, so this bounds how long a single vote can hold tokens.
+#[constant]
+pub const MAX_VOTING_DURATION: i64 = 30 * 24 * 60 * 60;
@@ -1,7 +1,27 @@
 use anchor_lang::prelude::*;
 
 #[error_code]
-pub enum ErrorCode {
-    #[msg("Custom error message")]
-    CustomError,
+pub enum DaoError {
+    #[msg("Voter has no voting power.")]
+    NoVotingPower,
+    #[msg("Arithmetic overflow.")]
+    Overflow,
+    #[msg("Approval threshold must be between 1 and 10000 basis points.")]
+    InvalidApprovalThreshold,
+    #[msg("Voting must end after it starts, in the future and within the maximum voting duration.")]
+    InvalidVotingWindow,
+    #[msg("Proposal is not open for voting.")]
+    VotingClosed,
+    #[msg("Voting period has not ended yet.")]
+    VotingNotEnded,
+    #[msg("Proposal is not in the required state.")]
+    InvalidProposalState,
+    #[msg("Token account is not of the DAO governance mint.")]
+    InvalidGovernanceMint,
+    #[msg("Lock must end in the future, within the maximum lock duration and not before the current lock.")]
+    InvalidLockDuration,
+    #[msg("Escrowed tokens are still locked.")]
+    TokensLocked,
+    #[msg("Not enough tokens in escrow.")]
+    InsufficientBalance,
 }
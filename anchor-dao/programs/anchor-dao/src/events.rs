@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::{ProposalState, VoteChoice};
+
+/// Emitted when a proposal is finalized, cancelled or executed.
+#[event]
+pub struct ProposalStateEvent {
+    pub proposal: Pubkey,
+    pub proposal_index: u64,
+    /// State the proposal moved to.
+    pub state: ProposalState,
+}
+
+/// Emitted when a vote is cast.
+#[event]
+pub struct VoteEvent {
+    pub proposal: Pubkey,
+    pub proposal_index: u64,
+    pub voter: Pubkey,
+    pub vote_type: VoteChoice,
+    /// Credits added to the tally of `vote_type`.
+    pub vote_credits: u64,
+}
@@ -1,15 +1,28 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
 
-use crate::{Dao, Proposal, Vote};
+use crate::{
+    error::DaoError, Dao, Proposal, ProposalState, Vote, VoteChoice, VoteEvent, VoterEscrow,
+};
 
 
 /// Casts a vote on a proposal within a DAO using quadratic voting.
 /// 
-/// Quadratic voting means that the influence of a vote is the square root of the number of tokens held by the voter.
+/// Quadratic voting means that the influence of a vote is the square root of the voter's
+/// escrowed tokens, weighted by their remaining lock time (see `VoterEscrow::vote_credits`).
 /// This allows token holders with fewer tokens to still have meaningful input while limiting the domination of large holders.
+///
+/// Tokens held outside the escrow do not count. Voting keeps the escrowed ones locked until
+/// the proposal's voting ends.
+///
+/// The proposal is addressed by its `index`, so votes can target any open proposal of the DAO.
+///
+/// ### PDA Derivation:
+/// - Proposal: `[b"proposal", dao_account_pda_pubkey, proposal_index_bytes]`
+/// - Vote:     `[b"vote", proposal_account_pubkey, voter_pubkey]`
+/// - Escrow:   `[b"escrow", dao_account_pda_pubkey, voter_pubkey]`
 
 #[derive(Accounts)]
+#[instruction(proposal_index: u64)]
 pub struct CastVote<'info> {
       /// The voter casting the vote.
     /// Must sign the transaction and pay for the `vote_account` rent.
@@ -20,7 +33,7 @@ pub struct CastVote<'info> {
         seeds=[
             b"dao", 
             dao_account_pda.authority.as_ref(),
-            dao_account_pda.name.as_bytes().as_ref()
+            dao_account_pda.name.as_bytes()
         ],
         bump=dao_account_pda.bump
     )]
@@ -31,7 +44,7 @@ pub struct CastVote<'info> {
         seeds=[
             b"proposal", 
             dao_account_pda.key().as_ref(), 
-            dao_account_pda.proposal_count.to_le_bytes().as_ref()
+            proposal_index.to_le_bytes().as_ref()
         ],
         bump=proposal_account.bump
     )]
@@ -46,12 +59,14 @@ pub struct CastVote<'info> {
     )]
     pub vote_account: Account<'info, Vote>,
 
+    /// Governance tokens locked by the voter, see `LockTokens`.
     #[account(
-        token::authority=voter
+        mut,
+        seeds=[b"escrow", dao_account_pda.key().as_ref(), voter.key().as_ref()],
+        bump=escrow_account.bump
     )]
-    pub voter_token_account: Account<'info,TokenAccount>,
+    pub escrow_account: Account<'info, VoterEscrow>,
 
-    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
@@ -59,17 +74,45 @@ pub struct CastVote<'info> {
 impl <'info> CastVote<'info> {
     /// Casts the vote using quadratic voting.
     ///
-    /// - `vote_type`: A `u8` indicating the type of vote (e.g., 0 = no, 1 = yes).
+    /// - `proposal_index`: Index of the proposal in the DAO.
+    /// - `vote_type`: Yes, No or Abstain.
     /// - `bumps`: The bump seeds for the involved PDAs (only `vote_account` is used here).
     ///
-    /// Calculates the square root of the token amount in the voter’s account to determine voting credits.
-    /// This is a simplified approach to implement quadratic voti
-    pub fn cast_vote(&mut self, vote_type:u8, bumps: &CastVoteBumps)->Result<()> {
-        let voting_credits=(self.voter_token_account.amount as f64).sqrt() as u64;
+    /// The voting credits are the integer square root of the time-weighted escrow balance,
+    /// see `VoterEscrow::vote_credits`.
+    ///
+    /// The credits are added to the proposal tally of `vote_type`. Fails with `NoVotingPower`
+    /// when the voter has no locked tokens, and with `VotingClosed` outside the voting window.
+    pub fn cast_vote(
+        &mut self,
+        proposal_index: u64,
+        vote_type: VoteChoice,
+        bumps: &CastVoteBumps,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(self.proposal_account.is_voting_open(now), DaoError::VotingClosed);
+
+        let voting_credits = self.escrow_account.vote_credits(now);
+        require!(voting_credits > 0, DaoError::NoVotingPower);
+
+        let escrow = &mut self.escrow_account;
+        escrow.voted_until_timestamp = escrow
+            .voted_until_timestamp
+            .max(self.proposal_account.voting_end_timestamp);
+
+        self.proposal_account.tally(vote_type, voting_credits)?;
+        self.proposal_account.state = ProposalState::Voting;
 
         self.vote_account.set_inner(
            Vote { voter: self.voter.key(), vote_type, vote_credits: voting_credits, bump: bumps.vote_account }
         );
+        emit!(VoteEvent {
+            proposal: self.proposal_account.key(),
+            proposal_index,
+            voter: self.voter.key(),
+            vote_type,
+            vote_credits: voting_credits,
+        });
         Ok(())
     }
 }
\ No newline at end of file
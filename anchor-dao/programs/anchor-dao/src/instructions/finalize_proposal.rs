@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::DaoError, Dao, Proposal, ProposalState, ProposalStateEvent};
+
+/// Accounts context for finalizing a proposal once its voting period has ended.
+///
+/// Permissionless: anyone can finalize, the outcome only depends on the tally and on the
+/// `quorum` and `approval_threshold_bps` of the DAO.
+///
+/// ### PDA Derivation:
+/// - DAO:      `[b"dao", dao_authority_pubkey, dao_name_bytes]`
+/// - Proposal: `[b"proposal", dao_account_pda_pubkey, proposal_index_bytes]`
+
+#[derive(Accounts)]
+#[instruction(proposal_index: u64)]
+pub struct FinalizeProposal<'info> {
+    #[account(
+        seeds=[
+            b"dao",
+            dao_account_pda.authority.as_ref(),
+            dao_account_pda.name.as_bytes()
+        ],
+        bump=dao_account_pda.bump
+    )]
+    pub dao_account_pda: Account<'info, Dao>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"proposal",
+            dao_account_pda.key().as_ref(),
+            proposal_index.to_le_bytes().as_ref()
+        ],
+        bump=proposal_account.bump
+    )]
+    pub proposal_account: Account<'info, Proposal>,
+}
+
+impl<'info> FinalizeProposal<'info> {
+    /// Moves a `Draft` or `Voting` proposal whose voting period has ended to `Succeeded`
+    /// or `Defeated`.
+    pub fn finalize_proposal(&mut self, proposal_index: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut self.proposal_account;
+        require!(
+            matches!(proposal.state, ProposalState::Draft | ProposalState::Voting),
+            DaoError::InvalidProposalState
+        );
+        require!(now >= proposal.voting_end_timestamp, DaoError::VotingNotEnded);
+
+        proposal.finalize(
+            self.dao_account_pda.quorum,
+            self.dao_account_pda.approval_threshold_bps,
+        );
+        emit!(ProposalStateEvent {
+            proposal: proposal.key(),
+            proposal_index,
+            state: proposal.state,
+        });
+        Ok(())
+    }
+}
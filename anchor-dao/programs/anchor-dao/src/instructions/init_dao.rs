@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
 
-use crate::Dao;
+use crate::{error::DaoError, Dao};
 
 /// Accounts context for initializing a new DAO.
 ///
@@ -12,6 +13,8 @@ use crate::Dao;
 /// `seeds = [b"dao", authority_pubkey, name_bytes]`
 ///
 /// This ensures the same authority can create multiple DAOs as long as the name differs.
+///
+/// Voting power in the DAO comes only from tokens of `governance_mint`.
 
 #[derive(Accounts)]
 #[instruction(name:String)]
@@ -23,11 +26,13 @@ pub struct InitializeDao<'info> {
         init,
         payer=dao_authority,
         space= 8+Dao::INIT_SPACE,
-        seeds=[b"dao",dao_authority.key().as_ref(),name.as_bytes().as_ref()],
+        seeds=[b"dao",dao_authority.key().as_ref(),name.as_bytes()],
         bump
     )]
     pub dao_account_pda: Account<'info, Dao>,
 
+    pub governance_mint: Account<'info, Mint>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -37,13 +42,29 @@ impl<'info> InitializeDao<'info> {
     /// Sets:
     /// - `name` of the DAO
     /// - `authority` to the signer
+    /// - `governance_mint` to the mint of the voting token
     /// - `proposal_count` to zero
+    /// - `quorum` and `approval_threshold_bps`, used to finalize proposals
     /// - `bump` to the PDA bump seed
-    pub fn init_dao(&mut self, name: String, bumps: &InitializeDaoBumps) -> Result<()> {
+    pub fn init_dao(
+        &mut self,
+        name: String,
+        quorum: u64,
+        approval_threshold_bps: u16,
+        bumps: &InitializeDaoBumps,
+    ) -> Result<()> {
+        require!(
+            (1..=10_000).contains(&approval_threshold_bps),
+            DaoError::InvalidApprovalThreshold
+        );
+
         self.dao_account_pda.set_inner(Dao {
             name,
             authority: self.dao_authority.key(),
+            governance_mint: self.governance_mint.key(),
             proposal_count: 0,
+            quorum,
+            approval_threshold_bps,
             bump: bumps.dao_account_pda,
         });
         Ok(())
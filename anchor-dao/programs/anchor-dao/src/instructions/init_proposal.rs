@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{Dao, Proposal};
+use crate::{error::DaoError, Dao, Proposal, ProposalState, MAX_VOTING_DURATION};
 
 /// Accounts context for initializing a new proposal under a DAO.
 ///
@@ -10,6 +10,9 @@ use crate::{Dao, Proposal};
 /// ### PDA Derivation:
 /// - DAO:     `[b"dao", dao_authority_pubkey, dao_name_bytes]`
 /// - Proposal: `[b"proposal", dao_account_pda_pubkey, proposal_count_bytes]`
+///
+/// The proposal takes the current `proposal_count` as its `index`, so proposals are
+/// numbered from zero and their addresses never change.
 
 #[derive(Accounts)]
 pub struct InitializeProposal<'info> {
@@ -18,7 +21,7 @@ pub struct InitializeProposal<'info> {
 
     #[account(
         mut,
-        seeds=[b"dao", dao_authority.key().as_ref(), dao_account_pda.name.as_bytes().as_ref()],
+        seeds=[b"dao", dao_authority.key().as_ref(), dao_account_pda.name.as_bytes()],
         bump=dao_account_pda.bump
     )]
     pub dao_account_pda: Account<'info, Dao>,
@@ -42,18 +45,36 @@ pub struct InitializeProposal<'info> {
 impl<'info> InitializeProposal<'info> {
     /// Initializes a new proposal for the DAO.
     ///
-    /// Increments the DAO's `proposal_count`, then creates a new `Proposal` account.
+    /// Increments the DAO's `proposal_count`, then creates a new `Proposal` account in
+    /// the `Draft` state, open for votes from `voting_start_timestamp` until
+    /// `voting_end_timestamp`. The window lasts at most `MAX_VOTING_DURATION`.
     pub fn init_proposal(
         &mut self,
         metadata: String,
+        voting_start_timestamp: i64,
+        voting_end_timestamp: i64,
         bumps: &InitializeProposalBumps,
     ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            voting_start_timestamp < voting_end_timestamp
+                && now < voting_end_timestamp
+                && voting_end_timestamp - voting_start_timestamp <= MAX_VOTING_DURATION,
+            DaoError::InvalidVotingWindow
+        );
+
+        let index = self.dao_account_pda.proposal_count;
         self.dao_account_pda.proposal_count += 1;
         self.proposal_account.set_inner(Proposal {
             metadata,
             authority: self.dao_authority.key(),
+            index,
             yes_vote_count: 0,
             no_vote_count: 0,
+            abstain_vote_count: 0,
+            voting_start_timestamp,
+            voting_end_timestamp,
+            state: ProposalState::Draft,
             bump: bumps.proposal_account,
         });
         Ok(())
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+};
+
+use crate::{error::DaoError, Dao, VoterEscrow, MAX_LOCK_DURATION};
+
+/// Accounts context for locking governance tokens in a voter escrow.
+///
+/// Creates the escrow of the owner on first use. The tokens are moved to the associated
+/// token account of the escrow, where they give voting power until the lock ends.
+///
+/// ### PDA Derivation:
+/// - DAO:    `[b"dao", dao_authority_pubkey, dao_name_bytes]`
+/// - Escrow: `[b"escrow", dao_account_pda_pubkey, owner_pubkey]`
+
+#[derive(Accounts)]
+pub struct LockTokens<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds=[b"dao", dao_account_pda.authority.as_ref(), dao_account_pda.name.as_bytes()],
+        bump=dao_account_pda.bump
+    )]
+    pub dao_account_pda: Account<'info, Dao>,
+
+    #[account(address = dao_account_pda.governance_mint @ DaoError::InvalidGovernanceMint)]
+    pub governance_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer=owner,
+        space= 8+VoterEscrow::INIT_SPACE,
+        seeds=[b"escrow", dao_account_pda.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub escrow_account: Account<'info, VoterEscrow>,
+
+    #[account(
+        init_if_needed,
+        payer=owner,
+        associated_token::mint=governance_mint,
+        associated_token::authority=escrow_account
+    )]
+    pub escrow_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint=governance_mint,
+        token::authority=owner
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> LockTokens<'info> {
+    /// Deposits `amount` governance tokens and locks the escrow until `lock_end_timestamp`.
+    ///
+    /// `amount` may be zero to only extend the lock. The lock can never be shortened and
+    /// lasts at most `MAX_LOCK_DURATION`.
+    pub fn lock_tokens(
+        &mut self,
+        amount: u64,
+        lock_end_timestamp: i64,
+        bumps: &LockTokensBumps,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let escrow = &mut self.escrow_account;
+        require!(
+            lock_end_timestamp > now
+                && lock_end_timestamp - now <= MAX_LOCK_DURATION
+                && lock_end_timestamp >= escrow.lock_end_timestamp,
+            DaoError::InvalidLockDuration
+        );
+
+        escrow.dao = self.dao_account_pda.key();
+        escrow.owner = self.owner.key();
+        escrow.amount = escrow.amount.checked_add(amount).ok_or(DaoError::Overflow)?;
+        escrow.lock_end_timestamp = lock_end_timestamp;
+        escrow.bump = bumps.escrow_account;
+
+        if amount > 0 {
+            let cpi_accounts = TransferChecked {
+                from: self.owner_token_account.to_account_info(),
+                mint: self.governance_mint.to_account_info(),
+                to: self.escrow_vault.to_account_info(),
+                authority: self.owner.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+            transfer_checked(cpi_ctx, amount, self.governance_mint.decimals)?;
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,15 @@
 pub mod init_dao;
 pub mod init_proposal;
 pub mod cast_vote;
+pub mod finalize_proposal;
+pub mod update_proposal;
+pub mod lock_tokens;
+pub mod withdraw_tokens;
 
 pub use init_dao::*;
 pub use init_proposal::*;
 pub use cast_vote::*;
+pub use finalize_proposal::*;
+pub use update_proposal::*;
+pub use lock_tokens::*;
+pub use withdraw_tokens::*;
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::DaoError, Dao, Proposal, ProposalState, ProposalStateEvent};
+
+/// Accounts context for the DAO authority to move a proposal to a terminal state.
+///
+/// ### PDA Derivation:
+/// - DAO:      `[b"dao", dao_authority_pubkey, dao_name_bytes]`
+/// - Proposal: `[b"proposal", dao_account_pda_pubkey, proposal_index_bytes]`
+
+#[derive(Accounts)]
+#[instruction(proposal_index: u64)]
+pub struct UpdateProposal<'info> {
+    pub dao_authority: Signer<'info>,
+
+    #[account(
+        seeds=[b"dao", dao_authority.key().as_ref(), dao_account_pda.name.as_bytes()],
+        bump=dao_account_pda.bump
+    )]
+    pub dao_account_pda: Account<'info, Dao>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"proposal",
+            dao_account_pda.key().as_ref(),
+            proposal_index.to_le_bytes().as_ref()
+        ],
+        bump=proposal_account.bump
+    )]
+    pub proposal_account: Account<'info, Proposal>,
+}
+
+impl<'info> UpdateProposal<'info> {
+    /// Withdraws a `Draft` or `Voting` proposal. Votes are rejected from then on.
+    ///
+    /// Fails with `VotingClosed` once `voting_end_timestamp` has passed: the outcome is
+    /// settled by then and only `finalize_proposal` can record it, so the authority
+    /// cannot veto a proposal that passed before anyone finalized it.
+    ///
+    /// Escrows that already voted on it stay locked until its `voting_end_timestamp`:
+    /// an escrow only records the latest voting end it backs, not which proposals it
+    /// voted on, so the lock cannot be lifted per proposal. `MAX_VOTING_DURATION` bounds
+    /// the wait.
+    pub fn cancel_proposal(&mut self, proposal_index: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut self.proposal_account;
+        require!(
+            matches!(proposal.state, ProposalState::Draft | ProposalState::Voting),
+            DaoError::InvalidProposalState
+        );
+        require!(now < proposal.voting_end_timestamp, DaoError::VotingClosed);
+
+        proposal.state = ProposalState::Cancelled;
+        emit!(ProposalStateEvent {
+            proposal: proposal.key(),
+            proposal_index,
+            state: proposal.state,
+        });
+        Ok(())
+    }
+
+    /// Records that a `Succeeded` proposal has been carried out.
+    pub fn execute_proposal(&mut self, proposal_index: u64) -> Result<()> {
+        let proposal = &mut self.proposal_account;
+        require!(
+            proposal.state == ProposalState::Succeeded,
+            DaoError::InvalidProposalState
+        );
+
+        proposal.state = ProposalState::Executed;
+        emit!(ProposalStateEvent {
+            proposal: proposal.key(),
+            proposal_index,
+            state: proposal.state,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked};
+
+use crate::{error::DaoError, Dao, VoterEscrow};
+
+/// Accounts context for withdrawing governance tokens from a voter escrow.
+///
+/// ### PDA Derivation:
+/// - DAO:    `[b"dao", dao_authority_pubkey, dao_name_bytes]`
+/// - Escrow: `[b"escrow", dao_account_pda_pubkey, owner_pubkey]`
+
+#[derive(Accounts)]
+pub struct WithdrawTokens<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds=[b"dao", dao_account_pda.authority.as_ref(), dao_account_pda.name.as_bytes()],
+        bump=dao_account_pda.bump
+    )]
+    pub dao_account_pda: Account<'info, Dao>,
+
+    #[account(address = dao_account_pda.governance_mint @ DaoError::InvalidGovernanceMint)]
+    pub governance_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds=[b"escrow", dao_account_pda.key().as_ref(), owner.key().as_ref()],
+        bump=escrow_account.bump
+    )]
+    pub escrow_account: Account<'info, VoterEscrow>,
+
+    #[account(
+        mut,
+        associated_token::mint=governance_mint,
+        associated_token::authority=escrow_account
+    )]
+    pub escrow_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint=governance_mint,
+        token::authority=owner
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WithdrawTokens<'info> {
+    /// Returns `amount` escrowed tokens to the owner.
+    ///
+    /// Fails with `TokensLocked` until the lock has ended and the voting periods of all the
+    /// proposals voted on with the escrow are over.
+    pub fn withdraw_tokens(&mut self, amount: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(self.escrow_account.is_unlocked(now), DaoError::TokensLocked);
+        require!(amount <= self.escrow_account.amount, DaoError::InsufficientBalance);
+
+        self.escrow_account.amount -= amount;
+
+        let cpi_accounts = TransferChecked {
+            from: self.escrow_vault.to_account_info(),
+            mint: self.governance_mint.to_account_info(),
+            to: self.owner_token_account.to_account_info(),
+            authority: self.escrow_account.to_account_info(),
+        };
+        let dao_key = self.dao_account_pda.key();
+        let owner_key = self.owner.key();
+        let signer_seeds = &[
+            b"escrow",
+            dao_key.as_ref(),
+            owner_key.as_ref(),
+            &[self.escrow_account.bump],
+        ];
+        let signer_seeds = &[&signer_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        transfer_checked(cpi_ctx, amount, self.governance_mint.decimals)
+    }
+}
@@ -1,11 +1,17 @@
+// `#[program]` generates the IDL instructions with the deprecated `AccountInfo::realloc`
+// in Anchor 0.31, and an `allow` on the module does not reach them.
+#![allow(deprecated)]
+
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
 
 use anchor_lang::prelude::*;
 
 pub use constants::*;
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -15,15 +21,60 @@ declare_id!("42WyLUdYjx1t9czGdEFV5y5ECSDnnggfTLk3shbJYLdK");
 pub mod anchor_dao {
     use super::*;
 
-    pub fn initialize_dao(ctx: Context<InitializeDao>, name: String) -> Result<()> {
-        ctx.accounts.init_dao(name, &ctx.bumps)
+    pub fn initialize_dao(
+        ctx: Context<InitializeDao>,
+        name: String,
+        quorum: u64,
+        approval_threshold_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_dao(name, quorum, approval_threshold_bps, &ctx.bumps)
+    }
+
+    pub fn initialize_proposal(
+        ctx: Context<InitializeProposal>,
+        metadata: String,
+        voting_start_timestamp: i64,
+        voting_end_timestamp: i64,
+    ) -> Result<()> {
+        ctx.accounts.init_proposal(
+            metadata,
+            voting_start_timestamp,
+            voting_end_timestamp,
+            &ctx.bumps,
+        )
+    }
+
+    pub fn cast_vote(
+        ctx: Context<CastVote>,
+        proposal_index: u64,
+        vote_type: VoteChoice,
+    ) -> Result<()> {
+        ctx.accounts.cast_vote(proposal_index, vote_type, &ctx.bumps)
+    }
+
+    pub fn lock_tokens(
+        ctx: Context<LockTokens>,
+        amount: u64,
+        lock_end_timestamp: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .lock_tokens(amount, lock_end_timestamp, &ctx.bumps)
+    }
+
+    pub fn withdraw_tokens(ctx: Context<WithdrawTokens>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw_tokens(amount)
+    }
+
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>, proposal_index: u64) -> Result<()> {
+        ctx.accounts.finalize_proposal(proposal_index)
     }
 
-    pub fn initialize_proposal(ctx: Context<InitializeProposal>, metadata: String) -> Result<()> {
-        ctx.accounts.init_proposal(metadata, &ctx.bumps)
+    pub fn cancel_proposal(ctx: Context<UpdateProposal>, proposal_index: u64) -> Result<()> {
+        ctx.accounts.cancel_proposal(proposal_index)
     }
 
-    pub fn cast_vote(ctx: Context<CastVote>, vote_type: u8) -> Result<()> {
-        ctx.accounts.cast_vote(vote_type, &ctx.bumps)
+    pub fn execute_proposal(ctx: Context<UpdateProposal>, proposal_index: u64) -> Result<()> {
+        ctx.accounts.execute_proposal(proposal_index)
     }
 }
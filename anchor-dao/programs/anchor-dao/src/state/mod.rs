@@ -1,12 +1,20 @@
 use anchor_lang::prelude::*;
 
+use crate::{error::DaoError, MAX_LOCK_DURATION};
+
 #[account]
 #[derive(Debug, InitSpace)]
 pub struct Dao {
     #[max_len(300)]
     pub name: String,
     pub authority: Pubkey,
+    /// Mint of the token granting voting power.
+    pub governance_mint: Pubkey,
     pub proposal_count: u64,
+    /// Minimum vote credits (yes, no and abstain) for a proposal to pass.
+    pub quorum: u64,
+    /// Minimum share of yes votes among yes and no votes for a proposal to pass, in basis points.
+    pub approval_threshold_bps: u16,
     pub bump: u8,
 }
 
@@ -16,16 +24,302 @@ pub struct Proposal {
     #[max_len(300)]
     pub metadata: String,
     pub authority: Pubkey,
+    /// Position of the proposal in its DAO, used in its PDA seeds.
+    pub index: u64,
     pub yes_vote_count: u64,
     pub no_vote_count: u64,
+    pub abstain_vote_count: u64,
+    /// Unix timestamp from which votes are accepted.
+    pub voting_start_timestamp: i64,
+    /// Unix timestamp from which votes are rejected and the proposal can be finalized.
+    pub voting_end_timestamp: i64,
+    pub state: ProposalState,
     pub bump: u8,
 }
 
+impl Proposal {
+    /// Whether votes are accepted at `now`.
+    pub fn is_voting_open(&self, now: i64) -> bool {
+        matches!(self.state, ProposalState::Draft | ProposalState::Voting)
+            && (self.voting_start_timestamp..self.voting_end_timestamp).contains(&now)
+    }
+
+    /// Resolves the vote: `Succeeded` when `quorum` is reached and the yes votes make
+    /// at least `approval_threshold_bps` of the yes and no votes, `Defeated` otherwise.
+    pub fn finalize(&mut self, quorum: u64, approval_threshold_bps: u16) {
+        let yes = self.yes_vote_count as u128;
+        let no = self.no_vote_count as u128;
+        let total = yes + no + self.abstain_vote_count as u128;
+
+        let approved = yes * 10_000 >= (yes + no) * approval_threshold_bps as u128;
+        self.state = match total >= quorum as u128 && yes > 0 && approved {
+            true => ProposalState::Succeeded,
+            false => ProposalState::Defeated,
+        };
+    }
+
+    /// Adds `credits` to the tally of `choice`.
+    pub fn tally(&mut self, choice: VoteChoice, credits: u64) -> Result<()> {
+        let count = match choice {
+            VoteChoice::Yes => &mut self.yes_vote_count,
+            VoteChoice::No => &mut self.no_vote_count,
+            VoteChoice::Abstain => &mut self.abstain_vote_count,
+        };
+        *count = count.checked_add(credits).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+}
+
+/// Lifecycle of a proposal.
+///
+/// `Draft` until the first vote, `Voting` until finalized after the voting window into
+/// `Succeeded` or `Defeated`. The DAO authority can cancel a `Draft` or `Voting` proposal
+/// and marks `Succeeded` proposals `Executed` once carried out.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ProposalState {
+    Draft,
+    Voting,
+    Succeeded,
+    Defeated,
+    Cancelled,
+    Executed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum VoteChoice {
+    Yes,
+    No,
+    Abstain,
+}
+
+/// Governance tokens a voter locked in a DAO to vote.
+/// - PDA derived from seed `[b"escrow", dao, owner]`
+///
+/// The tokens sit in the associated token account of the escrow and can only be withdrawn
+/// once both the lock and the voting periods of the proposals voted on have ended, so the
+/// same tokens cannot vote twice from different wallets.
+///
+/// Only the latest voting end is kept, so a vote on a proposal cancelled afterwards still
+/// holds the tokens until that proposal's voting window would have ended.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct VoterEscrow {
+    pub dao: Pubkey,
+    pub owner: Pubkey,
+    /// Governance tokens held in escrow.
+    pub amount: u64,
+    /// Unix timestamp until which the tokens are locked.
+    pub lock_end_timestamp: i64,
+    /// Latest voting end of the proposals voted on with this escrow.
+    pub voted_until_timestamp: i64,
+    pub bump: u8,
+}
+
+impl VoterEscrow {
+    /// Escrowed amount weighted by the remaining lock time, ve-style: the full amount for a
+    /// `MAX_LOCK_DURATION` lock, decaying linearly to zero at `lock_end_timestamp`.
+    pub fn voting_power(&self, now: i64) -> u64 {
+        let remaining = (self.lock_end_timestamp - now).clamp(0, MAX_LOCK_DURATION);
+        (self.amount as u128 * remaining as u128 / MAX_LOCK_DURATION as u128) as u64
+    }
+
+    /// Quadratic vote credits at `now`: the square root of `voting_power`, rounded down.
+    pub fn vote_credits(&self, now: i64) -> u64 {
+        sqrt(self.voting_power(now))
+    }
+
+    /// Whether the tokens can be withdrawn at `now`.
+    pub fn is_unlocked(&self, now: i64) -> bool {
+        now >= self.lock_end_timestamp && now >= self.voted_until_timestamp
+    }
+}
+
+/// Integer square root, rounded down.
+fn sqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+    // Newton's method from an initial guess above the root.
+    let mut x = 1u64 << ((64 - value.leading_zeros()).div_ceil(2));
+    loop {
+        let y = (x + value / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
 #[account]
 #[derive(Debug, InitSpace)]
 pub struct Vote {
     pub voter: Pubkey,
-    pub vote_type: u8,
+    pub vote_type: VoteChoice,
     pub vote_credits: u64,
     pub bump: u8
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proposal() -> Proposal {
+        Proposal {
+            metadata: String::new(),
+            authority: Pubkey::default(),
+            index: 0,
+            yes_vote_count: 0,
+            no_vote_count: 0,
+            abstain_vote_count: 0,
+            voting_start_timestamp: 100,
+            voting_end_timestamp: 200,
+            state: ProposalState::Draft,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn tally_adds_to_the_chosen_count() {
+        let mut proposal = proposal();
+        proposal.tally(VoteChoice::Yes, 3).unwrap();
+        proposal.tally(VoteChoice::No, 5).unwrap();
+        proposal.tally(VoteChoice::Abstain, 7).unwrap();
+        proposal.tally(VoteChoice::Yes, 11).unwrap();
+
+        assert_eq!(proposal.yes_vote_count, 14);
+        assert_eq!(proposal.no_vote_count, 5);
+        assert_eq!(proposal.abstain_vote_count, 7);
+    }
+
+    #[test]
+    fn voting_is_open_within_the_window_only() {
+        let mut proposal = proposal();
+        assert!(!proposal.is_voting_open(99));
+        assert!(proposal.is_voting_open(100));
+        assert!(proposal.is_voting_open(199));
+        assert!(!proposal.is_voting_open(200));
+
+        for state in [
+            ProposalState::Succeeded,
+            ProposalState::Defeated,
+            ProposalState::Cancelled,
+            ProposalState::Executed,
+        ] {
+            proposal.state = state;
+            assert!(!proposal.is_voting_open(150), "{state:?}");
+        }
+    }
+
+    fn finalized(
+        yes: u64,
+        no: u64,
+        abstain: u64,
+        quorum: u64,
+        threshold_bps: u16,
+    ) -> ProposalState {
+        let mut proposal = proposal();
+        proposal.yes_vote_count = yes;
+        proposal.no_vote_count = no;
+        proposal.abstain_vote_count = abstain;
+        proposal.finalize(quorum, threshold_bps);
+        proposal.state
+    }
+
+    #[test]
+    fn finalize_requires_the_quorum() {
+        // Abstentions count towards the quorum.
+        assert_eq!(finalized(60, 0, 40, 100, 5_000), ProposalState::Succeeded);
+        assert_eq!(finalized(60, 0, 39, 100, 5_000), ProposalState::Defeated);
+        assert_eq!(finalized(0, 0, 0, 0, 5_000), ProposalState::Defeated);
+    }
+
+    #[test]
+    fn finalize_requires_the_approval_threshold() {
+        assert_eq!(finalized(60, 40, 0, 1, 6_000), ProposalState::Succeeded);
+        assert_eq!(finalized(59, 41, 0, 1, 6_000), ProposalState::Defeated);
+        assert_eq!(finalized(1, 0, 0, 1, 10_000), ProposalState::Succeeded);
+        assert_eq!(finalized(u64::MAX, 1, 0, 1, 10_000), ProposalState::Defeated);
+        // Tallies near `u64::MAX` do not overflow.
+        assert_eq!(
+            finalized(u64::MAX, u64::MAX, u64::MAX, u64::MAX, 5_000),
+            ProposalState::Succeeded
+        );
+    }
+
+    #[test]
+    fn tally_rejects_overflow() {
+        let mut proposal = proposal();
+        proposal.tally(VoteChoice::No, u64::MAX).unwrap();
+        assert!(proposal.tally(VoteChoice::No, 1).is_err());
+        assert_eq!(proposal.no_vote_count, u64::MAX);
+    }
+
+    fn escrow(amount: u64, lock_end_timestamp: i64) -> VoterEscrow {
+        VoterEscrow {
+            dao: Pubkey::default(),
+            owner: Pubkey::default(),
+            amount,
+            lock_end_timestamp,
+            voted_until_timestamp: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn voting_power_decays_linearly_to_the_lock_end() {
+        let amount = 1_000_000;
+        let escrow = escrow(amount, MAX_LOCK_DURATION);
+
+        assert_eq!(escrow.voting_power(0), amount);
+        assert_eq!(escrow.voting_power(MAX_LOCK_DURATION / 4), amount * 3 / 4);
+        assert_eq!(escrow.voting_power(MAX_LOCK_DURATION / 2), amount / 2);
+        assert_eq!(escrow.voting_power(MAX_LOCK_DURATION - 1), 0);
+        assert_eq!(escrow.voting_power(MAX_LOCK_DURATION), 0);
+        assert_eq!(escrow.voting_power(MAX_LOCK_DURATION + 1), 0);
+
+        let mut previous = u64::MAX;
+        for now in (0..=MAX_LOCK_DURATION).step_by(86_400) {
+            let power = escrow.voting_power(now);
+            assert!(power <= previous, "{now}");
+            previous = power;
+        }
+    }
+
+    #[test]
+    fn voting_power_is_capped_at_the_max_lock() {
+        // A lock cannot be longer than `MAX_LOCK_DURATION`, but the weight is capped anyway.
+        let escrow = escrow(u64::MAX, 2 * MAX_LOCK_DURATION);
+        assert_eq!(escrow.voting_power(0), u64::MAX);
+        assert_eq!(escrow.vote_credits(0), u32::MAX as u64);
+    }
+
+    #[test]
+    fn vote_credits_are_the_root_of_the_voting_power() {
+        let escrow = escrow(1_000_000, MAX_LOCK_DURATION);
+        assert_eq!(escrow.vote_credits(0), 1_000);
+        assert_eq!(escrow.vote_credits(MAX_LOCK_DURATION * 3 / 4), 500);
+        assert_eq!(escrow.vote_credits(MAX_LOCK_DURATION), 0);
+    }
+
+    #[test]
+    fn escrow_unlocks_after_the_lock_and_the_votes() {
+        let mut escrow = escrow(1, 100);
+        assert!(!escrow.is_unlocked(99));
+        assert!(escrow.is_unlocked(100));
+
+        escrow.voted_until_timestamp = 200;
+        assert!(!escrow.is_unlocked(100));
+        assert!(!escrow.is_unlocked(199));
+        assert!(escrow.is_unlocked(200));
+    }
+
+    #[test]
+    fn sqrt_rounds_down() {
+        let values = [0, 1, 2, 3, 4, 15, 16, 17, 1 << 32, (1 << 32) - 1, u64::MAX];
+        for value in values {
+            let root = sqrt(value) as u128;
+            assert!(root * root <= value as u128, "{value}");
+            assert!((root + 1) * (root + 1) > value as u128, "{value}");
+        }
+    }
+}
@@ -1,16 +1,672 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { AnchorDao } from "../target/types/anchor_dao";
+import {
+  createMint,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+const { Keypair, PublicKey, LAMPORTS_PER_SOL } = anchor.web3;
+type PublicKey = anchor.web3.PublicKey;
+type Keypair = anchor.web3.Keypair;
 
 describe("anchor-dao", () => {
   // Configure the client to use the local cluster.
   anchor.setProvider(anchor.AnchorProvider.env());
 
   const program = anchor.workspace.anchorDao as Program<AnchorDao>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const connection = provider.connection;
+  const authority = (provider.wallet as anchor.Wallet).payer;
+
+  // 1e9 tokens of 6 decimals, enough for short locks to still give voting power.
+  const LOCK_AMOUNT = 1_000_000_000_000_000;
+  const DAY = 24 * 60 * 60;
+  const MAX_LOCK_DURATION = 4 * 365 * DAY;
+  const MAX_VOTING_DURATION = 30 * DAY;
+
+  // DAO names are unique per run, so the suite also passes against a running validator.
+  const run = Date.now();
+  let daoCount = 0;
+  let governanceMint: PublicKey;
+
+  /// Unix timestamp of the validator clock.
+  const now = async (): Promise<number> =>
+    await connection.getBlockTime(await connection.getSlot());
+
+  const daoAddress = (name: string): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("dao"), authority.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const proposalAddress = (dao: PublicKey, index: number): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("proposal"),
+        dao.toBuffer(),
+        new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const escrowAddress = (dao: PublicKey, owner: PublicKey): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), dao.toBuffer(), owner.toBuffer()],
+      program.programId
+    )[0];
+
+  const voteAddress = (proposal: PublicKey, voter: PublicKey): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("vote"), proposal.toBuffer(), voter.toBuffer()],
+      program.programId
+    )[0];
+
+  /// Creates a DAO under a new name and returns its address.
+  const createDao = async (
+    quorum: number,
+    approvalThresholdBps: number
+  ): Promise<PublicKey> => {
+    const name = `dao-${run}-${daoCount++}`;
+    const dao = daoAddress(name);
+    await program.methods
+      .initializeDao(name, new anchor.BN(quorum), approvalThresholdBps)
+      .accountsPartial({
+        daoAuthority: authority.publicKey,
+        daoAccountPda: dao,
+        governanceMint,
+      })
+      .rpc();
+    return dao;
+  };
+
+  /// Creates the next proposal of `dao`, open from `start` until `end`, and returns its index.
+  const createProposal = async (
+    dao: PublicKey,
+    start: number,
+    end: number
+  ): Promise<number> => {
+    const index = (
+      await program.account.dao.fetch(dao)
+    ).proposalCount.toNumber();
+    await program.methods
+      .initializeProposal(
+        `proposal ${index}`,
+        new anchor.BN(start),
+        new anchor.BN(end)
+      )
+      .accountsPartial({
+        daoAuthority: authority.publicKey,
+        daoAccountPda: dao,
+        proposalAccount: proposalAddress(dao, index),
+      })
+      .rpc();
+    return index;
+  };
+
+  /// A new funded wallet holding `LOCK_AMOUNT` governance tokens.
+  const newWallet = async (): Promise<Keypair> => {
+    const wallet = Keypair.generate();
+    const signature = await connection.requestAirdrop(
+      wallet.publicKey,
+      LAMPORTS_PER_SOL
+    );
+    await connection.confirmTransaction(signature);
+    const tokenAccount = await getOrCreateAssociatedTokenAccount(
+      connection,
+      authority,
+      governanceMint,
+      wallet.publicKey
+    );
+    await mintTo(
+      connection,
+      authority,
+      governanceMint,
+      tokenAccount.address,
+      authority,
+      LOCK_AMOUNT
+    );
+    return wallet;
+  };
+
+  const lockTokens = (
+    dao: PublicKey,
+    owner: Keypair,
+    amount: number,
+    lockEnd: number,
+    mint: PublicKey = governanceMint
+  ) => {
+    const escrow = escrowAddress(dao, owner.publicKey);
+    return program.methods
+      .lockTokens(new anchor.BN(amount), new anchor.BN(lockEnd))
+      .accountsPartial({
+        owner: owner.publicKey,
+        daoAccountPda: dao,
+        governanceMint: mint,
+        escrowAccount: escrow,
+        escrowVault: getAssociatedTokenAddressSync(mint, escrow, true),
+        ownerTokenAccount: getAssociatedTokenAddressSync(
+          mint,
+          owner.publicKey
+        ),
+      })
+      .signers([owner])
+      .rpc();
+  };
+
+  const withdrawTokens = (dao: PublicKey, owner: Keypair, amount: number) => {
+    const escrow = escrowAddress(dao, owner.publicKey);
+    return program.methods
+      .withdrawTokens(new anchor.BN(amount))
+      .accountsPartial({
+        owner: owner.publicKey,
+        daoAccountPda: dao,
+        governanceMint,
+        escrowAccount: escrow,
+        escrowVault: getAssociatedTokenAddressSync(governanceMint, escrow, true),
+        ownerTokenAccount: getAssociatedTokenAddressSync(
+          governanceMint,
+          owner.publicKey
+        ),
+      })
+      .signers([owner])
+      .rpc();
+  };
+
+  const tokenBalance = async (owner: PublicKey): Promise<number> =>
+    Number(
+      (
+        await connection.getTokenAccountBalance(
+          getAssociatedTokenAddressSync(governanceMint, owner)
+        )
+      ).value.amount
+    );
+
+  /// A new wallet with all its tokens locked in `dao` until `lockEnd`.
+  const newVoter = async (dao: PublicKey, lockEnd: number) => {
+    const voter = await newWallet();
+    await lockTokens(dao, voter, LOCK_AMOUNT, lockEnd);
+    return voter;
+  };
+
+  const castVote = (
+    dao: PublicKey,
+    index: number,
+    voter: Keypair,
+    choice: "yes" | "no" | "abstain"
+  ) => {
+    const proposal = proposalAddress(dao, index);
+    return program.methods
+      .castVote(new anchor.BN(index), { [choice]: {} } as any)
+      .accountsPartial({
+        voter: voter.publicKey,
+        daoAccountPda: dao,
+        proposalAccount: proposal,
+        voteAccount: voteAddress(proposal, voter.publicKey),
+        escrowAccount: escrowAddress(dao, voter.publicKey),
+      })
+      .signers([voter])
+      .rpc();
+  };
+
+  const finalizeProposal = (dao: PublicKey, index: number) =>
+    program.methods
+      .finalizeProposal(new anchor.BN(index))
+      .accountsPartial({
+        daoAccountPda: dao,
+        proposalAccount: proposalAddress(dao, index),
+      })
+      .rpc();
+
+  /// Sends `cancel_proposal` or `execute_proposal`, signed by `signer`.
+  const updateProposal = (
+    instruction: "cancelProposal" | "executeProposal",
+    dao: PublicKey,
+    index: number,
+    signer: Keypair = authority
+  ) =>
+    program.methods[instruction](new anchor.BN(index))
+      .accountsPartial({
+        daoAuthority: signer.publicKey,
+        daoAccountPda: dao,
+        proposalAccount: proposalAddress(dao, index),
+      })
+      .signers([signer])
+      .rpc();
+
+  const proposalState = async (dao: PublicKey, index: number) =>
+    (await program.account.proposal.fetch(proposalAddress(dao, index))).state;
+
+  /// Anchor events logged by the confirmed transaction `signature`.
+  const eventsOf = async (signature: string) => {
+    await connection.confirmTransaction(signature, "confirmed");
+    const tx = await connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    return [...parser.parseLogs(tx.meta.logMessages)];
+  };
+
+  /// Waits until the validator clock reaches `timestamp`.
+  const sleepUntil = async (timestamp: number) => {
+    while ((await now()) < timestamp) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+  };
+
+  /// Asserts that `promise` fails with the program error `code`.
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect(err).to.be.instanceOf(anchor.AnchorError);
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  before(async () => {
+    governanceMint = await createMint(
+      connection,
+      authority,
+      authority.publicKey,
+      null,
+      6
+    );
+  });
+
+  describe("voting", () => {
+    it("adds the vote credits to the tally of each choice", async () => {
+      const dao = await createDao(1, 5_000);
+      const start = await now();
+      const index = await createProposal(dao, start - 10, start + 7 * DAY);
+      const proposal = proposalAddress(dao, index);
+
+      const credits: Record<string, number> = {};
+      for (const choice of ["yes", "no", "abstain"] as const) {
+        const voter = await newVoter(dao, start + 30 * DAY);
+        const events = await eventsOf(await castVote(dao, index, voter, choice));
+
+        const vote = await program.account.vote.fetch(
+          voteAddress(proposal, voter.publicKey)
+        );
+        expect(vote.voteType).to.deep.equal({ [choice]: {} });
+        expect(vote.voteCredits.toNumber()).to.be.greaterThan(0);
+        expect(events).to.have.length(1);
+        expect(events[0].name).to.equal("voteEvent");
+        expect(events[0].data.proposal.toBase58()).to.equal(proposal.toBase58());
+        expect(events[0].data.proposalIndex.toNumber()).to.equal(index);
+        expect(events[0].data.voter.toBase58()).to.equal(
+          voter.publicKey.toBase58()
+        );
+        expect(events[0].data.voteType).to.deep.equal({ [choice]: {} });
+        expect(events[0].data.voteCredits.toNumber()).to.equal(
+          vote.voteCredits.toNumber()
+        );
+        credits[choice] = vote.voteCredits.toNumber();
+      }
+
+      const tally = await program.account.proposal.fetch(proposal);
+      expect(tally.yesVoteCount.toNumber()).to.equal(credits.yes);
+      expect(tally.noVoteCount.toNumber()).to.equal(credits.no);
+      expect(tally.abstainVoteCount.toNumber()).to.equal(credits.abstain);
+      expect(tally.state).to.deep.equal({ voting: {} });
+    });
+
+    it("rejects a second vote from the same voter", async () => {
+      const dao = await createDao(1, 5_000);
+      const start = await now();
+      const index = await createProposal(dao, start - 10, start + 7 * DAY);
+      const voter = await newVoter(dao, start + 30 * DAY);
+
+      await castVote(dao, index, voter, "yes");
+      let failed = false;
+      try {
+        await castVote(dao, index, voter, "no");
+      } catch {
+        // The vote account already exists.
+        failed = true;
+      }
+      expect(failed).to.equal(true);
+
+      const tally = await program.account.proposal.fetch(
+        proposalAddress(dao, index)
+      );
+      expect(tally.noVoteCount.toNumber()).to.equal(0);
+    });
+
+    it("votes on any open proposal by its index", async () => {
+      const dao = await createDao(1, 5_000);
+      const start = await now();
+      const first = await createProposal(dao, start - 10, start + 7 * DAY);
+      const second = await createProposal(dao, start - 10, start + 7 * DAY);
+      expect([first, second]).to.deep.equal([0, 1]);
+      const voter = await newVoter(dao, start + 30 * DAY);
+
+      // The older proposal stays votable after a newer one is created.
+      await castVote(dao, second, voter, "no");
+      await castVote(dao, first, voter, "yes");
+
+      const firstTally = await program.account.proposal.fetch(
+        proposalAddress(dao, first)
+      );
+      const secondTally = await program.account.proposal.fetch(
+        proposalAddress(dao, second)
+      );
+      expect(firstTally.index.toNumber()).to.equal(first);
+      expect(firstTally.yesVoteCount.toNumber()).to.be.greaterThan(0);
+      expect(firstTally.noVoteCount.toNumber()).to.equal(0);
+      expect(secondTally.index.toNumber()).to.equal(second);
+      expect(secondTally.noVoteCount.toNumber()).to.be.greaterThan(0);
+      expect(secondTally.yesVoteCount.toNumber()).to.equal(0);
+    });
+
+    it("rejects voters without locked tokens", async () => {
+      const dao = await createDao(1, 5_000);
+      const start = await now();
+      const index = await createProposal(dao, start - 10, start + 7 * DAY);
+      const voter = await newWallet();
+      await lockTokens(dao, voter, 0, start + 30 * DAY);
+
+      await expectError(castVote(dao, index, voter, "yes"), "NoVotingPower");
+    });
+  });
+
+  describe("proposal lifecycle", () => {
+    it("rejects invalid voting windows", async () => {
+      const dao = await createDao(1, 5_000);
+      const start = await now();
+
+      await expectError(
+        createProposal(dao, start + DAY, start + DAY),
+        "InvalidVotingWindow"
+      );
+      await expectError(
+        createProposal(dao, start - 2 * DAY, start - DAY),
+        "InvalidVotingWindow"
+      );
+    });
+
+    it("rejects votes outside the voting window", async () => {
+      const dao = await createDao(1, 5_000);
+      const start = await now();
+      const early = await createProposal(dao, start + DAY, start + 2 * DAY);
+      const ended = await createProposal(dao, start - 10, start + 5);
+      const voter = await newVoter(dao, start + 30 * DAY);
+
+      await expectError(castVote(dao, early, voter, "yes"), "VotingClosed");
+      await sleepUntil(start + 5);
+      await expectError(castVote(dao, ended, voter, "yes"), "VotingClosed");
+    });
+
+    it("rejects finalizing before the voting ends", async () => {
+      const dao = await createDao(1, 5_000);
+      const start = await now();
+      const index = await createProposal(dao, start - 10, start + DAY);
+
+      await expectError(finalizeProposal(dao, index), "VotingNotEnded");
+      expect(await proposalState(dao, index)).to.deep.equal({ draft: {} });
+    });
+
+    it("resolves proposals against the quorum and the approval threshold", async () => {
+      // 60% of the yes and no votes must be yes.
+      const dao = await createDao(1, 6_000);
+      const unreachable = await createDao(Number.MAX_SAFE_INTEGER, 6_000);
+      const lockEnd = (await now()) + 30 * DAY;
+      const yesVoter = await newVoter(dao, lockEnd);
+      const splitYesVoter = await newVoter(dao, lockEnd);
+      const splitNoVoter = await newVoter(dao, lockEnd);
+      const noQuorumVoter = await newVoter(unreachable, lockEnd);
+
+      const start = await now();
+      const end = start + 20;
+      const approved = await createProposal(dao, start - 10, end);
+      await castVote(dao, approved, yesVoter, "yes");
+
+      // Equal voters on both sides make about 50% yes.
+      const split = await createProposal(dao, start - 10, end);
+      await castVote(dao, split, splitYesVoter, "yes");
+      await castVote(dao, split, splitNoVoter, "no");
+
+      const empty = await createProposal(dao, start - 10, end);
+
+      const noQuorum = await createProposal(unreachable, start - 10, end);
+      await castVote(unreachable, noQuorum, noQuorumVoter, "yes");
+
+      await sleepUntil(end);
+      for (const [proposalDao, index] of [
+        [dao, approved],
+        [dao, split],
+        [dao, empty],
+        [unreachable, noQuorum],
+      ] as const) {
+        await finalizeProposal(proposalDao, index);
+      }
+
+      expect(await proposalState(dao, approved)).to.deep.equal({
+        succeeded: {},
+      });
+      expect(await proposalState(dao, split)).to.deep.equal({ defeated: {} });
+      expect(await proposalState(dao, empty)).to.deep.equal({ defeated: {} });
+      expect(await proposalState(unreachable, noQuorum)).to.deep.equal({
+        defeated: {},
+      });
+
+      // Finalized proposals are final.
+      await expectError(
+        finalizeProposal(dao, approved),
+        "InvalidProposalState"
+      );
+      await expectError(
+        updateProposal("cancelProposal", dao, approved),
+        "InvalidProposalState"
+      );
+      await expectError(
+        updateProposal("executeProposal", dao, split),
+        "InvalidProposalState"
+      );
+
+      await updateProposal("executeProposal", dao, approved);
+      expect(await proposalState(dao, approved)).to.deep.equal({
+        executed: {},
+      });
+      await expectError(
+        updateProposal("executeProposal", dao, approved),
+        "InvalidProposalState"
+      );
+    });
+
+    it("cancels open proposals only", async () => {
+      const dao = await createDao(1, 5_000);
+      const voter = await newVoter(dao, (await now()) + 30 * DAY);
+      const stranger = await newWallet();
+      const start = await now();
+      const index = await createProposal(dao, start - 10, start + 20);
+
+      await expectError(
+        updateProposal("executeProposal", dao, index),
+        "InvalidProposalState"
+      );
+      // Only the DAO authority can cancel, the DAO address is derived from it.
+      await expectError(
+        updateProposal("cancelProposal", dao, index, stranger),
+        "ConstraintSeeds"
+      );
+
+      await updateProposal("cancelProposal", dao, index);
+      expect(await proposalState(dao, index)).to.deep.equal({ cancelled: {} });
+
+      await expectError(castVote(dao, index, voter, "yes"), "VotingClosed");
+      await expectError(
+        updateProposal("cancelProposal", dao, index),
+        "InvalidProposalState"
+      );
+      await sleepUntil(start + 20);
+      await expectError(finalizeProposal(dao, index), "InvalidProposalState");
+    });
+
+    it("cannot cancel a proposal once its voting has ended", async () => {
+      const dao = await createDao(1, 5_000);
+      const voter = await newVoter(dao, (await now()) + 30 * DAY);
+      const start = await now();
+      const index = await createProposal(dao, start - 10, start + 20);
+      await castVote(dao, index, voter, "yes");
+
+      // The proposal passed, the authority cannot veto it before it is finalized.
+      await sleepUntil(start + 20);
+      await expectError(
+        updateProposal("cancelProposal", dao, index),
+        "VotingClosed"
+      );
+
+      await finalizeProposal(dao, index);
+      expect(await proposalState(dao, index)).to.deep.equal({
+        succeeded: {},
+      });
+    });
+
+    it("emits the state proposals move to", async () => {
+      const dao = await createDao(1, 5_000);
+      const voter = await newVoter(dao, (await now()) + 30 * DAY);
+      const start = await now();
+      const passed = await createProposal(dao, start - 10, start + 20);
+      const cancelled = await createProposal(dao, start - 10, start + 20);
+      await castVote(dao, passed, voter, "yes");
+
+      const expectStateEvent = async (
+        signature: Promise<string>,
+        index: number,
+        state: string
+      ) => {
+        const events = await eventsOf(await signature);
+        expect(events).to.have.length(1);
+        expect(events[0].name).to.equal("proposalStateEvent");
+        expect(events[0].data.proposal.toBase58()).to.equal(
+          proposalAddress(dao, index).toBase58()
+        );
+        expect(events[0].data.proposalIndex.toNumber()).to.equal(index);
+        expect(events[0].data.state).to.deep.equal({ [state]: {} });
+      };
+
+      await expectStateEvent(
+        updateProposal("cancelProposal", dao, cancelled),
+        cancelled,
+        "cancelled"
+      );
+      await sleepUntil(start + 20);
+      await expectStateEvent(finalizeProposal(dao, passed), passed, "succeeded");
+      await expectStateEvent(
+        updateProposal("executeProposal", dao, passed),
+        passed,
+        "executed"
+      );
+    });
+  });
+
+  describe("voter escrow", () => {
+    it("only locks tokens of the DAO governance mint", async () => {
+      const dao = await createDao(1, 5_000);
+      expect(
+        (await program.account.dao.fetch(dao)).governanceMint.toBase58()
+      ).to.equal(governanceMint.toBase58());
+      const otherMint = await createMint(
+        connection,
+        authority,
+        authority.publicKey,
+        null,
+        6
+      );
+      const owner = await newWallet();
+      const lockEnd = (await now()) + 30 * DAY;
+
+      await expectError(
+        lockTokens(dao, owner, LOCK_AMOUNT, lockEnd, otherMint),
+        "InvalidGovernanceMint"
+      );
+    });
+
+    it("rejects locks in the past, beyond the maximum or shortened", async () => {
+      const dao = await createDao(1, 5_000);
+      const owner = await newWallet();
+      const start = await now();
+
+      await expectError(
+        lockTokens(dao, owner, LOCK_AMOUNT, start - 1),
+        "InvalidLockDuration"
+      );
+      await expectError(
+        lockTokens(dao, owner, LOCK_AMOUNT, start + MAX_LOCK_DURATION + DAY),
+        "InvalidLockDuration"
+      );
+
+      await lockTokens(dao, owner, LOCK_AMOUNT / 2, start + 30 * DAY);
+      await expectError(
+        lockTokens(dao, owner, 0, start + 10 * DAY),
+        "InvalidLockDuration"
+      );
+      // Extending the lock and adding tokens are fine.
+      await lockTokens(dao, owner, LOCK_AMOUNT / 2, start + 60 * DAY);
+      const escrow = await program.account.voterEscrow.fetch(
+        escrowAddress(dao, owner.publicKey)
+      );
+      expect(escrow.amount.toNumber()).to.equal(LOCK_AMOUNT);
+      expect(escrow.lockEndTimestamp.toNumber()).to.equal(start + 60 * DAY);
+    });
+
+    it("keeps tokens locked until the lock ends", async () => {
+      const dao = await createDao(1, 5_000);
+      const lockEnd = (await now()) + 15;
+      const owner = await newVoter(dao, lockEnd);
+      expect(await tokenBalance(owner.publicKey)).to.equal(0);
+
+      await expectError(withdrawTokens(dao, owner, 1), "TokensLocked");
+
+      await sleepUntil(lockEnd);
+      await expectError(
+        withdrawTokens(dao, owner, LOCK_AMOUNT + 1),
+        "InsufficientBalance"
+      );
+      await withdrawTokens(dao, owner, LOCK_AMOUNT);
+      expect(await tokenBalance(owner.publicKey)).to.equal(LOCK_AMOUNT);
+    });
+
+    it("keeps voted tokens locked until the voting ends", async () => {
+      const dao = await createDao(1, 5_000);
+      const lockEnd = (await now()) + 15;
+      const voter = await newVoter(dao, lockEnd);
+      const votingEnd = (await now()) + 30;
+      const index = await createProposal(dao, votingEnd - DAY, votingEnd);
+      await castVote(dao, index, voter, "yes");
+
+      const escrow = await program.account.voterEscrow.fetch(
+        escrowAddress(dao, voter.publicKey)
+      );
+      expect(escrow.votedUntilTimestamp.toNumber()).to.equal(votingEnd);
+
+      // The lock is over but the tokens still back a running vote, so they cannot
+      // vote again from another wallet.
+      await sleepUntil(lockEnd);
+      await expectError(withdrawTokens(dao, voter, 1), "TokensLocked");
+
+      await sleepUntil(votingEnd);
+      await withdrawTokens(dao, voter, LOCK_AMOUNT);
+      expect(await tokenBalance(voter.publicKey)).to.equal(LOCK_AMOUNT);
+    });
+
+    it("caps the voting window, and so how long a vote locks tokens", async () => {
+      const dao = await createDao(1, 5_000);
+      const start = await now();
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+      await expectError(
+        createProposal(dao, start, start + MAX_VOTING_DURATION + 1),
+        "InvalidVotingWindow"
+      );
+      await createProposal(dao, start, start + MAX_VOTING_DURATION);
+    });
   });
 });